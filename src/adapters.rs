@@ -0,0 +1,162 @@
+use std::ops::Range;
+
+use crate::timestamp::Timestamp;
+use crate::subline::SubLine;
+use crate::timeline;
+use crate::utils;
+
+/// Lazy transforms of owned lines, e.g. ```subs.into_iter().shifted(-500).renumbered().collect()```.
+pub trait SubLineIterator: Iterator<Item = SubLine> + Sized {
+    /// Moves lines by ```offset``` miliseconds, clamping at zero.
+    fn shifted(self, offset: i64) -> Shifted<Self> {
+        Shifted {
+            iter: self,
+            offset,
+        }
+    }
+
+    /// Drops lines outside of the ```range``` and cuts the partially overlapping ones.
+    fn clipped(self, range: Range<Timestamp>) -> Clipped<Self> {
+        Clipped {
+            iter: self,
+            range,
+        }
+    }
+
+    /// Removes tags from lines' texts.
+    fn stripped_tags(self) -> StrippedTags<Self> {
+        StrippedTags { iter: self }
+    }
+
+    /// Numbers lines consecutively from 1.
+    fn renumbered(self) -> Renumbered<Self> {
+        Renumbered {
+            iter: self,
+            index: 0,
+        }
+    }
+}
+
+impl<I: Iterator<Item = SubLine>> SubLineIterator for I {}
+
+/// See ```SubLineIterator::shifted```.
+#[derive(Debug, Clone)]
+pub struct Shifted<I> {
+    iter: I,
+    offset: i64,
+}
+
+impl<I: Iterator<Item = SubLine>> Iterator for Shifted<I> {
+    type Item = SubLine;
+
+    fn next(&mut self) -> Option<SubLine> {
+        let mut line = self.iter.next()?;
+        timeline::shift_line(&mut line, self.offset);
+        Some(line)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+/// See ```SubLineIterator::clipped```.
+#[derive(Debug, Clone)]
+pub struct Clipped<I> {
+    iter: I,
+    range: Range<Timestamp>,
+}
+
+impl<I: Iterator<Item = SubLine>> Iterator for Clipped<I> {
+    type Item = SubLine;
+
+    fn next(&mut self) -> Option<SubLine> {
+        loop {
+            let mut line = self.iter.next()?;
+            if line.end <= self.range.start || line.start >= self.range.end {
+                continue;
+            }
+            line.start = line.start.max(self.range.start);
+            line.end = line.end.min(self.range.end);
+            let range = &self.range;
+            line.words.retain(|word| word.end > range.start && word.start < range.end);
+            for word in &mut line.words {
+                word.start = word.start.max(range.start);
+                word.end = word.end.min(range.end);
+            }
+            return Some(line);
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.iter.size_hint().1)
+    }
+}
+
+/// See ```SubLineIterator::stripped_tags```.
+#[derive(Debug, Clone)]
+pub struct StrippedTags<I> {
+    iter: I,
+}
+
+impl<I: Iterator<Item = SubLine>> Iterator for StrippedTags<I> {
+    type Item = SubLine;
+
+    fn next(&mut self) -> Option<SubLine> {
+        let mut line = self.iter.next()?;
+        line.text = utils::strip_tags(&line.text);
+        for word in &mut line.words {
+            word.text = utils::strip_tags(&word.text);
+        }
+        Some(line)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+/// See ```SubLineIterator::renumbered```.
+#[derive(Debug, Clone)]
+pub struct Renumbered<I> {
+    iter: I,
+    index: u32,
+}
+
+impl<I: Iterator<Item = SubLine>> Iterator for Renumbered<I> {
+    type Item = SubLine;
+
+    fn next(&mut self) -> Option<SubLine> {
+        let mut line = self.iter.next()?;
+        self.index += 1;
+        line.index = self.index;
+        Some(line)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod adapters_tests {
+    use super::*;
+    use crate::subtitles::Subtitles;
+
+    #[test]
+    fn pipeline() {
+        let subs = utils::subs_from_texts(&["<i>One</i>", "Two", "Three", "Four"]);
+        let clipped: Subtitles = subs.into_iter()
+            .shifted(-1500)
+            .clipped(Timestamp::new(0, 0, 0, 600)..Timestamp::new(0, 0, 3, 0))
+            .stripped_tags()
+            .renumbered()
+            .collect();
+
+        let lines: Vec<_> = clipped.inner
+            .iter()
+            .map(|line| (line.index, line.start.total_miliseconds(), line.end.total_miliseconds(), &*line.text))
+            .collect();
+        assert_eq!(lines, vec![(1, 600, 1500, "Two"), (2, 2500, 3000, "Three")]);
+    }
+}
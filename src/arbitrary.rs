@@ -0,0 +1,131 @@
+//! ```quickcheck::Arbitrary``` implementations generating valid values:
+//! lines never end before they start and have non-blank text,
+//! ```Subtitles``` are sorted, don't overlap and are numbered from 1.
+
+use quickcheck::{Arbitrary, Gen};
+
+use crate::timestamp::Timestamp;
+use crate::subline::SubLine;
+use crate::subtitles::Subtitles;
+
+/// Upper bound of generated timestamps, 10 hours.
+const MAX_TIME: u64 = 10 * 3_600_000;
+/// Upper bound of generated lines' durations and gaps between them.
+const MAX_DURATION: u64 = 10_000;
+
+static WORDS: &[&str] = &["the", "trial", "will", "be", "<i>soon</i>", "Your", "Grace.", "¿Qué?", "да", "日本語",
+                          "I'm", "-", "♪", "&", "<b>No!</b>", "42"];
+
+fn below(g: &mut Gen, bound: u64) -> u64 {
+    u64::arbitrary(g) % bound
+}
+
+fn text(g: &mut Gen) -> String {
+    let rows = 1 + below(g, 2) as usize;
+    let mut result = Vec::with_capacity(rows);
+    for _ in 0..rows {
+        let words = 1 + below(g, 6) as usize;
+        let row: Vec<&str> = (0..words).map(|_| *g.choose(WORDS).unwrap()).collect();
+        result.push(row.join(" "));
+    }
+    result.join("\r\n")
+}
+
+impl Arbitrary for Timestamp {
+    fn arbitrary(g: &mut Gen) -> Timestamp {
+        Timestamp::from_miliseconds(below(g, MAX_TIME))
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Timestamp>> {
+        Box::new(self.total_miliseconds().shrink().map(Timestamp::from_miliseconds))
+    }
+}
+
+impl Arbitrary for SubLine {
+    fn arbitrary(g: &mut Gen) -> SubLine {
+        let start = below(g, MAX_TIME);
+        let end = start + below(g, MAX_DURATION);
+        SubLine::new(1 + below(g, 1000) as u32,
+                     text(g),
+                     Timestamp::from_miliseconds(start),
+                     Timestamp::from_miliseconds(end))
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = SubLine>> {
+        let line = self.clone();
+        let rows: Vec<String> = self.text.lines().map(|row| row.to_owned()).collect();
+        let shorter = (1..rows.len()).map(move |count| {
+            let mut shorter = line.clone();
+            shorter.text = rows[..count].join("\r\n");
+            shorter
+        });
+
+        let line = self.clone();
+        let earlier = self.start.shrink().map(move |start| {
+            let mut earlier = line.clone();
+            earlier.end = start + line.duration();
+            earlier.start = start;
+            earlier
+        });
+        Box::new(shorter.chain(earlier))
+    }
+}
+
+impl Arbitrary for Subtitles {
+    fn arbitrary(g: &mut Gen) -> Subtitles {
+        let count = below(g, g.size() as u64 + 1) as usize;
+        let mut subs = Subtitles::default();
+        let mut time = below(g, MAX_DURATION);
+
+        for i in 0..count {
+            let start = time;
+            let end = start + below(g, MAX_DURATION);
+            subs.inner.push(SubLine::new(i as u32 + 1,
+                                         text(g),
+                                         Timestamp::from_miliseconds(start),
+                                         Timestamp::from_miliseconds(end)));
+            time = end + below(g, MAX_DURATION);
+        }
+        subs
+    }
+
+    /// Removes single lines, keeping the rest consistent.
+    fn shrink(&self) -> Box<dyn Iterator<Item = Subtitles>> {
+        let subs = self.clone();
+        Box::new((0..self.len()).rev().map(move |position| {
+            let mut shrunk = subs.clone();
+            shrunk.inner.remove(position);
+            shrunk.renumber();
+            shrunk
+        }))
+    }
+}
+
+#[cfg(test)]
+mod arbitrary_tests {
+    use quickcheck::{Arbitrary, Gen};
+    use std::str::FromStr;
+
+    use crate::subtitles::Subtitles;
+    use crate::utils;
+
+    fn consistent(subs: &Subtitles) -> bool {
+        subs.inner.iter().enumerate().all(|(i, line)| {
+            line.index as usize == i + 1 && line.start <= line.end && !line.text.trim().is_empty() &&
+            subs.inner.get(i + 1).map(|next| line.end <= next.start).unwrap_or(true)
+        })
+    }
+
+    #[test]
+    fn subtitles() {
+        let mut g = Gen::new(50);
+        for _ in 0..100 {
+            let subs = Subtitles::arbitrary(&mut g);
+            assert!(consistent(&subs));
+            assert!(subs.shrink().all(|shrunk| consistent(&shrunk)));
+
+            let parsed = Subtitles::from_str(&utils::prepare(&subs.to_string())).unwrap();
+            assert_eq!(parsed, subs);
+        }
+    }
+}
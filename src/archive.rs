@@ -0,0 +1,128 @@
+//! Near-duplicate detection across many files, e.g. different rips of the same translation.
+
+use std::collections::HashMap;
+
+use crate::subtitles::Subtitles;
+use crate::fingerprint::{self, FNV_OFFSET};
+
+/// Number of MinHash values per file.
+const SIGNATURE_LENGTH: usize = 64;
+/// Signature values per band, files sharing a band are compared.
+const BAND_ROWS: usize = 4;
+/// Words per shingle.
+const SHINGLE_WORDS: usize = 3;
+
+/// Groups files whose texts are near-identical, ignoring timing, formatting and indices.
+/// Similarity is the Jaccard index of the sets of three word sequences, estimated
+/// with MinHash, files with the same ```Subtitles::fingerprint``` are always grouped.
+/// ```threshold``` is the minimal similarity in ```0.0..=1.0```.
+///
+/// Returns groups of at least two positions in ```files```, groups and positions in them are sorted.
+/// Only files sharing a part of the signature are compared, so large archives aren't compared pairwise.
+pub fn find_duplicates(files: &[Subtitles], threshold: f64) -> Vec<Vec<usize>> {
+    let mut groups: Vec<usize> = (0..files.len()).collect();
+    let mut by_fingerprint: HashMap<u64, usize> = HashMap::new();
+    for (i, subs) in files.iter().enumerate() {
+        if let Some(&first) = by_fingerprint.get(&subs.fingerprint()) {
+            join(&mut groups, first, i);
+        } else {
+            by_fingerprint.insert(subs.fingerprint(), i);
+        }
+    }
+
+    let signatures: Vec<Option<[u64; SIGNATURE_LENGTH]>> = files.iter().map(signature).collect();
+    let mut buckets: HashMap<(usize, u64), Vec<usize>> = HashMap::new();
+    for (i, signature) in signatures.iter().enumerate() {
+        if let Some(ref signature) = *signature {
+            for (band, rows) in signature.chunks(BAND_ROWS).enumerate() {
+                let key = rows.iter().fold(FNV_OFFSET, |hash, row| fingerprint::fnv(hash, &row.to_le_bytes()));
+                buckets.entry((band, key)).or_default().push(i);
+            }
+        }
+    }
+    for candidates in buckets.values() {
+        for (n, &a) in candidates.iter().enumerate() {
+            for &b in &candidates[n + 1..] {
+                if let (Some(first), Some(second)) = (signatures[a], signatures[b]) {
+                    let equal = first.iter().zip(second.iter()).filter(|&(x, y)| x == y).count();
+                    if equal as f64 / SIGNATURE_LENGTH as f64 >= threshold {
+                        join(&mut groups, a, b);
+                    }
+                }
+            }
+        }
+    }
+
+    let mut members: HashMap<usize, Vec<usize>> = HashMap::new();
+    for i in 0..files.len() {
+        let root = root(&mut groups, i);
+        members.entry(root).or_default().push(i);
+    }
+    let mut result: Vec<Vec<usize>> = members.into_values().filter(|group| group.len() > 1).collect();
+    result.sort();
+    result
+}
+
+/// MinHash signature of the shingles of all texts, ```None``` if there are no words.
+fn signature(subs: &Subtitles) -> Option<[u64; SIGNATURE_LENGTH]> {
+    let text = subs.inner
+        .iter()
+        .map(|line| fingerprint::normalized_text(&line.text).to_lowercase())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let words: Vec<&str> = text.split_whitespace().collect();
+    if words.is_empty() {
+        return None;
+    }
+
+    let mut signature = [u64::MAX; SIGNATURE_LENGTH];
+    for shingle in words.windows(SHINGLE_WORDS.min(words.len())) {
+        let hash = shingle.iter()
+            .fold(FNV_OFFSET, |hash, word| fingerprint::fnv(fingerprint::fnv(hash, word.as_bytes()), b" "));
+        for (seed, minimum) in signature.iter_mut().enumerate() {
+            *minimum = (*minimum).min(mix(hash ^ (seed as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15)));
+        }
+    }
+    Some(signature)
+}
+
+/// SplitMix64 finalizer, a different hash function for every seed.
+fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+fn root(groups: &mut [usize], mut i: usize) -> usize {
+    while groups[i] != i {
+        groups[i] = groups[groups[i]];
+        i = groups[i];
+    }
+    i
+}
+
+fn join(groups: &mut [usize], a: usize, b: usize) {
+    let (a, b) = (root(groups, a), root(groups, b));
+    groups[a.max(b)] = a.min(b);
+}
+
+#[cfg(test)]
+mod archive_tests {
+    use crate::utils;
+
+    #[test]
+    fn find_duplicates() {
+        let texts = ["The quick brown fox", "jumps over the lazy dog.", "Then it runs away", "into the dark forest",
+                     "and never comes back.", "The end of the story", "is not known to anyone", "but the dog."];
+        let original = utils::subs_from_texts(&texts);
+        let retimed = original.clone().edit().shift(500).finish().unwrap();
+        let mut edited = texts.to_vec();
+        edited[7] = "but the cat.";
+        let other = utils::subs_from_texts(&["Something", "completely different", "is said here"]);
+        let files = vec![original.clone(), other.clone(), retimed, utils::subs_from_texts(&edited), original, other];
+
+        assert_eq!(super::find_duplicates(&files, 0.8), vec![vec![0, 2, 3, 4], vec![1, 5]]);
+        assert_eq!(super::find_duplicates(&files, 1.0), vec![vec![0, 2, 4], vec![1, 5]]);
+        assert!(super::find_duplicates(&[], 0.5).is_empty());
+    }
+}
@@ -0,0 +1,160 @@
+use crate::subtitles::Subtitles;
+use crate::utils;
+
+/// Options for ```Subtitles::fix_casing```.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CasingOptions {
+    /// Percentage of uppercase letters (among all cased letters)
+    /// starting from which the track is treated as ALL-CAPS.
+    pub threshold: u8,
+    /// Words that must stay uppercase (e.g. ```"FBI"```, ```"NASA"```).
+    pub acronyms: Vec<String>,
+    /// Convert the track even if it's not detected as ALL-CAPS.
+    pub force: bool,
+}
+
+impl Default for CasingOptions {
+    fn default() -> CasingOptions {
+        CasingOptions {
+            threshold: 90,
+            acronyms: Vec::new(),
+            force: false,
+        }
+    }
+}
+
+impl Subtitles {
+    /// Returns ```true``` if the track is written in ALL-CAPS,
+    /// e.g. it was converted from the CEA-608 broadcast captions.
+    /// Markup tags are not taken into account.
+    pub fn is_all_caps(&self, threshold: u8) -> bool {
+        let mut upper = 0u64;
+        let mut lower = 0u64;
+
+        for line in self {
+            for c in utils::strip_tags(&line.text).chars() {
+                if c.is_uppercase() {
+                    upper += 1;
+                } else if c.is_lowercase() {
+                    lower += 1;
+                }
+            }
+        }
+
+        if upper + lower == 0 {
+            return false;
+        }
+        upper * 100 >= (upper + lower) * threshold as u64
+    }
+
+    /// Converts ALL-CAPS track into the sentence case.
+    ///
+    /// The first letter of the track and every first letter after the sentence-final
+    /// punctuation (```.```, ```!```, ```?```) is kept uppercase, sentences are allowed
+    /// to continue in the next line. Acronyms from ```options``` and pronoun "I" are preserved.
+    ///
+    /// Returns ```true``` if the track was converted.
+    pub fn fix_casing(&mut self, options: &CasingOptions) -> bool {
+        if !options.force && !self.is_all_caps(options.threshold) {
+            return false;
+        }
+
+        let mut capitalize = true;
+        for line in &mut self.inner {
+            line.text = sentence_case(&line.text, &mut capitalize, &options.acronyms);
+        }
+        true
+    }
+}
+
+fn sentence_case(text: &str, capitalize: &mut bool, acronyms: &[String]) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut word = String::new();
+    let mut closing = None;
+
+    for c in text.chars() {
+        if let Some(end) = closing {
+            result.push(c);
+            if c == end {
+                closing = None;
+            }
+            continue;
+        }
+
+        if c.is_alphanumeric() || (c == '\'' && !word.is_empty()) {
+            word.push(c);
+            continue;
+        }
+
+        if !word.is_empty() {
+            push_word(&mut result, &word, capitalize, acronyms);
+            word.clear();
+        }
+
+        match c {
+            '<' => closing = Some('>'),
+            '{' => closing = Some('}'),
+            '.' | '!' | '?' => *capitalize = true,
+            _ => {}
+        }
+        result.push(c);
+    }
+
+    if !word.is_empty() {
+        push_word(&mut result, &word, capitalize, acronyms);
+    }
+    result
+}
+
+fn push_word(result: &mut String, word: &str, capitalize: &mut bool, acronyms: &[String]) {
+    let upper = word.to_uppercase();
+    let lower = word.to_lowercase();
+
+    if acronyms.iter().any(|acronym| acronym.to_uppercase() == upper) {
+        result.push_str(&upper);
+    } else if lower == "i" || lower.starts_with("i'") {
+        result.push('I');
+        result.push_str(&lower[1..]);
+    } else if *capitalize {
+        let mut chars = lower.chars();
+        if let Some(first) = chars.next() {
+            result.extend(first.to_uppercase());
+            result.push_str(chars.as_str());
+        }
+    } else {
+        result.push_str(&lower);
+    }
+
+    if word.chars().any(|c| c.is_alphabetic()) {
+        *capitalize = false;
+    }
+}
+
+#[cfg(test)]
+mod casing_tests {
+    use super::*;
+    use crate::utils;
+
+    #[test]
+    fn fix_casing() {
+        let mut subs = utils::subs_from_texts(&["THE TRIAL WILL BE",
+                              "GETTING UNDER WAY SOON. I'M SURE\r\nTHE FBI KNOWS.",
+                              "<i>YOUR GRACE!</i> WHAT?"]);
+        let options = CasingOptions { acronyms: vec!["fbi".to_owned()], ..Default::default() };
+
+        assert!(subs.is_all_caps(options.threshold));
+        assert!(subs.fix_casing(&options));
+        assert_eq!(subs[0].text, "The trial will be");
+        assert_eq!(subs[1].text, "getting under way soon. I'm sure\r\nthe FBI knows.");
+        assert_eq!(subs[2].text, "<i>Your grace!</i> What?");
+    }
+
+    #[test]
+    fn mixed_case_untouched() {
+        let mut subs = utils::subs_from_texts(&["Later.", "Go away, NOW!"]);
+        let primal_subs = subs.clone();
+
+        assert!(!subs.fix_casing(&CasingOptions::default()));
+        assert_eq!(subs, primal_subs);
+    }
+}
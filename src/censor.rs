@@ -0,0 +1,129 @@
+use regex::{self, Captures, Regex};
+
+use crate::subtitles::Subtitles;
+
+/// Case-insensitive list of words to censor.
+///
+/// Words are matched as a whole, a trailing ```*``` matches any ending
+/// (e.g. ```"damn*"``` matches "damn", "damned" and "damnit").
+#[derive(Debug, Clone)]
+pub struct WordList {
+    regex: Option<Regex>,
+}
+
+impl WordList {
+    /// Constructs a new ```WordList``` from given words.
+    pub fn new<S: AsRef<str>>(words: &[S]) -> WordList {
+        let alternatives: Vec<String> = words.iter()
+            .map(|word| word.as_ref().trim())
+            .filter(|word| !word.is_empty() && *word != "*")
+            .map(|word| if word.ends_with('*') {
+                format!(r"{}\w*", regex::escape(word.trim_end_matches('*')))
+            } else {
+                regex::escape(word)
+            })
+            .collect();
+
+        if alternatives.is_empty() {
+            return WordList { regex: None };
+        }
+
+        let pattern = format!(r"(?i)\b(?:{})\b", alternatives.join("|"));
+        WordList { regex: Some(Regex::new(&pattern).unwrap()) }
+    }
+
+    /// Returns ```true``` if given text contains any of the listed words.
+    pub fn is_match(&self, text: &str) -> bool {
+        self.regex.as_ref().map(|regex| regex.is_match(text)).unwrap_or(false)
+    }
+}
+
+/// How censored words are masked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CensorStyle {
+    /// Replace every letter with an asterisk: "damn" becomes "****".
+    Asterisks,
+    /// Replace the word with a grawlix: "damn" becomes "#$@&".
+    Grawlix,
+    /// Remove the whole line containing a censored word.
+    RemoveLine,
+}
+
+static GRAWLIX: &[char] = &['#', '$', '@', '&', '%', '*', '!'];
+
+impl Subtitles {
+    /// Masks words from the given list in every line according ```style```.
+    /// Lines are renumbered if any of them were removed.
+    ///
+    /// Returns the number of censored lines.
+    pub fn censor(&mut self, words: &WordList, style: CensorStyle) -> usize {
+        let regex = match words.regex {
+            Some(ref regex) => regex,
+            None => return 0,
+        };
+
+        if style == CensorStyle::RemoveLine {
+            let len = self.inner.len();
+            self.inner.retain(|line| !regex.is_match(&line.text));
+            let removed = len - self.inner.len();
+            if removed > 0 {
+                self.renumber();
+            }
+            return removed;
+        }
+
+        let mut censored = 0;
+        for line in &mut self.inner {
+            if !regex.is_match(&line.text) {
+                continue;
+            }
+            line.text = regex.replace_all(&line.text, |cap: &Captures| {
+                let len = cap[0].chars().count();
+                match style {
+                    CensorStyle::Grawlix => GRAWLIX.iter().cycle().take(len).cloned().collect(),
+                    _ => "*".repeat(len),
+                }
+            }).into_owned();
+            censored += 1;
+        }
+        censored
+    }
+}
+
+#[cfg(test)]
+mod censor_tests {
+    use super::*;
+    use crate::utils;
+
+    #[test]
+    fn asterisks() {
+        let mut subs = utils::subs_from_texts(&["Damn it!", "Goddamn weather.", "Fine.", "You DAMNED fool."]);
+        let words = WordList::new(&["damn*"]);
+
+        assert_eq!(subs.censor(&words, CensorStyle::Asterisks), 2);
+        assert_eq!(subs[0].text, "**** it!");
+        assert_eq!(subs[1].text, "Goddamn weather.");
+        assert_eq!(subs[3].text, "You ****** fool.");
+    }
+
+    #[test]
+    fn grawlix() {
+        let mut subs = utils::subs_from_texts(&["Damn it!", "Goddamn weather.", "Fine.", "You DAMNED fool."]);
+        let words = WordList::new(&["damn", "fool"]);
+
+        assert_eq!(subs.censor(&words, CensorStyle::Grawlix), 2);
+        assert_eq!(subs[0].text, "#$@& it!");
+        assert_eq!(subs[3].text, "You DAMNED #$@&.");
+    }
+
+    #[test]
+    fn remove_line() {
+        let mut subs = utils::subs_from_texts(&["Damn it!", "Goddamn weather.", "Fine.", "You DAMNED fool."]);
+        let words = WordList::new(&["damn*", "weather"]);
+
+        assert_eq!(subs.censor(&words, CensorStyle::RemoveLine), 3);
+        assert_eq!(subs.len(), 1);
+        assert_eq!(subs[0].text, "Fine.");
+        assert_eq!(subs[0].index, 1);
+    }
+}
@@ -0,0 +1,183 @@
+use std::borrow::Cow;
+
+use crate::subtitles::Subtitles;
+use crate::subline::SubLine;
+use crate::utils;
+
+/// Half-width katakana and punctuation from ```U+FF61``` to ```U+FF9F``` as full-width characters.
+static KATAKANA: &str = "。「」、・ヲァィゥェォャュョッーアイウエオカキクケコサシスセソタチツテト\
+                         ナニヌネノハヒフヘホマミムメモヤユヨラリルレロワン゛゜";
+const HALF_KATAKANA_START: u32 = 0xff61;
+const VOICED_MARK: char = '\u{ff9e}';
+const SEMI_VOICED_MARK: char = '\u{ff9f}';
+/// Full-width ASCII is shifted by this from ASCII.
+const FULL_WIDTH_OFFSET: u32 = 0xfee0;
+const IDEOGRAPHIC_SPACE: char = '\u{3000}';
+
+/// Width to which characters are converted by ```Subtitles::normalize_width```.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Width {
+    #[default]
+    Keep,
+    Half,
+    Full,
+}
+
+/// Options of ```Subtitles::normalize_width```, by default as for Japanese broadcast (ARIB)
+/// captions converted to SubRip: half-width letters and digits, full-width katakana.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CjkOptions {
+    /// Letters, digits and punctuation of ASCII, e.g. ```Ａ``` and ```A```.
+    pub ascii: Width,
+    /// Katakana and Japanese punctuation, e.g. ```ｶﾞ``` and ```ガ```.
+    pub katakana: Width,
+    /// Ideographic and ASCII spaces.
+    pub spaces: Width,
+}
+
+impl Default for CjkOptions {
+    fn default() -> CjkOptions {
+        CjkOptions {
+            ascii: Width::Half,
+            katakana: Width::Full,
+            spaces: Width::Keep,
+        }
+    }
+}
+
+impl Subtitles {
+    /// Converts characters of texts between half-width and full-width forms.
+    /// Tags are left as they are. Returns the number of changed lines.
+    pub fn normalize_width(&mut self, options: &CjkOptions) -> usize {
+        self.edit_text(|text| {
+            if text.is_ascii() && options.ascii != Width::Full && options.spaces != Width::Full {
+                Cow::Borrowed(text)
+            } else {
+                Cow::Owned(normalize(text, options))
+            }
+        })
+    }
+}
+
+impl SubLine {
+    /// Columns taken by the widest row of the text without tags,
+    /// full-width and wide East Asian characters take two columns.
+    pub fn display_width(&self) -> usize {
+        utils::strip_tags(&self.text).lines().map(display_width).max().unwrap_or(0)
+    }
+}
+
+/// Columns taken by ```text```, see ```SubLine::display_width```.
+pub fn display_width(text: &str) -> usize {
+    text.chars().filter(|c| !c.is_control()).map(|c| if is_wide(c) { 2 } else { 1 }).sum()
+}
+
+fn is_wide(c: char) -> bool {
+    matches!(c as u32,
+             0x1100..=0x115f | 0x2e80..=0x303e | 0x3040..=0xa4cf | 0xac00..=0xd7a3 | 0xf900..=0xfaff |
+             0xfe30..=0xfe4f | 0xff00..=0xff60 | 0xffe0..=0xffe6 | 0x20000..=0x3fffd)
+}
+
+fn normalize(text: &str, options: &CjkOptions) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    let mut in_tag = false;
+    while let Some(c) = chars.next() {
+        in_tag = match c {
+            '<' => true,
+            '>' => false,
+            _ => in_tag,
+        };
+        if in_tag || c == '>' {
+            result.push(c);
+            continue;
+        }
+        let code = c as u32;
+        match c {
+            ' ' if options.spaces == Width::Full => result.push(IDEOGRAPHIC_SPACE),
+            IDEOGRAPHIC_SPACE if options.spaces == Width::Half => result.push(' '),
+            '!'..='~' if options.ascii == Width::Full && c != '<' => {
+                result.push(char::from_u32(code + FULL_WIDTH_OFFSET).unwrap_or(c))
+            }
+            '\u{ff01}'..='\u{ff5e}' if options.ascii == Width::Half => {
+                result.push(char::from_u32(code - FULL_WIDTH_OFFSET).unwrap_or(c))
+            }
+            '\u{ff61}'..='\u{ff9f}' if options.katakana == Width::Full => {
+                let full = KATAKANA.chars().nth((code - HALF_KATAKANA_START) as usize).unwrap_or(c);
+                let marked = match chars.peek() {
+                    Some(&VOICED_MARK) => voiced(full, 1),
+                    Some(&SEMI_VOICED_MARK) => voiced(full, 2),
+                    _ => None,
+                };
+                if marked.is_some() {
+                    chars.next();
+                }
+                result.push(marked.unwrap_or(full));
+            }
+            _ if options.katakana == Width::Half => half_katakana(c, &mut result),
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+/// Katakana with the voiced (```shift``` 1) or semi-voiced (2) mark, if there is such.
+fn voiced(c: char, shift: u32) -> Option<char> {
+    let voiceable = if shift == 1 { "カキクケコサシスセソタチツテトハヒフヘホ" } else { "ハヒフヘホ" };
+    if c == 'ウ' && shift == 1 {
+        Some('ヴ')
+    } else if voiceable.contains(c) {
+        char::from_u32(c as u32 + shift)
+    } else {
+        None
+    }
+}
+
+fn half_katakana(c: char, result: &mut String) {
+    let half = |c: char| KATAKANA.chars().position(|full| full == c).and_then(|i| char::from_u32(HALF_KATAKANA_START + i as u32));
+    if let Some(half) = half(c) {
+        result.push(half);
+        return;
+    }
+    let base = [(1, VOICED_MARK), (2, SEMI_VOICED_MARK)]
+        .iter()
+        .filter_map(|&(shift, mark)| {
+            let base = if c == 'ヴ' { 'ウ' } else { char::from_u32((c as u32).checked_sub(shift)?)? };
+            if voiced(base, shift) == Some(c) { Some((half(base)?, mark)) } else { None }
+        })
+        .next();
+    match base {
+        Some((base, mark)) => {
+            result.push(base);
+            result.push(mark);
+        }
+        None => result.push(c),
+    }
+}
+
+#[cfg(test)]
+mod cjk_tests {
+    use super::*;
+    use crate::utils;
+
+    #[test]
+    fn normalize_width() {
+        let mut subs = utils::subs_from_texts(&["ＡＢＣ１２３！", "<i>ｶﾞｯｺｳ ﾊﾟﾝ ｳﾞ</i>", "Plain"]);
+        assert_eq!(subs.normalize_width(&CjkOptions::default()), 2);
+        assert_eq!(subs.inner[0].text, "ABC123!");
+        assert_eq!(subs.inner[1].text, "<i>ガッコウ パン ヴ</i>");
+
+        let half = CjkOptions { ascii: Width::Full, katakana: Width::Half, spaces: Width::Full };
+        assert_eq!(subs.normalize_width(&half), 3);
+        assert_eq!(subs.inner[0].text, "ＡＢＣ１２３！");
+        assert_eq!(subs.inner[1].text, "<i>ｶﾞｯｺｳ\u{3000}ﾊﾟﾝ\u{3000}ｳﾞ</i>");
+        assert_eq!(subs.inner[2].text, "Ｐｌａｉｎ");
+    }
+
+    #[test]
+    fn display_width() {
+        let line = SubLine::new(1, "<i>日本語</i>\r\nabc ｶﾞ".to_owned(), Default::default(), Default::default());
+        assert_eq!(line.display_width(), 6);
+        assert_eq!(super::display_width("ＡＢ"), 4);
+    }
+}
@@ -0,0 +1,173 @@
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+use std::io::{Error, ErrorKind};
+
+use crate::subtitles::Subtitles;
+
+/// RGB color with opacity, as used by ```<font color="...">``` tags and ASS styles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Color {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+    /// Opacity, ```255``` is opaque.
+    pub alpha: u8,
+}
+
+static NAMED: &[(&str, u32)] = &[("white", 0xffffff),
+                                  ("silver", 0xc0c0c0),
+                                  ("gray", 0x808080),
+                                  ("grey", 0x808080),
+                                  ("black", 0x000000),
+                                  ("red", 0xff0000),
+                                  ("maroon", 0x800000),
+                                  ("yellow", 0xffff00),
+                                  ("olive", 0x808000),
+                                  ("lime", 0x00ff00),
+                                  ("green", 0x008000),
+                                  ("aqua", 0x00ffff),
+                                  ("cyan", 0x00ffff),
+                                  ("teal", 0x008080),
+                                  ("blue", 0x0000ff),
+                                  ("navy", 0x000080),
+                                  ("fuchsia", 0xff00ff),
+                                  ("magenta", 0xff00ff),
+                                  ("purple", 0x800080),
+                                  ("orange", 0xffa500)];
+
+impl Color {
+    /// Constructs an opaque ```Color```.
+    pub fn rgb(red: u8, green: u8, blue: u8) -> Color {
+        Color {
+            red,
+            green,
+            blue,
+            alpha: 255,
+        }
+    }
+
+    fn from_u32(rgb: u32) -> Color {
+        Color::rgb((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8)
+    }
+
+    /// Parses ASS color: ```&HBBGGRR&``` as in ```\c``` override tags
+    /// or ```&HAABBGGRR``` as in styles, where ```AA``` is transparency.
+    pub fn from_ass(s: &str) -> Result<Color, Error> {
+        let invalid = || Error::new(ErrorKind::InvalidData, "Invalid ASS color");
+        let s = s.trim();
+        let hex = s.strip_prefix("&H").or_else(|| s.strip_prefix("&h")).ok_or_else(invalid)?.trim_end_matches('&');
+        if hex.is_empty() || hex.len() > 8 {
+            return Err(invalid());
+        }
+        let value = u32::from_str_radix(hex, 16).map_err(|_| invalid())?;
+        Ok(Color {
+            red: value as u8,
+            green: (value >> 8) as u8,
+            blue: (value >> 16) as u8,
+            alpha: 255 - (value >> 24) as u8,
+        })
+    }
+
+    /// ASS ```\c``` override tag value, e.g. ```&H0080FF&``` for orange.
+    pub fn to_ass(&self) -> String {
+        format!("&H{:02X}{:02X}{:02X}&", self.blue, self.green, self.red)
+    }
+
+    /// ASS style color with transparency, e.g. ```&H000080FF```.
+    pub fn to_ass_style(&self) -> String {
+        format!("&H{:02X}{:02X}{:02X}{:02X}",
+                255 - self.alpha,
+                self.blue,
+                self.green,
+                self.red)
+    }
+}
+
+impl FromStr for Color {
+    type Err = Error;
+    /// Parses html color: ```#RRGGBB```, ```#RGB```, ```RRGGBB``` or a basic color name.
+    fn from_str(s: &str) -> Result<Color, Error> {
+        let invalid = || Error::new(ErrorKind::InvalidData, "Invalid color");
+        let s = s.trim();
+
+        let name = s.to_lowercase();
+        if let Some(&(_, rgb)) = NAMED.iter().find(|&&(named, _)| named == name) {
+            return Ok(Color::from_u32(rgb));
+        }
+
+        let hex = s.trim_start_matches('#');
+        if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(invalid());
+        }
+        match hex.len() {
+            6 => Ok(Color::from_u32(u32::from_str_radix(hex, 16).map_err(|_| invalid())?)),
+            3 => {
+                let doubled: String = hex.chars().flat_map(|c| vec![c, c]).collect();
+                Ok(Color::from_u32(u32::from_str_radix(&doubled, 16).map_err(|_| invalid())?))
+            }
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl Display for Color {
+    /// Formats color as html ```#rrggbb```.
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.red, self.green, self.blue)
+    }
+}
+
+impl Subtitles {
+    /// Removes colors from all lines for players which don't support them,
+    /// other ```<font>``` attributes are kept.
+    ///
+    /// Returns the number of changed lines.
+    pub fn strip_colors(&mut self) -> usize {
+        let mut changed = 0;
+        for line in &mut self.inner {
+            let before = line.text.len();
+            line.set_color(None);
+            if line.text.len() != before {
+                changed += 1;
+            }
+        }
+        changed
+    }
+}
+
+#[cfg(test)]
+mod color_tests {
+    use super::*;
+    use std::str::FromStr;
+    use crate::utils;
+
+    #[test]
+    fn parse() {
+        let orange = Color::rgb(255, 128, 0);
+        assert_eq!(Color::from_str("#FF8000").unwrap(), orange);
+        assert_eq!(Color::from_str("ff8000").unwrap(), orange);
+        assert_eq!(Color::from_str("#f80").unwrap(), Color::rgb(255, 136, 0));
+        assert_eq!(Color::from_str("Red").unwrap(), Color::rgb(255, 0, 0));
+        assert!(Color::from_str("#12345").is_err());
+        assert!(Color::from_str("reddish").is_err());
+        assert_eq!(orange.to_string(), "#ff8000");
+    }
+
+    #[test]
+    fn ass() {
+        let orange = Color::rgb(255, 128, 0);
+        assert_eq!(orange.to_ass(), "&H0080FF&");
+        assert_eq!(orange.to_ass_style(), "&H000080FF");
+        assert_eq!(Color::from_ass("&H0080FF&").unwrap(), orange);
+        assert_eq!(Color::from_ass("&H800080ff").unwrap().alpha, 127);
+        assert!(Color::from_ass("#FF8000").is_err());
+    }
+
+    #[test]
+    fn strip_colors() {
+        let mut subs = utils::subs_from_texts(&["<font color=\"red\">Red</font> text", "<i>Plain</i>"]);
+        assert_eq!(subs.strip_colors(), 1);
+        assert_eq!(subs.inner[0].text, "Red text");
+    }
+}
@@ -0,0 +1,262 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+#[cfg(feature = "compact")]
+use smallstr::SmallString;
+
+use crate::timestamp::Timestamp;
+use crate::subline::{SubLine, Word};
+use crate::subtitles::Subtitles;
+use crate::metadata::Metadata;
+use crate::cue_settings::CueSettings;
+
+/// Rarely used parts of a line, allocated only if any of them is set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Extra {
+    id: Option<String>,
+    comment: Option<String>,
+    settings: CueSettings,
+    words: Vec<Word>,
+}
+
+/// Time of a line, miliseconds with the ```compact``` feature, saturating at about 49 days.
+#[cfg(feature = "compact")]
+type Time = u32;
+#[cfg(not(feature = "compact"))]
+type Time = Timestamp;
+
+#[cfg(feature = "compact")]
+fn pack(time: Timestamp) -> Time {
+    time.total_miliseconds().min(u64::from(u32::MAX)) as u32
+}
+
+#[cfg(not(feature = "compact"))]
+fn pack(time: Timestamp) -> Time {
+    time
+}
+
+#[cfg(feature = "compact")]
+fn unpack(time: Time) -> Timestamp {
+    Timestamp::from_miliseconds(u64::from(time))
+}
+
+#[cfg(not(feature = "compact"))]
+fn unpack(time: Time) -> Timestamp {
+    time
+}
+
+/// Texts up to this many bytes are stored in the line itself with the ```compact``` feature.
+#[cfg(feature = "compact")]
+const INLINE_TEXT: usize = 16;
+
+#[cfg(feature = "compact")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Text {
+    Inline(SmallString<[u8; INLINE_TEXT]>),
+    Shared(Arc<str>),
+}
+
+#[cfg(not(feature = "compact"))]
+type Text = Arc<str>;
+
+/// Line of ```CompactSubtitles```, its text is shared with the equal texts of other lines.
+/// With the ```compact``` feature short texts are stored inline instead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompactLine {
+    index: u32,
+    start: Time,
+    end: Time,
+    text: Text,
+    extra: Option<Box<Extra>>,
+}
+
+impl CompactLine {
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    pub fn id(&self) -> Option<&str> {
+        self.extra.as_ref().and_then(|extra| extra.id.as_deref())
+    }
+
+    pub fn start(&self) -> Timestamp {
+        unpack(self.start)
+    }
+
+    pub fn end(&self) -> Timestamp {
+        unpack(self.end)
+    }
+
+    #[cfg(feature = "compact")]
+    pub fn text(&self) -> &str {
+        match self.text {
+            Text::Inline(ref text) => text,
+            Text::Shared(ref text) => text,
+        }
+    }
+
+    #[cfg(not(feature = "compact"))]
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Converts back to ```SubLine```.
+    pub fn to_line(&self) -> SubLine {
+        let mut line = SubLine::new(self.index, self.text().to_owned(), self.start(), self.end());
+        if let Some(ref extra) = self.extra {
+            line.id = extra.id.clone();
+            line.comment = extra.comment.clone();
+            line.settings = extra.settings.clone();
+            line.words = extra.words.clone();
+        }
+        line
+    }
+}
+
+/// Read-mostly ```Subtitles``` storage for holding many tracks in memory:
+/// equal texts (```"♪"```, ```"[inaudible]"```, speaker labels) are stored once.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CompactSubtitles {
+    lines: Vec<CompactLine>,
+    pub metadata: Metadata,
+    texts: HashSet<Arc<str>>,
+}
+
+impl CompactSubtitles {
+    /// Returns the number of lines.
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// Returns the number of distinct shared texts,
+    /// with the ```compact``` feature it doesn't count the texts stored inline.
+    pub fn unique_texts(&self) -> usize {
+        self.texts.len()
+    }
+
+    /// Line at the given position.
+    pub fn get(&self, position: usize) -> Option<&CompactLine> {
+        self.lines.get(position)
+    }
+
+    pub fn iter(&self) -> ::std::slice::Iter<'_, CompactLine> {
+        self.lines.iter()
+    }
+
+    /// Appends the line, sharing its text if it's already stored.
+    pub fn push(&mut self, line: SubLine) {
+        #[cfg(feature = "compact")]
+        let text = if line.text.len() <= INLINE_TEXT {
+            Text::Inline(SmallString::from_str(&line.text))
+        } else {
+            Text::Shared(self.share(line.text))
+        };
+        #[cfg(not(feature = "compact"))]
+        let text = self.share(line.text);
+
+        let extra = if line.id.is_none() && line.comment.is_none() && line.settings.is_empty() &&
+                       line.words.is_empty() {
+            None
+        } else {
+            Some(Box::new(Extra {
+                id: line.id,
+                comment: line.comment,
+                settings: line.settings,
+                words: line.words,
+            }))
+        };
+        self.lines.push(CompactLine {
+            index: line.index,
+            start: pack(line.start),
+            end: pack(line.end),
+            text,
+            extra,
+        });
+    }
+
+    fn share(&mut self, text: String) -> Arc<str> {
+        match self.texts.get(&*text) {
+            Some(text) => text.clone(),
+            None => {
+                let text: Arc<str> = Arc::from(text);
+                self.texts.insert(text.clone());
+                text
+            }
+        }
+    }
+
+    /// Converts back to ```Subtitles```.
+    pub fn to_subtitles(&self) -> Subtitles {
+        let mut subs = Subtitles::from(self.lines.iter().map(CompactLine::to_line).collect::<Vec<_>>());
+        subs.metadata = self.metadata.clone();
+        subs
+    }
+}
+
+impl From<Subtitles> for CompactSubtitles {
+    fn from(subs: Subtitles) -> CompactSubtitles {
+        let mut compact = CompactSubtitles {
+            lines: Vec::with_capacity(subs.len()),
+            metadata: subs.metadata,
+            texts: HashSet::new(),
+        };
+        for line in subs.inner {
+            compact.push(line);
+        }
+        compact
+    }
+}
+
+impl<'a> IntoIterator for &'a CompactSubtitles {
+    type Item = &'a CompactLine;
+    type IntoIter = ::std::slice::Iter<'a, CompactLine>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.lines.iter()
+    }
+}
+
+#[cfg(test)]
+mod compact_tests {
+    use super::*;
+    use crate::utils;
+
+    #[test]
+    fn interning() {
+        let mut subs = utils::subs_from_texts(&["♪", "Text", "♪", "[inaudible]", "♪"]);
+        subs.inner[1].comment = Some("Note".to_owned());
+        subs.metadata.title = Some("Title".to_owned());
+
+        let compact = CompactSubtitles::from(subs.clone());
+        assert_eq!(compact.len(), 5);
+        assert_eq!(compact.get(2).unwrap().text(), "♪");
+        assert_eq!(compact.to_subtitles(), subs);
+        #[cfg(not(feature = "compact"))]
+        {
+            assert_eq!(compact.unique_texts(), 3);
+            assert!(Arc::ptr_eq(&compact.get(0).unwrap().text, &compact.get(4).unwrap().text));
+        }
+    }
+
+    #[cfg(feature = "compact")]
+    #[test]
+    fn inline() {
+        use std::mem::size_of;
+
+        let long = "A line long enough to be shared";
+        let subs = utils::subs_from_texts(&["♪", long, "♪", long]);
+        let compact = CompactSubtitles::from(subs.clone());
+        assert_eq!(compact.unique_texts(), 1);
+        match (&compact.get(1).unwrap().text, &compact.get(3).unwrap().text) {
+            (Text::Shared(a), Text::Shared(b)) => assert!(Arc::ptr_eq(a, b)),
+            _ => panic!("long text isn't shared"),
+        }
+        assert_eq!(compact.get(0).unwrap().text, Text::Inline(SmallString::from_str("♪")));
+        assert_eq!(compact.to_subtitles(), subs);
+        assert!(size_of::<CompactLine>() <= size_of::<SubLine>() / 2);
+    }
+}
@@ -0,0 +1,178 @@
+//! Comparison of whole tracks, e.g. speech recognition output against human-made subtitles.
+
+use crate::timestamp::Timestamp;
+use crate::subtitles::Subtitles;
+use crate::utils;
+
+/// Group of time-overlapping lines from both tracks, compared as a whole.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlignedPair {
+    pub start: Timestamp,
+    pub end: Timestamp,
+    /// Indices of the reference lines, empty if only hypothesis has lines here.
+    pub reference: Vec<u32>,
+    /// Indices of the hypothesis lines, empty if only reference has lines here.
+    pub hypothesis: Vec<u32>,
+    pub reference_words: usize,
+    pub word_errors: usize,
+    pub reference_chars: usize,
+    pub char_errors: usize,
+}
+
+/// Result of ```compare::similarity```.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimilarityReport {
+    pub pairs: Vec<AlignedPair>,
+    /// Word errors (substitutions, deletions and insertions) per reference word.
+    pub word_error_rate: f64,
+    /// Character errors per reference character.
+    pub char_error_rate: f64,
+}
+
+/// Line in a group: whether it's from reference, its index and text.
+type Member<'a> = (bool, u32, &'a str);
+
+/// Computes word and character error rates of the ```hypothesis``` track
+/// against the ```reference``` track.
+///
+/// Lines are grouped by time overlap, so differently split lines are still
+/// compared with each other, and errors are counted within every group.
+/// Text is compared without tags, punctuation and case.
+pub fn similarity(reference: &Subtitles, hypothesis: &Subtitles) -> SimilarityReport {
+    let mut lines: Vec<(Timestamp, Timestamp, bool, u32, &str)> = reference.inner
+        .iter()
+        .map(|line| (line.start, line.end, true, line.index, &*line.text))
+        .chain(hypothesis.inner.iter().map(|line| (line.start, line.end, false, line.index, &*line.text)))
+        .collect();
+    lines.sort_by_key(|line| (line.0, line.1));
+
+    let mut groups: Vec<(Timestamp, Timestamp, Vec<Member>)> = Vec::new();
+    for (start, end, is_reference, index, text) in lines {
+        if let Some(group) = groups.last_mut() {
+            if start < group.1 {
+                if end > group.1 {
+                    group.1 = end;
+                }
+                group.2.push((is_reference, index, text));
+                continue;
+            }
+        }
+        groups.push((start, end, vec![(is_reference, index, text)]));
+    }
+
+    let mut pairs = Vec::with_capacity(groups.len());
+    for (start, end, members) in groups {
+        let mut reference_text = String::new();
+        let mut hypothesis_text = String::new();
+        let mut reference_indices = Vec::new();
+        let mut hypothesis_indices = Vec::new();
+
+        for (is_reference, index, text) in members {
+            let (joined, indices) = if is_reference {
+                (&mut reference_text, &mut reference_indices)
+            } else {
+                (&mut hypothesis_text, &mut hypothesis_indices)
+            };
+            joined.push(' ');
+            joined.push_str(text);
+            indices.push(index);
+        }
+
+        let reference_words = words(&reference_text);
+        let hypothesis_words = words(&hypothesis_text);
+        let reference_chars: Vec<char> = reference_words.join(" ").chars().collect();
+        let hypothesis_chars: Vec<char> = hypothesis_words.join(" ").chars().collect();
+
+        pairs.push(AlignedPair {
+            start,
+            end,
+            reference: reference_indices,
+            hypothesis: hypothesis_indices,
+            reference_words: reference_words.len(),
+            word_errors: edit_distance(&reference_words, &hypothesis_words),
+            reference_chars: reference_chars.len(),
+            char_errors: edit_distance(&reference_chars, &hypothesis_chars),
+        });
+    }
+
+    let rate = |errors: usize, total: usize| if total == 0 {
+        if errors == 0 { 0.0 } else { 1.0 }
+    } else {
+        errors as f64 / total as f64
+    };
+    let word_error_rate = rate(pairs.iter().map(|pair| pair.word_errors).sum(),
+                               pairs.iter().map(|pair| pair.reference_words).sum());
+    let char_error_rate = rate(pairs.iter().map(|pair| pair.char_errors).sum(),
+                               pairs.iter().map(|pair| pair.reference_chars).sum());
+
+    SimilarityReport {
+        pairs,
+        word_error_rate,
+        char_error_rate,
+    }
+}
+
+/// Normalized words of the text.
+pub(crate) fn words(text: &str) -> Vec<String> {
+    utils::strip_tags(text)
+        .split(|c: char| !(c.is_alphanumeric() || c == '\''))
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase())
+        .collect()
+}
+
+/// Levenshtein distance.
+pub fn edit_distance<T: PartialEq>(a: &[T], b: &[T]) -> usize {
+    let mut previous: Vec<usize> = (0..b.len() + 1).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, x) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, y) in b.iter().enumerate() {
+            let substitution = previous[j] + if x == y { 0 } else { 1 };
+            current[j + 1] = *[substitution, previous[j + 1] + 1, current[j] + 1].iter().min().unwrap();
+        }
+        ::std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod compare_tests {
+    use super::*;
+    use crate::timestamp::Timestamp;
+    use crate::utils;
+
+    #[test]
+    fn _edit_distance() {
+        assert_eq!(edit_distance(b"kitten", b"sitting"), 3);
+        assert_eq!(edit_distance(b"", b"abc"), 3);
+        assert_eq!(edit_distance(b"abc", b"abc"), 0);
+    }
+
+    #[test]
+    fn _similarity() {
+        let reference = utils::subs_from_texts(&["<i>Your Grace.</i>", "The trial will be\r\ngetting under way soon.", "Later."]);
+        let mut hypothesis = utils::subs_from_texts(&["your grace", "the trial will", "be getting under way", "later"]);
+        // ASR split the second line into two halves
+        hypothesis.inner[1].start = Timestamp::new(0, 0, 2, 0);
+        hypothesis.inner[1].end = Timestamp::new(0, 0, 2, 500);
+        hypothesis.inner[2].start = Timestamp::new(0, 0, 2, 500);
+        hypothesis.inner[2].end = Timestamp::new(0, 0, 3, 0);
+        hypothesis.inner[3].start = Timestamp::new(0, 0, 4, 0);
+        hypothesis.inner[3].end = Timestamp::new(0, 0, 5, 0);
+
+        let report = similarity(&reference, &hypothesis);
+        assert_eq!(report.pairs.len(), 3);
+        assert_eq!(report.pairs[1].reference, vec![2]);
+        assert_eq!(report.pairs[1].hypothesis, vec![2, 3]);
+        // "soon" is missing
+        assert_eq!(report.pairs[1].word_errors, 1);
+        assert_eq!(report.word_error_rate, 1.0 / 11.0);
+        assert_eq!(report.char_error_rate, 5.0 / 55.0);
+
+        let same = similarity(&reference, &reference);
+        assert_eq!(same.word_error_rate, 0.0);
+        assert_eq!(same.char_error_rate, 0.0);
+    }
+}
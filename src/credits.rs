@@ -0,0 +1,59 @@
+use crate::timestamp::Timestamp;
+use crate::subtitles::Subtitles;
+use crate::subline::SubLine;
+
+impl Subtitles {
+    /// Inserts a credit line (e.g. ```"Subtitles by ..."```) lasting ```duration``` into the first
+    /// gap between lines that is long enough, starting from the zero time. Returns the inserted line,
+    /// or ```None``` if there is no such gap before the last line ends (use ```append_credit``` then).
+    pub fn prepend_credit(&mut self, text: &str, duration: Timestamp) -> Option<&mut SubLine> {
+        let last_end = self.inner.iter().map(|line| line.end).max().unwrap_or_default();
+        let mut free_from = Timestamp::default();
+        let mut lines = self.inner.iter().map(|line| (line.start, line.end)).collect::<Vec<_>>();
+        lines.sort();
+        for (start, end) in lines {
+            if start >= free_from && start - free_from >= duration {
+                break;
+            }
+            free_from = free_from.max(end);
+            if free_from >= last_end {
+                return None;
+            }
+        }
+        Some(self.insert_by_start(free_from, free_from + duration, text.to_owned()))
+    }
+
+    /// Inserts a credit line lasting ```duration``` after all other lines end. Returns the inserted line.
+    pub fn append_credit(&mut self, text: &str, duration: Timestamp) -> &mut SubLine {
+        let start = self.inner.iter().map(|line| line.end).max().unwrap_or_default();
+        self.insert_by_start(start, start + duration, text.to_owned())
+    }
+}
+
+#[cfg(test)]
+mod credits_tests {
+    use crate::timestamp::Timestamp;
+    use crate::subtitles::Subtitles;
+
+    #[test]
+    fn prepend_credit() {
+        let mut subs = subtitles![(1000, 3000, "First"), (4000, 6000, "Second"), (9500, 10000, "Third")];
+        let line = subs.prepend_credit("Credit", Timestamp::from_miliseconds(2000)).unwrap();
+        assert_eq!((line.index, line.start.total_miliseconds(), line.end.total_miliseconds()), (3, 6000, 8000));
+        assert_eq!(subs.inner[3].index, 4);
+
+        let line = subs.prepend_credit("Short", Timestamp::from_miliseconds(1000)).unwrap();
+        assert_eq!((line.index, line.start.total_miliseconds()), (1, 0));
+
+        assert!(subs.prepend_credit("Long", Timestamp::from_miliseconds(5000)).is_none());
+        let mut empty = Subtitles::default();
+        assert_eq!(empty.prepend_credit("Credit", Timestamp::from_miliseconds(5000)).unwrap().index, 1);
+    }
+
+    #[test]
+    fn append_credit() {
+        let mut subs = subtitles![(1000, 3000, "First"), (4000, 6000, "Second")];
+        let line = subs.append_credit("Credit", Timestamp::from_miliseconds(2000));
+        assert_eq!((line.index, line.start.total_miliseconds(), line.end.total_miliseconds()), (3, 6000, 8000));
+    }
+}
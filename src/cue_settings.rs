@@ -0,0 +1,261 @@
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+use std::io::Error;
+
+use crate::subtitles::Subtitles;
+
+/// Percentage with two decimal places, stored as hundredths of percent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Percent(pub u32);
+
+impl Percent {
+    fn parse(s: &str) -> Option<Percent> {
+        let number = s.strip_suffix('%')?;
+        if number.is_empty() || !number.chars().all(|c| c.is_ascii_digit() || c == '.') {
+            return None;
+        }
+        let value: f64 = number.parse().ok()?;
+        if value > 100.0 {
+            return None;
+        }
+        Some(Percent((value * 100.0).round() as u32))
+    }
+}
+
+impl Display for Percent {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let (tenths, hundredths) = (self.0 % 100 / 10, self.0 % 10);
+        if tenths == 0 && hundredths == 0 {
+            write!(f, "{}%", self.0 / 100)
+        } else if hundredths == 0 {
+            write!(f, "{}.{}%", self.0 / 100, tenths)
+        } else {
+            write!(f, "{}.{}{}%", self.0 / 100, tenths, hundredths)
+        }
+    }
+}
+
+/// Writing direction of vertical text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Vertical {
+    /// ```rl```, lines go from right to left, as in Japanese.
+    RightToLeft,
+    /// ```lr```
+    LeftToRight,
+}
+
+/// Line position: line number (negative counts from the end) or percentage of the video.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Line {
+    Number(i32),
+    Percent(Percent),
+}
+
+/// Which part of the cue box is at the ```line``` or ```position```.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum BoxAlign {
+    /// ```start``` for line, ```line-left``` for position.
+    Start,
+    Center,
+    /// ```end``` for line, ```line-right``` for position.
+    End,
+}
+
+/// Alignment of the text inside of the cue box.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TextAlign {
+    Start,
+    Center,
+    End,
+    Left,
+    Right,
+}
+
+/// WebVTT cue settings (```vertical:rl line:0 position:50%,center align:start size:80%```).
+/// Unknown and invalid settings are ignored, as the WebVTT specification requires.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CueSettings {
+    pub vertical: Option<Vertical>,
+    pub line: Option<(Line, Option<BoxAlign>)>,
+    pub position: Option<(Percent, Option<BoxAlign>)>,
+    pub size: Option<Percent>,
+    pub align: Option<TextAlign>,
+}
+
+impl CueSettings {
+    /// Returns ```true``` if no setting is set.
+    pub fn is_empty(&self) -> bool {
+        *self == CueSettings::default()
+    }
+}
+
+impl FromStr for CueSettings {
+    type Err = Error;
+    /// Parses space-separated settings, never fails.
+    fn from_str(s: &str) -> Result<CueSettings, Error> {
+        let mut settings = CueSettings::default();
+
+        for setting in s.split_whitespace() {
+            let mut parts = setting.splitn(2, ':');
+            let (name, value) = match (parts.next(), parts.next()) {
+                (Some(name), Some(value)) => (name, value),
+                _ => continue,
+            };
+            let mut values = value.splitn(2, ',');
+            let value = values.next().unwrap_or("");
+            let alignment = values.next();
+
+            match name {
+                "vertical" => {
+                    settings.vertical = match value {
+                        "rl" => Some(Vertical::RightToLeft),
+                        "lr" => Some(Vertical::LeftToRight),
+                        _ => settings.vertical,
+                    }
+                }
+                "line" => {
+                    let line = Percent::parse(value)
+                        .map(Line::Percent)
+                        .or_else(|| value.parse().ok().map(Line::Number));
+                    let alignment = match alignment {
+                        Some("start") => Some(BoxAlign::Start),
+                        Some("center") => Some(BoxAlign::Center),
+                        Some("end") => Some(BoxAlign::End),
+                        _ => None,
+                    };
+                    if let Some(line) = line {
+                        settings.line = Some((line, alignment));
+                    }
+                }
+                "position" => {
+                    let alignment = match alignment {
+                        Some("line-left") => Some(BoxAlign::Start),
+                        Some("center") => Some(BoxAlign::Center),
+                        Some("line-right") => Some(BoxAlign::End),
+                        _ => None,
+                    };
+                    if let Some(position) = Percent::parse(value) {
+                        settings.position = Some((position, alignment));
+                    }
+                }
+                "size" => settings.size = Percent::parse(value).or(settings.size),
+                "align" => {
+                    settings.align = match value {
+                        "start" => Some(TextAlign::Start),
+                        "center" | "middle" => Some(TextAlign::Center),
+                        "end" => Some(TextAlign::End),
+                        "left" => Some(TextAlign::Left),
+                        "right" => Some(TextAlign::Right),
+                        _ => settings.align,
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(settings)
+    }
+}
+
+impl Display for CueSettings {
+    /// Formats settings as in WebVTT cue timing line.
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let mut settings = Vec::new();
+        if let Some(vertical) = self.vertical {
+            settings.push(format!("vertical:{}", match vertical {
+                Vertical::RightToLeft => "rl",
+                Vertical::LeftToRight => "lr",
+            }));
+        }
+        if let Some((line, alignment)) = self.line {
+            let line = match line {
+                Line::Number(number) => number.to_string(),
+                Line::Percent(percent) => percent.to_string(),
+            };
+            settings.push(match alignment {
+                Some(BoxAlign::Start) => format!("line:{},start", line),
+                Some(BoxAlign::Center) => format!("line:{},center", line),
+                Some(BoxAlign::End) => format!("line:{},end", line),
+                None => format!("line:{}", line),
+            });
+        }
+        if let Some((position, alignment)) = self.position {
+            settings.push(match alignment {
+                Some(BoxAlign::Start) => format!("position:{},line-left", position),
+                Some(BoxAlign::Center) => format!("position:{},center", position),
+                Some(BoxAlign::End) => format!("position:{},line-right", position),
+                None => format!("position:{}", position),
+            });
+        }
+        if let Some(size) = self.size {
+            settings.push(format!("size:{}", size));
+        }
+        if let Some(align) = self.align {
+            settings.push(format!("align:{}", match align {
+                TextAlign::Start => "start",
+                TextAlign::Center => "center",
+                TextAlign::End => "end",
+                TextAlign::Left => "left",
+                TextAlign::Right => "right",
+            }));
+        }
+        write!(f, "{}", settings.join(" "))
+    }
+}
+
+impl Subtitles {
+    /// Removes cue settings of all lines, e.g. before saving to SubRip,
+    /// which can't keep them.
+    ///
+    /// Returns indices of the lines which had settings.
+    pub fn clear_cue_settings(&mut self) -> Vec<u32> {
+        let mut cleared = Vec::new();
+        for line in &mut self.inner {
+            if !line.settings.is_empty() {
+                line.settings = CueSettings::default();
+                cleared.push(line.index);
+            }
+        }
+        cleared
+    }
+}
+
+#[cfg(test)]
+mod cue_settings_tests {
+    use super::*;
+    use std::str::FromStr;
+    use crate::utils;
+
+    #[test]
+    fn parse_and_format() {
+        let settings = CueSettings::from_str("vertical:rl line:-1 position:33.5%,line-left size:80% align:start \
+                                              region:fred bogus line-x:0").unwrap();
+        assert_eq!(settings.vertical, Some(Vertical::RightToLeft));
+        assert_eq!(settings.line, Some((Line::Number(-1), None)));
+        assert_eq!(settings.position, Some((Percent(3350), Some(BoxAlign::Start))));
+        assert_eq!(settings.size, Some(Percent(8000)));
+        assert_eq!(settings.align, Some(TextAlign::Start));
+        assert_eq!(settings.to_string(),
+                   "vertical:rl line:-1 position:33.5%,line-left size:80% align:start");
+        assert_eq!(CueSettings::from_str(&settings.to_string()).unwrap(), settings);
+
+        let settings = CueSettings::from_str("line:10.25%,end size:120%").unwrap();
+        assert_eq!(settings.line, Some((Line::Percent(Percent(1025)), Some(BoxAlign::End))));
+        assert_eq!(settings.size, None);
+        assert!(CueSettings::from_str("").unwrap().is_empty());
+        assert_eq!(Percent(705).to_string(), "7.05%");
+    }
+
+    #[test]
+    fn clear_cue_settings() {
+        let mut subs = utils::subs_from_texts(&["One", "Two"]);
+        subs.inner[1].settings.vertical = Some(Vertical::RightToLeft);
+        assert_eq!(subs.clear_cue_settings(), vec![2]);
+        assert!(subs.clear_cue_settings().is_empty());
+    }
+}
@@ -0,0 +1,93 @@
+use std::borrow::Cow;
+
+#[cfg(feature = "text-tools")]
+use regex::Regex;
+
+use crate::subtitles::Subtitles;
+use crate::subline;
+
+impl Subtitles {
+    /// Applies ```f``` to every line's text. Texts for which ```f``` returns
+    /// ```Cow::Borrowed``` are left untouched, so bulk cleanups don't reallocate
+    /// lines they don't change. Returns the number of changed lines.
+    pub fn edit_text<F>(&mut self, mut f: F) -> usize
+        where F: FnMut(&str) -> Cow<str>
+    {
+        let mut changed = 0;
+        for line in &mut self.inner {
+            if let Cow::Owned(text) = f(&line.text) {
+                if text != line.text {
+                    line.text = text;
+                    changed += 1;
+                }
+            }
+        }
+        changed
+    }
+
+    /// Replaces all occurrences of ```from``` with ```to``` in all lines' texts.
+    /// Returns the number of changed lines.
+    pub fn replace_text(&mut self, from: &str, to: &str) -> usize {
+        if from.is_empty() {
+            return 0;
+        }
+        self.edit_text(|text| if text.contains(from) {
+            Cow::Owned(text.replace(from, to))
+        } else {
+            Cow::Borrowed(text)
+        })
+    }
+
+    /// Cuts texts longer than ```max_chars``` characters at word boundaries, see ```SubLine::truncated```.
+    /// Returns the number of changed lines.
+    pub fn truncate_texts(&mut self, max_chars: usize) -> usize {
+        self.edit_text(|text| subline::truncate(text, max_chars))
+    }
+
+    /// Replaces all matches of ```regex``` with ```replacement```, which may refer
+    /// to capture groups as ```$1``` or ```$name```. Returns the number of changed lines.
+    #[cfg(feature = "text-tools")]
+    pub fn replace_regex(&mut self, regex: &Regex, replacement: &str) -> usize {
+        self.edit_text(|text| regex.replace_all(text, replacement))
+    }
+}
+
+#[cfg(test)]
+mod edit_tests {
+    use super::*;
+    use crate::utils;
+
+    #[test]
+    fn edit_text() {
+        let mut subs = utils::subs_from_texts(&["Colour", "Text", "Colours"]);
+        let untouched = subs.inner[1].text.as_ptr();
+        assert_eq!(subs.replace_text("Colour", "Color"), 2);
+        assert_eq!(subs.inner[0].text, "Color");
+        assert_eq!(subs.inner[2].text, "Colors");
+        assert_eq!(subs.inner[1].text.as_ptr(), untouched);
+        assert_eq!(subs.replace_text("Colour", "Color"), 0);
+        assert_eq!(subs.replace_text("", "Color"), 0);
+
+        assert_eq!(subs.edit_text(|text| Cow::Owned(text.to_owned())), 0);
+        assert_eq!(subs.edit_text(|text| Cow::Owned(text.to_uppercase())), 3);
+        assert_eq!(subs.inner[1].text, "TEXT");
+    }
+
+    #[test]
+    #[cfg(feature = "text-tools")]
+    fn replace_regex() {
+        let mut subs = utils::subs_from_texts(&["Color", "Text", "Colors"]);
+        let regex = Regex::new(r"(\w+)s\b").unwrap();
+        assert_eq!(subs.replace_regex(&regex, "many $1"), 1);
+        assert_eq!(subs.inner[2].text, "many Color");
+    }
+
+    #[test]
+    fn truncate_texts() {
+        let mut subs = utils::subs_from_texts(&["Short", "A much longer text"]);
+        assert_eq!(subs.truncate_texts(10), 1);
+        assert_eq!(subs.inner[0].text, "Short");
+        assert_eq!(subs.inner[1].text, "A much…");
+        assert_eq!(subs.truncate_texts(10), 0);
+    }
+}
@@ -0,0 +1,144 @@
+use std::io::{Error, ErrorKind};
+
+use crate::timestamp::Timestamp;
+use crate::subtitles::Subtitles;
+use crate::error::StructureError;
+use crate::timeline;
+use crate::utils;
+
+/// Chain of edits, see ```Subtitles::edit```.
+///
+/// Steps don't check the invariants of ```Subtitles```, it's done once by ```finish```.
+#[derive(Debug, Clone)]
+pub struct Editor {
+    subs: Subtitles,
+}
+
+impl Subtitles {
+    /// Starts a chain of edits, e.g.
+    /// ```subs.edit().shift(500).strip_tags().enforce_min_gap(100).renumber().finish()```.
+    pub fn edit(self) -> Editor {
+        Editor { subs: self }
+    }
+}
+
+impl Editor {
+    /// Moves all lines and their words by ```offset``` miliseconds, clamping at zero.
+    pub fn shift(mut self, offset: i64) -> Editor {
+        for line in &mut self.subs.inner {
+            timeline::shift_line(line, offset);
+        }
+        self
+    }
+
+    /// Removes tags from all lines' texts.
+    pub fn strip_tags(mut self) -> Editor {
+        self.subs.edit_text(|text| if text.contains('<') || text.contains('{') {
+            utils::strip_tags(text).into()
+        } else {
+            text.into()
+        });
+        self
+    }
+
+    /// Ends lines earlier so that at least ```gap``` miliseconds pass before the next line,
+    /// lines aren't cut shorter than their start.
+    pub fn enforce_min_gap(mut self, gap: u64) -> Editor {
+        for i in 1..self.subs.inner.len() {
+            let next = self.subs.inner[i].start.total_miliseconds();
+            let line = &mut self.subs.inner[i - 1];
+            let end = line.end.total_miliseconds();
+            if end + gap > next {
+                let cut = next.saturating_sub(gap).max(line.start.total_miliseconds());
+                line.end = Timestamp::from_miliseconds(cut.min(end));
+            }
+        }
+        self
+    }
+
+    /// Replaces all occurrences of ```from``` with ```to```, see ```Subtitles::replace_text```.
+    pub fn replace_text(mut self, from: &str, to: &str) -> Editor {
+        self.subs.replace_text(from, to);
+        self
+    }
+
+    /// Renumbers lines consecutively starting from 1.
+    pub fn renumber(mut self) -> Editor {
+        self.subs.renumber();
+        self
+    }
+
+    /// Applies ```f``` to the subtitles, for edits without a dedicated step.
+    pub fn apply<F: FnOnce(&mut Subtitles)>(mut self, f: F) -> Editor {
+        f(&mut self.subs);
+        self
+    }
+
+    /// Checks that lines don't end before they start, are sorted, don't overlap
+    /// and are numbered consecutively from 1, and returns the edited subtitles.
+    pub fn finish(self) -> Result<Subtitles, Error> {
+        let invalid = |message: String| Err(Error::new(ErrorKind::InvalidData, message));
+
+        for (i, line) in self.subs.inner.iter().enumerate() {
+            if line.index != i as u32 + 1 {
+                return Err(StructureError::IndexMismatch {
+                        index: i + 1,
+                        found: line.index,
+                    }
+                    .into());
+            }
+            if line.start > line.end {
+                return invalid(format!("Line {} ends before it starts", line.index));
+            }
+            if i > 0 && self.subs.inner[i - 1].end > line.start {
+                return invalid(format!("Line {} overlaps the previous line", line.index));
+            }
+        }
+        Ok(self.subs)
+    }
+}
+
+#[cfg(test)]
+mod editor_tests {
+    use crate::timestamp::Timestamp;
+    use crate::subline::{SubLine, Word};
+    use crate::adapters::SubLineIterator;
+    use crate::utils;
+
+    #[test]
+    fn edit() {
+        let mut subs = utils::subs_from_texts(&["<i>One</i>", "Two", "{\\an8}Three"]);
+        subs.inner[2].start = Timestamp::from_miliseconds(3050);
+        subs.inner.remove(0);
+
+        let subs = subs.edit().shift(-1000).strip_tags().enforce_min_gap(100).renumber().finish().unwrap();
+        let timing: Vec<_> = subs.inner
+            .iter()
+            .map(|line| (line.index, &*line.text, line.start.total_miliseconds(), line.end.total_miliseconds()))
+            .collect();
+        assert_eq!(timing, vec![(1, "Two", 1000, 1950), (2, "Three", 2050, 4000)]);
+    }
+
+    #[test]
+    fn shift_words() {
+        let mut subs = utils::subs_from_texts(&["One", "Two"]);
+        subs.inner[1].words = vec![Word {
+                                       start: Timestamp::from_miliseconds(2000),
+                                       end: Timestamp::from_miliseconds(2500),
+                                       text: "Two".to_owned(),
+                                   }];
+        let shifted: Vec<SubLine> = subs.clone().into_iter().shifted(-500).collect();
+        let edited = subs.edit().shift(-500).finish().unwrap();
+        assert_eq!(edited.inner, shifted);
+        assert_eq!(edited.inner[1].words[0].start, Timestamp::from_miliseconds(1500));
+    }
+
+    #[test]
+    fn finish() {
+        let subs = utils::subs_from_texts(&["One", "Two"]);
+        assert!(subs.clone().edit().apply(|subs| subs.inner.swap(0, 1)).finish().is_err());
+        assert!(subs.clone().edit().apply(|subs| subs.inner[0].end = Timestamp::from_miliseconds(2500)).finish().is_err());
+        assert!(subs.clone().edit().apply(|subs| { subs.inner.remove(0); }).finish().is_err());
+        assert!(subs.edit().apply(|subs| { subs.inner.remove(0); }).renumber().finish().is_ok());
+    }
+}
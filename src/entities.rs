@@ -0,0 +1,123 @@
+use std::borrow::Cow;
+
+use crate::subtitles::Subtitles;
+#[cfg(any(feature = "formats-webvtt", test))]
+use crate::tags::{self, Token};
+
+static NAMED: &[(&str, char)] = &[("amp", '&'),
+                                   ("lt", '<'),
+                                   ("gt", '>'),
+                                   ("quot", '"'),
+                                   ("apos", '\''),
+                                   ("nbsp", '\u{a0}'),
+                                   ("hellip", '…'),
+                                   ("ndash", '–'),
+                                   ("mdash", '—'),
+                                   ("lsquo", '‘'),
+                                   ("rsquo", '’'),
+                                   ("ldquo", '“'),
+                                   ("rdquo", '”'),
+                                   ("shy", '\u{ad}')];
+
+/// Replaces html entities (```&amp;```, ```&#8217;```, ```&#x2019;```)
+/// with characters, unknown entities are kept. Text without entities is borrowed.
+pub fn decode(text: &str) -> Cow<'_, str> {
+    if !text.contains('&') {
+        return Cow::Borrowed(text);
+    }
+    let mut result = String::new();
+    let mut last = 0;
+    for (i, _) in text.match_indices('&') {
+        let entity = match entity(&text[i + 1..]) {
+            Some(entity) => entity,
+            None => continue,
+        };
+        let decoded = if let Some(hex) = entity.strip_prefix("#x").or_else(|| entity.strip_prefix("#X")) {
+            u32::from_str_radix(hex, 16).ok().and_then(::std::char::from_u32)
+        } else if let Some(decimal) = entity.strip_prefix('#') {
+            decimal.parse().ok().and_then(::std::char::from_u32)
+        } else {
+            NAMED.iter().find(|&&(name, _)| name == entity).map(|&(_, c)| c)
+        };
+        if let Some(c) = decoded {
+            result.push_str(&text[last..i]);
+            result.push(c);
+        } else {
+            result.push_str(&text[last..i + entity.len() + 2]);
+        }
+        last = i + entity.len() + 2;
+    }
+    if last == 0 {
+        return Cow::Borrowed(text);
+    }
+    result.push_str(&text[last..]);
+    Cow::Owned(result)
+}
+
+/// Name or number of an entity at the start of ```text``` (after ```&```),
+/// i.e. ```#``` followed by up to 7 digits, ```#x``` followed by up to 6 hex digits or letters, ending with ```;```.
+fn entity(text: &str) -> Option<&str> {
+    let (prefix, max_len, is_valid): (usize, usize, fn(&u8) -> bool) = if text.starts_with("#x") || text.starts_with("#X") {
+        (2, 6, u8::is_ascii_hexdigit)
+    } else if text.starts_with('#') {
+        (1, 7, u8::is_ascii_digit)
+    } else {
+        (0, usize::MAX, u8::is_ascii_alphabetic)
+    };
+    let len = text.as_bytes()[prefix..].iter().take_while(|c| is_valid(c)).count();
+    if len == 0 || len > max_len || text.as_bytes().get(prefix + len) != Some(&b';') {
+        return None;
+    }
+    Some(&text[..prefix + len])
+}
+
+/// Escapes ```&```, ```<``` and ```>``` in the text between tags,
+/// so the text can be put into html-like formats. Tags are kept as is.
+#[cfg(any(feature = "formats-webvtt", test))]
+pub fn escape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for token in tags::tokenize(text) {
+        match token {
+            Token::Text(text) => {
+                for c in text.chars() {
+                    match c {
+                        '&' => result.push_str("&amp;"),
+                        '<' => result.push_str("&lt;"),
+                        '>' => result.push_str("&gt;"),
+                        c => result.push(c),
+                    }
+                }
+            }
+            token => result.push_str(token.raw()),
+        }
+    }
+    result
+}
+
+impl Subtitles {
+    /// Replaces html entities in all lines' texts with characters,
+    /// see also ```ParseOptions::decode_entities```.
+    pub fn decode_entities(&mut self) {
+        self.edit_text(decode);
+    }
+}
+
+#[cfg(test)]
+mod entities_tests {
+    use super::*;
+
+    #[test]
+    fn _decode() {
+        assert_eq!(decode("Tom &amp; Jerry&#8217;s &#x2014; &lt;3 &nbsp;"),
+                   "Tom & Jerry’s — <3 \u{a0}");
+        assert_eq!(decode("&unknown; &#xFFFFFF; & ;"), "&unknown; &#xFFFFFF; & ;");
+    }
+
+    #[test]
+    fn _escape() {
+        assert_eq!(escape("<i>Fish & chips</i> <3 -> <b>"), "<i>Fish &amp; chips</i> &lt;3 -&gt; <b>");
+        assert_eq!(escape("a < b && c > d"), "a &lt; b &amp;&amp; c &gt; d");
+        assert_eq!(escape("x <bold> </ i> y"), "x &lt;bold&gt; &lt;/ i&gt; y");
+        assert_eq!(decode(&escape("a < b && c > d")), "a < b && c > d");
+    }
+}
@@ -0,0 +1,76 @@
+use std::error;
+use std::fmt::{self, Display, Formatter};
+use std::io::{self, ErrorKind};
+
+use crate::timestamp::Timestamp;
+
+/// Error returned by lookups when ```Subtitles``` don't have the requested line
+/// or their inner structure is broken.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StructureError {
+    /// There is no line with such index.
+    NotFound { index: usize },
+    /// Line at the position of ```index``` has another index, e.g. inner vector
+    /// is not sorted or lines were not renumbered after editing.
+    IndexMismatch { index: usize, found: u32 },
+}
+
+impl Display for StructureError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            StructureError::NotFound { index } => write!(f, "There is no line with index {}", index),
+            StructureError::IndexMismatch { index, found } => {
+                write!(f,
+                       "Subtitles's inner structure is broken, line {} has index {}",
+                       index,
+                       found)
+            }
+        }
+    }
+}
+
+impl error::Error for StructureError {}
+
+impl From<StructureError> for io::Error {
+    fn from(error: StructureError) -> io::Error {
+        io::Error::new(ErrorKind::InvalidData, error)
+    }
+}
+
+/// Error returned by checked edits such as ```Subtitles::try_push```
+/// when the edited line would break the structure of ```Subtitles```.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum EditError {
+    /// Line's index doesn't follow the index of the last line.
+    InvalidIndex { index: u32, expected: u32 },
+    /// Line's index is zero or greater than the number of lines plus one.
+    IndexOutOfRange { index: u32, len: usize },
+    /// Line's start is bigger than its end.
+    InvalidTiming { start: Timestamp, end: Timestamp },
+    /// Line would overlap the line with given index.
+    Overlap { index: u32 },
+}
+
+impl Display for EditError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            EditError::InvalidIndex { index, expected } => {
+                write!(f, "Line has index {}, but {} was expected", index, expected)
+            }
+            EditError::IndexOutOfRange { index, len } => {
+                write!(f, "Index {} is out of range for {} lines", index, len)
+            }
+            EditError::InvalidTiming { .. } => write!(f, "Line's start is bigger than its end"),
+            EditError::Overlap { index } => write!(f, "Line overlaps line {}", index),
+        }
+    }
+}
+
+impl error::Error for EditError {}
+
+impl From<EditError> for io::Error {
+    fn from(error: EditError) -> io::Error {
+        io::Error::new(ErrorKind::InvalidInput, error)
+    }
+}
@@ -0,0 +1,63 @@
+use crate::subtitles::Subtitles;
+use crate::entities;
+use crate::utils;
+
+pub(crate) const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+impl Subtitles {
+    /// Hash of the timing and texts, equal for files which differ only in indices,
+    /// tags, html entities, newline styles or spacing. It's the same across
+    /// platforms and versions, so it can be stored, e.g. to find duplicates in an archive.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hash = FNV_OFFSET;
+        for line in self {
+            let start = line.start.total_miliseconds().to_le_bytes();
+            let end = line.end.total_miliseconds().to_le_bytes();
+            let text = normalized_text(&line.text);
+            // 0xff never occurs in UTF-8, so lines can't run into each other
+            for bytes in &[&start[..], &end[..], text.as_bytes(), &[0xff]] {
+                hash = fnv(hash, bytes);
+            }
+        }
+        hash
+    }
+}
+
+/// Continues FNV-1a ```hash``` with ```bytes```, start with ```FNV_OFFSET```.
+pub(crate) fn fnv(mut hash: u64, bytes: &[u8]) -> u64 {
+    for &byte in bytes {
+        hash = (hash ^ byte as u64).wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
+
+/// Text without tags and entities, with words separated by single spaces.
+pub(crate) fn normalized_text(text: &str) -> String {
+    let text = utils::strip_tags(text);
+    entities::decode(&text).split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod fingerprint_tests {
+    use super::*;
+    use crate::timestamp::Timestamp;
+    use crate::utils;
+
+    #[test]
+    fn fingerprint() {
+        let subs = utils::subs_from_texts(&["Tom & Jerry", "Two\r\nlines"]);
+        let mut same = utils::subs_from_texts(&["<i>Tom &amp; Jerry</i>", "Two\nlines "]);
+        same.inner[0].index = 10;
+        assert_eq!(subs.fingerprint(), same.fingerprint());
+        // Stored fingerprints must stay valid
+        assert_eq!(Subtitles::default().fingerprint(), FNV_OFFSET);
+        assert_eq!(utils::subs_from_texts(&["a"]).fingerprint(), 0x1c0e_f283_12b2_4768);
+
+        let mut shifted = subs.clone();
+        shifted.inner[1].end = Timestamp::from_miliseconds(3001);
+        assert_ne!(subs.fingerprint(), shifted.fingerprint());
+        let joined = utils::subs_from_texts(&["Tom & JerryTwo lines"]);
+        assert_ne!(subs.fingerprint(), joined.fingerprint());
+    }
+}
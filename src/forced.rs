@@ -0,0 +1,52 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::subtitles::Subtitles;
+
+impl Subtitles {
+    /// Makes a forced track, showing only foreign dialogue and signs, from the full track.
+    /// Lines are taken if they are marked with a ```{forced}``` tag (which is removed),
+    /// start with a language note like ```[In French]``` or ```(speaking Russian)```,
+    /// or their id is in ```ids```. Lines are renumbered, metadata is kept.
+    pub fn extract_forced(reference_full: &Subtitles, ids: &[&str]) -> Subtitles {
+        let mut forced = Subtitles { metadata: reference_full.metadata.clone(), ..Subtitles::default() };
+        for line in reference_full {
+            let listed = line.id.as_ref().is_some_and(|id| ids.contains(&&**id));
+            if !(listed || FORCED_TAG.is_match(&line.text) || LANGUAGE_NOTE.is_match(&line.text)) {
+                continue;
+            }
+            let mut line = line.clone();
+            line.text = FORCED_TAG.replace_all(&line.text, "").trim().to_owned();
+            forced.inner.push(line);
+        }
+        forced.renumber();
+        forced
+    }
+}
+
+static FORCED_TAG: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i)\{\s*forced\s*\}").unwrap());
+static LANGUAGE_NOTE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)^\s*(?:<[^>]*>\s*)*[\[(]\s*(?:in|speaking|speaks)\s+[a-z]+\s*[\])]").unwrap());
+
+#[cfg(test)]
+mod forced_tests {
+    use super::*;
+
+    #[test]
+    fn extract_forced() {
+        let mut full = subtitles![(0, 1000, "Where are you going?"),
+                                  (2000, 3000, "{forced}Je vais à Paris."),
+                                  (4000, 5000, "<i>[In Spanish] Hola.</i>"),
+                                  (6000, 7000, "Sign: EXIT"),
+                                  (8000, 9000, "[door slams]")];
+        full.inner[3].id = Some("sign-exit".to_owned());
+
+        let forced = Subtitles::extract_forced(&full, &["sign-exit"]);
+        let texts: Vec<_> = forced.inner.iter().map(|line| (line.index, &*line.text)).collect();
+        assert_eq!(texts,
+                   vec![(1, "Je vais à Paris."), (2, "<i>[In Spanish] Hola.</i>"), (3, "Sign: EXIT")]);
+        assert_eq!(forced.inner[0].start, full.inner[1].start);
+        assert_eq!(Subtitles::extract_forced(&full, &[]).len(), 2);
+    }
+}
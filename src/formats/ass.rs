@@ -0,0 +1,254 @@
+//! Advanced SubStation Alpha (```.ass```) format.
+
+use std::fmt::Write;
+use std::str::FromStr;
+
+use crate::timestamp::Timestamp;
+use crate::subtitles::Subtitles;
+use crate::subline::SubLine;
+use crate::tags::{self, Tag, Token};
+use crate::color::Color;
+
+/// Style of the ```[V4+ Styles]``` section.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssStyle {
+    pub name: String,
+    pub font: String,
+    pub size: f64,
+    pub primary_color: Color,
+    pub secondary_color: Color,
+    pub outline_color: Color,
+    pub back_color: Color,
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub outline: f64,
+    pub shadow: f64,
+    /// Numpad-like alignment, ```2``` is bottom center.
+    pub alignment: u8,
+    pub margin_left: u32,
+    pub margin_right: u32,
+    pub margin_vertical: u32,
+}
+
+impl Default for AssStyle {
+    fn default() -> AssStyle {
+        AssStyle {
+            name: "Default".to_owned(),
+            font: "Arial".to_owned(),
+            size: 20.0,
+            primary_color: Color::rgb(255, 255, 255),
+            secondary_color: Color::rgb(255, 0, 0),
+            outline_color: Color::rgb(0, 0, 0),
+            back_color: Color::rgb(0, 0, 0),
+            bold: false,
+            italic: false,
+            underline: false,
+            outline: 2.0,
+            shadow: 2.0,
+            alignment: 2,
+            margin_left: 10,
+            margin_right: 10,
+            margin_vertical: 10,
+        }
+    }
+}
+
+/// Script resolution and styles of the exported file.
+/// Lines use the first style, tags of their text become inline overrides.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StyleSheet {
+    pub play_res_x: u32,
+    pub play_res_y: u32,
+    pub styles: Vec<AssStyle>,
+}
+
+impl Default for StyleSheet {
+    fn default() -> StyleSheet {
+        StyleSheet {
+            play_res_x: 384,
+            play_res_y: 288,
+            styles: vec![AssStyle::default()],
+        }
+    }
+}
+
+/// Formats ```Subtitles``` as ASS with given styles.
+pub fn to_string(subs: &Subtitles, style_sheet: &StyleSheet) -> String {
+    write(subs, style_sheet, false)
+}
+
+/// Formats ```Subtitles``` as ASS karaoke: every word of the lines
+/// with word timing gets a ```\k``` tag with its duration in centiseconds,
+/// pauses between words get empty ```\k``` tags.
+/// Lines without word timing are formatted as in ```to_string```.
+pub fn to_ass_karaoke(subs: &Subtitles, style_sheet: &StyleSheet) -> String {
+    write(subs, style_sheet, true)
+}
+
+fn write(subs: &Subtitles, style_sheet: &StyleSheet, karaoke: bool) -> String {
+    let mut result = String::from("[Script Info]\n");
+    if let Some(ref title) = subs.metadata.title {
+        writeln!(result, "Title: {}", title).unwrap();
+    }
+    write!(result,
+           "ScriptType: v4.00+\nPlayResX: {}\nPlayResY: {}\n\n[V4+ Styles]\n\
+            Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, \
+            BackColour, Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, \
+            BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding\n",
+           style_sheet.play_res_x,
+           style_sheet.play_res_y)
+        .unwrap();
+
+    let default_style = AssStyle::default();
+    let styles = if style_sheet.styles.is_empty() {
+        ::std::slice::from_ref(&default_style)
+    } else {
+        &style_sheet.styles[..]
+    };
+    let flag = |enabled: bool| if enabled { -1 } else { 0 };
+    for style in styles {
+        writeln!(result,
+                 "Style: {},{},{},{},{},{},{},{},{},{},0,100,100,0,0,1,{},{},{},{},{},{},1",
+                 style.name,
+                 style.font,
+                 style.size,
+                 style.primary_color.to_ass_style(),
+                 style.secondary_color.to_ass_style(),
+                 style.outline_color.to_ass_style(),
+                 style.back_color.to_ass_style(),
+                 flag(style.bold),
+                 flag(style.italic),
+                 flag(style.underline),
+                 style.outline,
+                 style.shadow,
+                 style.alignment,
+                 style.margin_left,
+                 style.margin_right,
+                 style.margin_vertical)
+            .unwrap();
+    }
+
+    result.push_str("\n[Events]\nFormat: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text\n");
+    for line in subs {
+        writeln!(result,
+                 "Dialogue: 0,{},{},{},,0,0,0,,{}",
+                 timestamp(line.start),
+                 timestamp(line.end),
+                 styles[0].name,
+                 if karaoke && !line.words.is_empty() {
+                     karaoke_text(line)
+                 } else {
+                     text(&line.text)
+                 })
+            .unwrap();
+    }
+    result
+}
+
+/// Karaoke text of the line from its words.
+fn karaoke_text(line: &SubLine) -> String {
+    // Durations are differences of rounded times, so rounding errors don't add up
+    let centiseconds = |time: Timestamp| (time.total_miliseconds() + 5) / 10;
+    let mut result = String::new();
+    let mut time = centiseconds(line.start);
+
+    for (i, word) in line.words.iter().enumerate() {
+        let start = centiseconds(word.start).max(time);
+        let end = centiseconds(word.end).max(start);
+        if start > time {
+            write!(result, "{{\\k{}}}", start - time).unwrap();
+        }
+        if i > 0 {
+            result.push(' ');
+        }
+        write!(result, "{{\\k{}}}{}", end - start, text(word.text.trim())).unwrap();
+        time = end;
+    }
+    result
+}
+
+/// Converts SubRip tags into ASS override tags and newlines into ```\N```.
+fn text(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for token in tags::tokenize(text) {
+        match token {
+            Token::Text(text) => result.push_str(&text.replace("\r\n", "\\N").replace('\n', "\\N")),
+            Token::Open { ref name, attributes, .. } => {
+                match Tag::from_name(name) {
+                    Some(Tag::Font) => {
+                        if let Some(color) = tags::color_attribute(attributes).and_then(|color| Color::from_str(&color).ok()) {
+                            write!(result, "{{\\c{}}}", color.to_ass()).unwrap();
+                        }
+                    }
+                    Some(tag) => write!(result, "{{\\{}1}}", tag.name()).unwrap(),
+                    None => {}
+                }
+            }
+            Token::Close { ref name, .. } => {
+                match Tag::from_name(name) {
+                    Some(Tag::Font) => result.push_str("{\\c}"),
+                    Some(tag) => write!(result, "{{\\{}0}}", tag.name()).unwrap(),
+                    None => {}
+                }
+            }
+        }
+    }
+    result
+}
+
+/// ASS timestamps have centiseconds precision.
+fn timestamp(time: Timestamp) -> String {
+    format!("{}:{:02}:{:02}.{:02}",
+            time.hours,
+            time.minutes,
+            time.seconds,
+            time.miliseconds / 10)
+}
+
+#[cfg(test)]
+mod ass_tests {
+    use super::*;
+    use crate::subline::Word;
+    use crate::utils;
+
+    #[test]
+    fn _to_string() {
+        let subs = utils::subs_from_texts(&["<i>One</i>", "Two\r\n<font color=\"#FF8000\">lines</font>"]);
+        let style_sheet = StyleSheet {
+            styles: vec![AssStyle {
+                             name: "Burn".to_owned(),
+                             font: "Helvetica".to_owned(),
+                             size: 32.0,
+                             bold: true,
+                             alignment: 8,
+                             ..AssStyle::default()
+                         }],
+            ..StyleSheet::default()
+        };
+
+        let ass = to_string(&subs, &style_sheet);
+        assert!(ass.contains("\nStyle: Burn,Helvetica,32,&H00FFFFFF,&H000000FF,&H00000000,&H00000000,-1,0,0,\
+                              0,100,100,0,0,1,2,2,8,10,10,10,1\n"));
+        assert!(ass.contains("\nDialogue: 0,0:00:00.00,0:00:01.00,Burn,,0,0,0,,{\\i1}One{\\i0}\n"));
+        assert!(ass.ends_with("\nDialogue: 0,0:00:02.00,0:00:03.00,Burn,,0,0,0,,Two\\N{\\c&H0080FF&}lines{\\c}\n"));
+    }
+
+    #[test]
+    fn karaoke() {
+        let mut subs = utils::subs_from_texts(&["Never gonna give", "Plain"]);
+        let word = |start: u64, end: u64, text: &str| {
+            Word {
+                start: Timestamp::from_miliseconds(start),
+                end: Timestamp::from_miliseconds(end),
+                text: text.to_owned(),
+            }
+        };
+        subs.inner[0].words = vec![word(104, 333, "Never"), word(333, 501, "<i>gonna</i>"), word(700, 1000, "give")];
+
+        let ass = to_ass_karaoke(&subs, &StyleSheet::default());
+        assert!(ass.contains(",,{\\k10}{\\k23}Never {\\k17}{\\i1}gonna{\\i0}{\\k20} {\\k30}give\n"));
+        assert!(ass.ends_with(",,Plain\n"));
+        assert!(!to_string(&subs, &StyleSheet::default()).contains("\\k"));
+    }
+}
@@ -0,0 +1,190 @@
+//! Conversion of ```Subtitles``` into the written formats, reporting what gets lost.
+
+use std::fmt::{self, Display, Formatter};
+
+use crate::subtitles::Subtitles;
+use crate::subline::SubLine;
+use crate::metadata::FrameRate;
+use crate::tags::{self, Tag, Token};
+use crate::formats::{webvtt, ass, dcp, spruce, encore};
+
+/// Format which ```Subtitles``` can be written to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SubtitleFormat {
+    SubRip,
+    WebVtt,
+    Ass,
+    Dcp,
+    Spruce,
+    Encore,
+}
+
+/// Information which some formats can't represent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Feature {
+    /// Italic, bold and underline tags.
+    Styles,
+    /// ```<font color="...">``` tags.
+    Colors,
+    /// Cue settings, see ```SubLine::settings```.
+    Positions,
+    /// See ```SubLine::comment```.
+    Comments,
+    /// See ```SubLine::words```.
+    WordTiming,
+    /// ```Metadata::title```
+    Title,
+    /// ```Metadata::language```
+    Language,
+}
+
+static FEATURES: &[Feature] = &[Feature::Styles,
+                                Feature::Colors,
+                                Feature::Positions,
+                                Feature::Comments,
+                                Feature::WordTiming,
+                                Feature::Title,
+                                Feature::Language];
+
+impl Display for Feature {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            Feature::Styles => "styles",
+            Feature::Colors => "colors",
+            Feature::Positions => "positions",
+            Feature::Comments => "comments",
+            Feature::WordTiming => "word timing",
+            Feature::Title => "title",
+            Feature::Language => "language",
+        })
+    }
+}
+
+impl SubtitleFormat {
+    /// Returns ```true``` if the format keeps the feature when written by this crate.
+    pub fn supports(&self, feature: Feature) -> bool {
+        use self::Feature::*;
+        match *self {
+            SubtitleFormat::SubRip => matches!(feature, Styles | Colors),
+            SubtitleFormat::WebVtt => matches!(feature, Styles | Positions | Comments),
+            SubtitleFormat::Ass => matches!(feature, Styles | Colors | WordTiming | Title),
+            SubtitleFormat::Dcp => matches!(feature, Styles | Colors | Title | Language),
+            SubtitleFormat::Spruce => feature == Styles,
+            SubtitleFormat::Encore => false,
+        }
+    }
+}
+
+/// Features lost by a conversion.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ConversionReport {
+    /// Lost features with indices of the lines which had them,
+    /// empty for track-level features such as ```Feature::Title```.
+    pub lost: Vec<(Feature, Vec<u32>)>,
+}
+
+impl ConversionReport {
+    /// Returns ```true``` if nothing is lost.
+    pub fn is_empty(&self) -> bool {
+        self.lost.is_empty()
+    }
+
+    /// Returns ```true``` if the feature is lost.
+    pub fn loses(&self, feature: Feature) -> bool {
+        self.lost.iter().any(|&(lost, _)| lost == feature)
+    }
+}
+
+impl Display for ConversionReport {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for &(feature, ref lines) in &self.lost {
+            if lines.is_empty() {
+                writeln!(f, "{} dropped", feature)?;
+            } else {
+                writeln!(f, "{} dropped in {} lines", feature, lines.len())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns ```true``` if the line uses the feature, track-level features are never used by lines.
+fn has(line: &SubLine, feature: Feature) -> bool {
+    let tag = |check: &dyn Fn(Tag, &str) -> bool| {
+        tags::tokenize(&line.text).into_iter().any(|token| match token {
+            Token::Open { ref name, attributes, .. } => Tag::from_name(name).map(|tag| check(tag, attributes)).unwrap_or(false),
+            _ => false,
+        })
+    };
+    match feature {
+        Feature::Styles => tag(&|tag, _| tag == Tag::Italic || tag == Tag::Bold || tag == Tag::Underline),
+        Feature::Colors => tag(&|tag, attributes| tag == Tag::Font && tags::color_attribute(attributes).is_some()),
+        Feature::Positions => !line.settings.is_empty(),
+        Feature::Comments => line.comment.is_some(),
+        Feature::WordTiming => !line.words.is_empty(),
+        Feature::Title | Feature::Language => false,
+    }
+}
+
+/// Lists features of ```subs``` which ```target``` can't represent.
+pub fn check(subs: &Subtitles, target: SubtitleFormat) -> ConversionReport {
+    let mut report = ConversionReport::default();
+    for &feature in FEATURES {
+        if target.supports(feature) {
+            continue;
+        }
+        let track_level = match feature {
+            Feature::Title => subs.metadata.title.is_some(),
+            Feature::Language => subs.metadata.language.is_some(),
+            _ => false,
+        };
+        let lines: Vec<u32> = subs.inner.iter().filter(|line| has(line, feature)).map(|line| line.index).collect();
+        if track_level || !lines.is_empty() {
+            report.lost.push((feature, lines));
+        }
+    }
+    report
+}
+
+/// Writes ```subs``` in ```target``` format with default options and reports lost features.
+/// ASS is written as karaoke for the lines with word timing,
+/// frame-based formats use ```Metadata::fps``` or 25 fps.
+pub fn convert(subs: &Subtitles, target: SubtitleFormat) -> (String, ConversionReport) {
+    let fps = subs.metadata.fps.unwrap_or_else(|| FrameRate::new(25, 1));
+    let content = match target {
+        SubtitleFormat::SubRip => subs.to_string(),
+        SubtitleFormat::WebVtt => webvtt::to_string(subs),
+        SubtitleFormat::Ass => ass::to_ass_karaoke(subs, &ass::StyleSheet::default()),
+        SubtitleFormat::Dcp => dcp::to_string(subs, &dcp::DcpOptions::default()),
+        SubtitleFormat::Spruce => spruce::to_string(subs, fps),
+        SubtitleFormat::Encore => encore::to_string(subs, fps),
+    };
+    (content, check(subs, target))
+}
+
+#[cfg(test)]
+mod convert_tests {
+    use super::*;
+    use std::str::FromStr;
+    use crate::cue_settings::CueSettings;
+    use crate::utils;
+
+    #[test]
+    fn _convert() {
+        let mut subs = utils::subs_from_texts(&["<i>One</i>", "<font color=\"red\">Two</font>", "Three"]);
+        subs.inner[2].settings = CueSettings::from_str("align:start").unwrap();
+        subs.inner[2].comment = Some("Note".to_owned());
+        subs.metadata.title = Some("Title".to_owned());
+
+        let (content, report) = convert(&subs, SubtitleFormat::SubRip);
+        assert_eq!(content, subs.to_string());
+        assert_eq!(report.lost,
+                   vec![(Feature::Positions, vec![3]), (Feature::Comments, vec![3]), (Feature::Title, vec![])]);
+        assert_eq!(report.to_string(), "positions dropped in 1 lines\ncomments dropped in 1 lines\ntitle dropped\n");
+
+        let report = check(&subs, SubtitleFormat::WebVtt);
+        assert!(report.loses(Feature::Colors) && !report.loses(Feature::Styles));
+        assert_eq!(check(&subs, SubtitleFormat::Encore).lost.len(), 5);
+        assert!(check(&utils::subs_from_texts(&["Plain"]), SubtitleFormat::Encore).is_empty());
+    }
+}
@@ -0,0 +1,317 @@
+//! Digital cinema subtitle XML: Interop CineCanvas and SMPTE 428-7 Timed Text.
+
+use std::collections::hash_map::DefaultHasher;
+use std::fmt::Write;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+use crate::timestamp::Timestamp;
+use crate::subtitles::Subtitles;
+use crate::tags::{self, Tag, Token};
+use crate::color::Color;
+use crate::utils;
+
+/// Subtitle XML flavour.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DcpStandard {
+    /// Interop ```DCSubtitle```, times in 4 ms ticks.
+    #[default]
+    Interop,
+    /// SMPTE 428-7 ```SubtitleReel```, times in frames of ```DcpOptions::edit_rate```.
+    Smpte,
+}
+
+/// Reel information and text appearance of the exported file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DcpOptions {
+    pub standard: DcpStandard,
+    /// Subtitle id without ```urn:uuid:```, generated from the content if ```None```.
+    pub id: Option<String>,
+    /// Movie title, ```Metadata::title``` is used if ```None```.
+    pub title: Option<String>,
+    /// Language, ```Metadata::language``` or ```"en"``` is used if ```None```.
+    pub language: Option<String>,
+    pub reel_number: u32,
+    /// Frames per second, used by SMPTE.
+    pub edit_rate: u32,
+    /// SMPTE issue date, e.g. ```"2024-05-01T12:00:00.000-00:00"```.
+    pub issue_date: String,
+    pub font_id: String,
+    /// Interop font file name or SMPTE font resource id.
+    pub font_uri: String,
+    pub font_size: u32,
+    pub color: Color,
+    pub border_color: Color,
+    pub fade_up: Timestamp,
+    pub fade_down: Timestamp,
+    /// Position of the bottom row from the bottom of the screen, in percent.
+    pub vertical_position: f64,
+    /// Distance between rows, in percent.
+    pub line_spacing: f64,
+}
+
+impl Default for DcpOptions {
+    fn default() -> DcpOptions {
+        DcpOptions {
+            standard: DcpStandard::Interop,
+            id: None,
+            title: None,
+            language: None,
+            reel_number: 1,
+            edit_rate: 24,
+            issue_date: "2000-01-01T00:00:00.000-00:00".to_owned(),
+            font_id: "Font1".to_owned(),
+            font_uri: "font.ttf".to_owned(),
+            font_size: 42,
+            color: Color::rgb(255, 255, 255),
+            border_color: Color::rgb(0, 0, 0),
+            fade_up: Timestamp::from_miliseconds(80),
+            fade_down: Timestamp::from_miliseconds(80),
+            vertical_position: 8.0,
+            line_spacing: 6.5,
+        }
+    }
+}
+
+/// Formats ```Subtitles``` as DCP subtitle XML. Rows of a line become
+/// separate ```<Text>``` elements, tags become ```<Font>``` attributes.
+pub fn to_string(subs: &Subtitles, options: &DcpOptions) -> String {
+    let smpte = options.standard == DcpStandard::Smpte;
+    let id = match options.id {
+        Some(ref id) => id.clone(),
+        None => uuid(subs),
+    };
+    let title = options.title.as_ref().or(subs.metadata.title.as_ref()).map(|title| escape(title)).unwrap_or_default();
+    let language = options.language
+        .clone()
+        .or_else(|| subs.metadata.language.as_ref().map(|language| language.as_str().to_owned()))
+        .unwrap_or_else(|| "en".to_owned());
+    let time = |time: Timestamp| if smpte {
+        frames(time, options.edit_rate)
+    } else {
+        ticks(time)
+    };
+
+    let mut result = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    if smpte {
+        write!(result,
+               "<SubtitleReel xmlns=\"http://www.smpte-ra.org/schemas/428-7/2010/DCST\" \
+                xmlns:xs=\"http://www.w3.org/2001/XMLSchema\">\n  <Id>urn:uuid:{}</Id>\n  \
+                <ContentTitleText>{}</ContentTitleText>\n  <IssueDate>{}</IssueDate>\n  \
+                <ReelNumber>{}</ReelNumber>\n  <Language>{}</Language>\n  <EditRate>{} 1</EditRate>\n  \
+                <TimeCodeRate>{}</TimeCodeRate>\n  <StartTime>00:00:00:00</StartTime>\n  \
+                <LoadFont ID=\"{}\">urn:uuid:{}</LoadFont>\n  <SubtitleList>\n",
+               id,
+               title,
+               escape(&options.issue_date),
+               options.reel_number,
+               escape(&language),
+               options.edit_rate,
+               options.edit_rate,
+               escape(&options.font_id),
+               escape(&options.font_uri))
+            .unwrap();
+    } else {
+        write!(result,
+               "<DCSubtitle Version=\"1.0\">\n  <SubtitleID>{}</SubtitleID>\n  <MovieTitle>{}</MovieTitle>\n  \
+                <ReelNumber>{}</ReelNumber>\n  <Language>{}</Language>\n  \
+                <LoadFont Id=\"{}\" URI=\"{}\"/>\n",
+               id,
+               title,
+               options.reel_number,
+               escape(&language),
+               escape(&options.font_id),
+               escape(&options.font_uri))
+            .unwrap();
+    }
+
+    writeln!(result,
+             "  <Font {}=\"{}\" Size=\"{}\" Color=\"{}\" Effect=\"border\" EffectColor=\"{}\">",
+             if smpte { "ID" } else { "Id" },
+             escape(&options.font_id),
+             options.font_size,
+             color(options.color),
+             color(options.border_color))
+        .unwrap();
+    let (valign, vposition) = if smpte { ("Valign", "Vposition") } else { ("VAlign", "VPosition") };
+
+    for (spot, line) in subs.inner.iter().enumerate() {
+        writeln!(result,
+                 "    <Subtitle SpotNumber=\"{}\" TimeIn=\"{}\" TimeOut=\"{}\" FadeUpTime=\"{}\" FadeDownTime=\"{}\">",
+                 spot + 1,
+                 time(line.start),
+                 time(line.end),
+                 fade(options.fade_up, options),
+                 fade(options.fade_down, options))
+            .unwrap();
+        let rows = text(&line.text);
+        for (i, row) in rows.iter().enumerate() {
+            writeln!(result,
+                     "      <Text {}=\"bottom\" {}=\"{:.1}\">{}</Text>",
+                     valign,
+                     vposition,
+                     options.vertical_position + (rows.len() - 1 - i) as f64 * options.line_spacing,
+                     row)
+                .unwrap();
+        }
+        result.push_str("    </Subtitle>\n");
+    }
+
+    result.push_str("  </Font>\n");
+    result.push_str(if smpte { "  </SubtitleList>\n</SubtitleReel>\n" } else { "</DCSubtitle>\n" });
+    result
+}
+
+/// Rows of the text with tags turned into nested ```<Font>``` elements,
+/// which are closed at the end of every row and reopened on the next one.
+fn text(text: &str) -> Vec<String> {
+    let mut rows = vec![String::new()];
+    // Attributes of the open tags, or None for the tags without a <Font> counterpart
+    let mut open: Vec<(String, Option<String>)> = Vec::new();
+
+    for token in tags::tokenize(text) {
+        match token {
+            Token::Text(text) => {
+                for (i, part) in text.split('\n').enumerate() {
+                    if i > 0 {
+                        for _ in open.iter().filter(|&(_, attributes)| attributes.is_some()) {
+                            rows.last_mut().unwrap().push_str("</Font>");
+                        }
+                        let mut row = String::new();
+                        for attributes in open.iter().filter_map(|(_, attributes)| attributes.as_ref()) {
+                            write!(row, "<Font {}>", attributes).unwrap();
+                        }
+                        rows.push(row);
+                    }
+                    rows.last_mut().unwrap().push_str(&escape(part.trim_end_matches('\r')));
+                }
+            }
+            Token::Open { ref name, attributes, .. } => {
+                let font = match Tag::from_name(name) {
+                    Some(Tag::Italic) => Some("Italic=\"yes\"".to_owned()),
+                    Some(Tag::Bold) => Some("Weight=\"bold\"".to_owned()),
+                    Some(Tag::Underline) => Some("Underlined=\"yes\"".to_owned()),
+                    Some(Tag::Font) => {
+                        tags::color_attribute(attributes)
+                            .and_then(|value| Color::from_str(&value).ok())
+                            .map(|value| format!("Color=\"{}\"", color(value)))
+                    }
+                    Some(Tag::Strikethrough) | None => None,
+                };
+                if let Some(ref attributes) = font {
+                    write!(rows.last_mut().unwrap(), "<Font {}>", attributes).unwrap();
+                }
+                open.push((name.clone(), font));
+            }
+            Token::Close { ref name, .. } => {
+                if let Some(position) = open.iter().rposition(|(open, _)| open == name) {
+                    // Closes the tags opened after the matching one too
+                    for (_, font) in open.drain(position..).rev() {
+                        if font.is_some() {
+                            rows.last_mut().unwrap().push_str("</Font>");
+                        }
+                    }
+                }
+            }
+        }
+    }
+    for _ in open.iter().filter(|&(_, attributes)| attributes.is_some()) {
+        rows.last_mut().unwrap().push_str("</Font>");
+    }
+    rows
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// ```AARRGGBB```
+fn color(color: Color) -> String {
+    format!("{:02X}{:02X}{:02X}{:02X}", color.alpha, color.red, color.green, color.blue)
+}
+
+/// Interop time, ```HH:MM:SS:TTT``` with 4 ms ticks.
+fn ticks(time: Timestamp) -> String {
+    format!("{:02}:{:02}:{:02}:{:03}",
+            time.hours,
+            time.minutes,
+            time.seconds,
+            time.miliseconds / 4)
+}
+
+/// SMPTE time, ```HH:MM:SS:FF```.
+fn frames(time: Timestamp, edit_rate: u32) -> String {
+    format!("{:02}:{:02}:{:02}:{:02}",
+            time.hours,
+            time.minutes,
+            time.seconds,
+            time.miliseconds * edit_rate.max(1) / 1000)
+}
+
+/// Interop fades are in ticks, SMPTE ones are timecodes.
+fn fade(time: Timestamp, options: &DcpOptions) -> String {
+    match options.standard {
+        DcpStandard::Interop => (time.total_miliseconds() / 4).to_string(),
+        DcpStandard::Smpte => frames(time, options.edit_rate),
+    }
+}
+
+/// UUID-formatted hash of the subtitles, so the same content gets the same id.
+fn uuid(subs: &Subtitles) -> String {
+    let mut hashes = [0u64; 2];
+    for (seed, hash) in hashes.iter_mut().enumerate() {
+        let mut hasher = DefaultHasher::new();
+        seed.hash(&mut hasher);
+        for line in subs {
+            (line.start.total_miliseconds(), line.end.total_miliseconds(), &line.text).hash(&mut hasher);
+        }
+        *hash = hasher.finish();
+    }
+    utils::uuid(hashes[0], hashes[1])
+}
+
+#[cfg(test)]
+mod dcp_tests {
+    use super::*;
+    use crate::utils;
+
+    #[test]
+    fn interop() {
+        let mut subs = utils::subs_from_texts(&["<i>One & only</i>", "Two\r\n<i>lines <font color=\"red\">here</font>\r\nand</i> there"]);
+        subs.metadata.title = Some("Feature".to_owned());
+        let xml = to_string(&subs, &DcpOptions::default());
+
+        assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<DCSubtitle Version=\"1.0\">\n  <SubtitleID>"));
+        assert!(xml.contains("<MovieTitle>Feature</MovieTitle>\n  <ReelNumber>1</ReelNumber>\n  <Language>en</Language>\n  \
+                              <LoadFont Id=\"Font1\" URI=\"font.ttf\"/>\n"));
+        assert!(xml.contains("\n    <Subtitle SpotNumber=\"1\" TimeIn=\"00:00:00:000\" TimeOut=\"00:00:01:000\" \
+                              FadeUpTime=\"20\" FadeDownTime=\"20\">\n      \
+                              <Text VAlign=\"bottom\" VPosition=\"8.0\"><Font Italic=\"yes\">One &amp; only</Font></Text>\n"));
+        assert!(xml.contains("<Text VAlign=\"bottom\" VPosition=\"21.0\">Two</Text>\n      \
+                              <Text VAlign=\"bottom\" VPosition=\"14.5\"><Font Italic=\"yes\">lines \
+                              <Font Color=\"FFFF0000\">here</Font></Font></Text>\n      \
+                              <Text VAlign=\"bottom\" VPosition=\"8.0\"><Font Italic=\"yes\">and</Font> there</Text>\n"));
+        assert!(xml.ends_with("  </Font>\n</DCSubtitle>\n"));
+        assert_eq!(xml, to_string(&subs, &DcpOptions::default()));
+    }
+
+    #[test]
+    fn smpte() {
+        let mut subs = utils::subs_from_texts(&["One"]);
+        subs.inner[0].end = Timestamp::new(0, 0, 1, 500);
+        let options = DcpOptions {
+            standard: DcpStandard::Smpte,
+            id: Some("0b5e2cd6-8f9e-4b6e-9a57-2f0d4b3a1c11".to_owned()),
+            language: Some("de".to_owned()),
+            font_uri: "7cbd55fc-cc8c-4b0e-9cd0-ad0e3e1a4b0d".to_owned(),
+            ..DcpOptions::default()
+        };
+        let xml = to_string(&subs, &options);
+        assert!(xml.contains("<Id>urn:uuid:0b5e2cd6-8f9e-4b6e-9a57-2f0d4b3a1c11</Id>"));
+        assert!(xml.contains("<Language>de</Language>\n  <EditRate>24 1</EditRate>\n  <TimeCodeRate>24</TimeCodeRate>"));
+        assert!(xml.contains("<LoadFont ID=\"Font1\">urn:uuid:7cbd55fc-cc8c-4b0e-9cd0-ad0e3e1a4b0d</LoadFont>"));
+        assert!(xml.contains("TimeIn=\"00:00:00:00\" TimeOut=\"00:00:01:12\" FadeUpTime=\"00:00:00:01\""));
+        assert!(xml.contains("<Text Valign=\"bottom\" Vposition=\"8.0\">One</Text>"));
+        assert!(xml.ends_with("  </SubtitleList>\n</SubtitleReel>\n"));
+    }
+}
@@ -0,0 +1,39 @@
+//! Adobe Encore text subtitles (```.txt```).
+
+use std::fmt::Write;
+
+use crate::subtitles::Subtitles;
+use crate::metadata::FrameRate;
+use crate::utils;
+
+/// Formats ```Subtitles``` as Encore script at given frame rate:
+/// a number, in and out timecodes and the text per line, the following rows
+/// of the text go on separate lines. NTSC rates use drop-frame timecodes.
+/// Tags are removed.
+pub fn to_string(subs: &Subtitles, fps: FrameRate) -> String {
+    let mut result = String::new();
+    for (number, line) in subs.inner.iter().enumerate() {
+        let text = utils::strip_tags(&line.text).replace("\r\n", "\n");
+        writeln!(result,
+                 "{} {} {} {}",
+                 number + 1,
+                 fps.timecode(line.start, true),
+                 fps.timecode(line.end, true),
+                 text)
+            .unwrap();
+    }
+    result
+}
+
+#[cfg(test)]
+mod encore_tests {
+    use super::*;
+
+    #[test]
+    fn _to_string() {
+        let subs = utils::subs_from_texts(&["<i>One</i>", "Two\r\nlines"]);
+        assert_eq!(to_string(&subs, FrameRate::new(25, 1)),
+                   "1 00:00:00:00 00:00:01:00 One\n2 00:00:02:00 00:00:03:00 Two\nlines\n");
+        assert!(to_string(&subs, FrameRate::new(30000, 1001)).starts_with("1 00:00:00;00 00:00:00;29 One\n"));
+    }
+}
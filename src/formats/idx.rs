@@ -0,0 +1,192 @@
+//! DVD VobSub index (```.idx```) files, which keep timing of the bitmap
+//! subtitles stored in the accompanying ```.sub``` file.
+
+use std::io::{Error, ErrorKind};
+use std::sync::LazyLock;
+use std::str::FromStr;
+
+use regex::Regex;
+
+use crate::timestamp::Timestamp;
+use crate::subline::SubLine;
+use crate::subtitles::Subtitles;
+use crate::language::LanguageTag;
+
+/// Longest placeholder duration of a line, ends of the bitmaps are stored only in ```.sub```.
+const MAX_DURATION: u64 = 5000;
+
+/// Language track of the ```.idx``` file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VobSubTrack {
+    /// Stream index, ```index``` of the ```id:``` entry.
+    pub index: u32,
+    /// Lines with empty text, numbered consecutively. Ends are placeholders:
+    /// start of the next line, but no more than 5 seconds after the start.
+    pub subtitles: Subtitles,
+    /// Position of every line's bitmap in the ```.sub``` file.
+    pub file_positions: Vec<u64>,
+}
+
+/// Parsed ```.idx``` file.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct VobSubIndex {
+    /// Frame size, ```size:``` entry.
+    pub size: Option<(u32, u32)>,
+    /// Index of the default track, ```langidx:``` entry.
+    pub default_track: Option<u32>,
+    pub tracks: Vec<VobSubTrack>,
+}
+
+/// Parses ```.idx``` content. ```delay:``` entries shift the following timestamps
+/// of the track.
+pub fn from_str(content: &str) -> Result<VobSubIndex, Error> {
+    let invalid = |message: String| Err(Error::new(ErrorKind::InvalidData, message));
+
+    let mut index = VobSubIndex::default();
+    let mut starts: Vec<Vec<u64>> = Vec::new();
+    let mut delay = 0i64;
+
+    for (number, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (key, value) = match line.find(':') {
+            Some(colon) => (line[..colon].trim(), line[colon + 1..].trim()),
+            None => continue,
+        };
+
+        match key {
+            "size" => {
+                let mut parts = value.splitn(2, 'x').map(|part| part.trim().parse().ok());
+                if let (Some(Some(width)), Some(Some(height))) = (parts.next(), parts.next()) {
+                    index.size = Some((width, height));
+                }
+            }
+            "langidx" => index.default_track = value.parse().ok(),
+            "id" => {
+                // id: en, index: 0
+                let mut parts = value.splitn(2, ',');
+                let language = parts.next().and_then(|language| LanguageTag::from_str(language).ok());
+                let stream = parts.next()
+                    .and_then(|rest| rest.trim().strip_prefix("index:"))
+                    .and_then(|stream| stream.trim().parse().ok())
+                    .unwrap_or(index.tracks.len() as u32);
+
+                let mut subtitles = Subtitles::default();
+                subtitles.metadata.language = language;
+                subtitles.metadata.source_format = Some("idx".to_owned());
+                index.tracks.push(VobSubTrack {
+                    index: stream,
+                    subtitles,
+                    file_positions: Vec::new(),
+                });
+                starts.push(Vec::new());
+                delay = 0;
+            }
+            "delay" => {
+                let (negative, time) = match value.strip_prefix('-') {
+                    Some(time) => (true, time),
+                    None => (false, value),
+                };
+                match parse_timestamp(time.trim()) {
+                    Some(time) => {
+                        let time = time as i64;
+                        delay += if negative { -time } else { time };
+                    }
+                    None => return invalid(format!("Invalid delay at line {}", number + 1)),
+                }
+            }
+            "timestamp" => {
+                // timestamp: 00:00:01:101, filepos: 000000000
+                let cap = match TIMESTAMP.captures(line) {
+                    Some(cap) => cap,
+                    None => return invalid(format!("Invalid timestamp at line {}", number + 1)),
+                };
+                let track = match index.tracks.last_mut() {
+                    Some(track) => track,
+                    None => return invalid(format!("Timestamp before track id at line {}", number + 1)),
+                };
+                let time = parse_timestamp(cap.get(1).map_or("", |m| m.as_str())).unwrap_or(0) as i64 + delay;
+                let position = u64::from_str_radix(cap.get(2).map_or("", |m| m.as_str()), 16).unwrap_or(0);
+                starts.last_mut().unwrap().push(time.max(0) as u64);
+                track.file_positions.push(position);
+            }
+            _ => {}
+        }
+    }
+
+    for (track, starts) in index.tracks.iter_mut().zip(starts) {
+        for (i, &start) in starts.iter().enumerate() {
+            let next = starts.get(i + 1).cloned().unwrap_or(u64::MAX).max(start);
+            let end = next.min(start + MAX_DURATION);
+            track.subtitles.inner.push(SubLine::new(i as u32 + 1,
+                                                    String::new(),
+                                                    Timestamp::from_miliseconds(start),
+                                                    Timestamp::from_miliseconds(end)));
+        }
+    }
+    Ok(index)
+}
+
+/// Parses ```HH:MM:SS:mmm``` into miliseconds.
+fn parse_timestamp(s: &str) -> Option<u64> {
+    let parts: Vec<u64> = s.split(':').map(|part| part.parse().ok()).collect::<Option<_>>()?;
+    match parts[..] {
+        [hours, minutes, seconds, miliseconds] if minutes < 60 && seconds < 60 && miliseconds < 1000 => {
+            Some(((hours * 60 + minutes) * 60 + seconds) * 1000 + miliseconds)
+        }
+        _ => None,
+    }
+}
+
+static TIMESTAMP: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^timestamp:\s*(\d+:\d{2}:\d{2}:\d{3}),\s*filepos:\s*([0-9a-fA-F]+)$").unwrap());
+
+#[cfg(test)]
+mod idx_tests {
+    use super::*;
+
+    static CONTENT: &str = "# VobSub index file, v7 (do not modify this line!)\n\
+                            size: 720x480\n\
+                            palette: 000000, 828282, 828282, 828282\n\
+                            langidx: 1\n\
+                            \n\
+                            # English\n\
+                            id: en, index: 0\n\
+                            timestamp: 00:00:01:101, filepos: 000000000\n\
+                            timestamp: 00:00:02:500, filepos: 000000800\n\
+                            timestamp: 00:01:00:000, filepos: 00000a000\n\
+                            \n\
+                            id: fr, index: 1\n\
+                            delay: -00:00:00:500\n\
+                            timestamp: 00:00:00:200, filepos: 00000b000\n\
+                            timestamp: 00:00:03:000, filepos: 00000c000\n";
+
+    #[test]
+    fn _from_str() {
+        let index = from_str(CONTENT).unwrap();
+        assert_eq!(index.size, Some((720, 480)));
+        assert_eq!(index.default_track, Some(1));
+        assert_eq!(index.tracks.len(), 2);
+
+        let english = &index.tracks[0];
+        assert_eq!(english.index, 0);
+        assert_eq!(english.subtitles.metadata.language.as_ref().unwrap().as_str(), "en");
+        assert_eq!(english.file_positions, vec![0, 0x800, 0xa000]);
+        let timing: Vec<_> = english.subtitles
+            .inner
+            .iter()
+            .map(|line| (line.index, line.start.total_miliseconds(), line.end.total_miliseconds()))
+            .collect();
+        assert_eq!(timing, vec![(1, 1101, 2500), (2, 2500, 7500), (3, 60000, 65000)]);
+        assert!(english.subtitles.inner.iter().all(|line| line.text.is_empty()));
+
+        let french = &index.tracks[1];
+        assert_eq!(french.subtitles.inner[0].start, Timestamp::new(0, 0, 0, 0));
+        assert_eq!(french.subtitles.inner[1].start, Timestamp::new(0, 0, 2, 500));
+
+        assert!(from_str("timestamp: 00:00:01:101, filepos: 000000000").is_err());
+        assert!(from_str("id: en, index: 0\ntimestamp: 00:00:01, filepos: 0").is_err());
+    }
+}
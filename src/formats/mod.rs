@@ -0,0 +1,33 @@
+//! Subtitle formats other than SubRip.
+
+#[cfg(feature = "formats-webvtt")]
+pub mod webvtt;
+#[cfg(feature = "formats-ass")]
+pub mod ass;
+#[cfg(feature = "formats-srv3")]
+pub mod srv3;
+#[cfg(feature = "formats-idx")]
+pub mod idx;
+#[cfg(feature = "formats-dcp")]
+pub mod dcp;
+#[cfg(feature = "formats-spruce")]
+pub mod spruce;
+#[cfg(feature = "formats-encore")]
+pub mod encore;
+#[cfg(feature = "formats-realtext")]
+pub mod realtext;
+#[cfg(feature = "formats-qttext")]
+pub mod qttext;
+#[cfg(feature = "formats-xliff")]
+pub mod xliff;
+#[cfg(feature = "formats-po")]
+pub mod po;
+#[cfg(feature = "formats-stl")]
+pub mod stl;
+#[cfg(feature = "pgs")]
+pub mod pgs;
+#[cfg(feature = "formats-convert")]
+pub mod convert;
+
+#[cfg(feature = "formats-convert")]
+pub use self::convert::{SubtitleFormat, Feature, ConversionReport};
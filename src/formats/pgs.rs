@@ -0,0 +1,153 @@
+//! Blu-ray Presentation Graphic Stream (```.sup```) timing.
+
+use std::io::{Error, ErrorKind};
+
+use crate::timestamp::Timestamp;
+use crate::subline::SubLine;
+use crate::subtitles::Subtitles;
+
+/// Presentation Composition Segment, the first segment of every display set.
+const PRESENTATION_COMPOSITION: u8 = 0x16;
+/// Size of the segment header: magic, PTS, DTS, type and size.
+const HEADER_SIZE: usize = 13;
+
+/// Display timing of the bitmaps of a PGS stream.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PgsTrack {
+    /// Lines with empty text, numbered consecutively. A line lasts until the next
+    /// display set, the last line without following one has zero duration.
+    pub subtitles: Subtitles,
+    /// Offset of every line's display set in the stream.
+    pub segment_offsets: Vec<u64>,
+    /// Video size of the first display set.
+    pub size: Option<(u16, u16)>,
+}
+
+/// Walks PGS segments and turns display sets showing objects into lines,
+/// display sets with no objects end the shown ones. Bitmaps aren't decoded.
+pub fn from_bytes(data: &[u8]) -> Result<PgsTrack, Error> {
+    let mut track = PgsTrack::default();
+    let mut shown: Option<u64> = None;
+    let mut offset = 0;
+
+    while offset < data.len() {
+        let invalid = |message: &str| Err(Error::new(ErrorKind::InvalidData, format!("{} at offset {}", message, offset)));
+        if data.len() - offset < HEADER_SIZE {
+            return invalid("Truncated PGS segment header");
+        }
+        let header = &data[offset..offset + HEADER_SIZE];
+        if &header[..2] != b"PG" {
+            return invalid("Invalid PGS segment magic");
+        }
+        let presentation_time = u32::from(header[2]) << 24 | u32::from(header[3]) << 16 | u32::from(header[4]) << 8 |
+                                u32::from(header[5]);
+        let kind = header[10];
+        let size = (usize::from(header[11]) << 8) | usize::from(header[12]);
+        let body = offset + HEADER_SIZE;
+        if data.len() - body < size {
+            return invalid("Truncated PGS segment");
+        }
+
+        if kind == PRESENTATION_COMPOSITION {
+            // width, height, frame rate, composition number, state, palette update, palette id, objects
+            if size < 11 {
+                return invalid("Invalid PGS presentation composition");
+            }
+            let segment = &data[body..body + size];
+            let palette_update = segment[8] & 0x80 != 0;
+            let objects = segment[10];
+            // 90 kHz clock
+            let time = u64::from(presentation_time) / 90;
+
+            if track.size.is_none() {
+                let width = (u16::from(segment[0]) << 8) | u16::from(segment[1]);
+                let height = (u16::from(segment[2]) << 8) | u16::from(segment[3]);
+                track.size = Some((width, height));
+            }
+
+            if !(palette_update && objects > 0) {
+                if let Some(start) = shown.take() {
+                    push(&mut track.subtitles, start, time.max(start));
+                }
+                if objects > 0 {
+                    shown = Some(time);
+                    track.segment_offsets.push(offset as u64);
+                }
+            }
+        }
+        offset = body + size;
+    }
+
+    if let Some(start) = shown {
+        push(&mut track.subtitles, start, start);
+    }
+    track.subtitles.metadata.source_format = Some("pgs".to_owned());
+    Ok(track)
+}
+
+fn push(subs: &mut Subtitles, start: u64, end: u64) {
+    let index = subs.len() as u32 + 1;
+    subs.inner.push(SubLine::new(index,
+                                 String::new(),
+                                 Timestamp::from_miliseconds(start),
+                                 Timestamp::from_miliseconds(end)));
+}
+
+#[cfg(test)]
+mod pgs_tests {
+    use super::*;
+
+    fn segment(time: u32, kind: u8, body: &[u8]) -> Vec<u8> {
+        let time = time * 90;
+        let mut result = vec![b'P',
+                              b'G',
+                              (time >> 24) as u8,
+                              (time >> 16) as u8,
+                              (time >> 8) as u8,
+                              time as u8,
+                              0,
+                              0,
+                              0,
+                              0,
+                              kind,
+                              (body.len() >> 8) as u8,
+                              body.len() as u8];
+        result.extend_from_slice(body);
+        result
+    }
+
+    fn display_set(time: u32, objects: u8, palette_update: bool) -> Vec<u8> {
+        let mut composition = vec![0x07, 0x80, 0x04, 0x38, 0x10, 0, 1, 0x80, 0, 0, objects];
+        if palette_update {
+            composition[8] = 0x80;
+        }
+        composition.extend(vec![0; 8 * objects as usize]);
+        let mut result = segment(time, PRESENTATION_COMPOSITION, &composition);
+        result.extend(segment(time, 0x80, &[]));
+        result
+    }
+
+    #[test]
+    fn _from_bytes() {
+        let mut data = display_set(1000, 1, false);
+        let second = data.len() as u64;
+        data.extend(display_set(2000, 1, false));
+        data.extend(display_set(2500, 1, true));
+        data.extend(display_set(3000, 0, false));
+        let last = data.len() as u64;
+        data.extend(display_set(10000, 2, false));
+
+        let track = from_bytes(&data).unwrap();
+        assert_eq!(track.size, Some((1920, 1080)));
+        assert_eq!(track.segment_offsets, vec![0, second, last]);
+        let timing: Vec<_> = track.subtitles
+            .inner
+            .iter()
+            .map(|line| (line.index, line.start.total_miliseconds(), line.end.total_miliseconds()))
+            .collect();
+        assert_eq!(timing, vec![(1, 1000, 2000), (2, 2000, 3000), (3, 10000, 10000)]);
+
+        assert!(from_bytes(&data[..data.len() - 1]).is_err());
+        assert!(from_bytes(b"PX\0\0\0\0\0\0\0\0\x80\0\0").is_err());
+    }
+}
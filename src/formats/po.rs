@@ -0,0 +1,219 @@
+//! Gettext PO (```.po```) export of lines' texts for translation platforms.
+
+use std::fmt::Write;
+use std::io::{Error, ErrorKind};
+
+use crate::timestamp::Timestamp;
+use crate::subtitles::Subtitles;
+
+/// Writes every line as an entry with the text as ```msgid``` and empty ```msgstr```.
+/// ```msgctxt``` is the line's id or, if not set, index, so equal texts stay separate entries.
+/// Timing is written as an extracted comment (```#.```), the index as a reference (```#:```).
+pub fn to_string(subs: &Subtitles) -> String {
+    let mut result = String::from("msgid \"\"\nmsgstr \"\"\n\"Content-Type: text/plain; charset=UTF-8\\n\"\n");
+    if let Some(ref language) = subs.metadata.language {
+        writeln!(result, "\"X-Source-Language: {}\\n\"", language).unwrap();
+    }
+    for line in subs {
+        let context = match line.id {
+            Some(ref id) => id.clone(),
+            None => line.index.to_string(),
+        };
+        write!(result,
+               "\n#. {} --> {}\n#: {}\nmsgctxt {}\nmsgid {}\nmsgstr \"\"\n",
+               timestamp(line.start),
+               timestamp(line.end),
+               line.index,
+               quote(&context),
+               quote(&line.text.replace("\r\n", "\n")))
+            .unwrap();
+    }
+    result
+}
+
+/// Puts translations from PO ```content``` into lines' texts. Entries are matched to lines
+/// by ```msgctxt``` equal to the line's id, otherwise by the index in ```msgctxt``` or ```#:```.
+/// Untranslated and fuzzy entries are skipped. Returns the number of translated lines.
+pub fn merge(subs: &mut Subtitles, content: &str) -> Result<usize, Error> {
+    let mut translated = 0;
+    for entry in entries(content)? {
+        if entry.fuzzy || entry.id.is_empty() || entry.translation.is_empty() {
+            continue;
+        }
+        let position = entry.context
+            .as_ref()
+            .and_then(|context| subs.inner.iter().position(|line| line.id.as_ref() == Some(context)))
+            .or_else(|| {
+                let index = entry.context.as_ref().and_then(|context| context.parse().ok()).or(entry.reference);
+                index.and_then(|index: u32| subs.inner.iter().position(|line| line.index == index))
+            });
+        if let Some(position) = position {
+            subs.inner[position].text = entry.translation.replace("\r\n", "\n").replace('\n', "\r\n");
+            translated += 1;
+        }
+    }
+    Ok(translated)
+}
+
+#[derive(Debug, Default)]
+struct Entry {
+    context: Option<String>,
+    id: String,
+    translation: String,
+    reference: Option<u32>,
+    fuzzy: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Context,
+    Id,
+    Translation,
+    Other,
+}
+
+fn entries(content: &str) -> Result<Vec<Entry>, Error> {
+    let mut entries = Vec::new();
+    let mut entry = Entry::default();
+    let mut field = Field::Other;
+    let mut started = false;
+
+    for (number, line) in content.lines().enumerate() {
+        let invalid = || Err(Error::new(ErrorKind::InvalidData, format!("Invalid PO string at line {}", number + 1)));
+        let line = line.trim();
+        let (keyword, rest) = match line.find(|c: char| c.is_whitespace()) {
+            Some(space) if !line.starts_with('"') => (&line[..space], line[space..].trim_start()),
+            _ => (line, ""),
+        };
+
+        // A comment or keyword after a translation starts the next entry
+        if field == Field::Translation && (line.starts_with('#') || keyword == "msgctxt" || keyword == "msgid") {
+            entries.push(::std::mem::take(&mut entry));
+            field = Field::Other;
+            started = false;
+        }
+
+        if line.is_empty() {
+            if started {
+                entries.push(::std::mem::take(&mut entry));
+                field = Field::Other;
+                started = false;
+            }
+        } else if let Some(flags) = line.strip_prefix("#,") {
+            entry.fuzzy = flags.split(',').any(|flag| flag.trim() == "fuzzy");
+        } else if let Some(references) = line.strip_prefix("#:") {
+            entry.reference = references.split_whitespace().next().and_then(|reference| reference.parse().ok());
+        } else if line.starts_with('#') {
+            // Other comments aren't needed for merging
+        } else if line.starts_with('"') {
+            let value = match unquote(line) {
+                Some(value) => value,
+                None => return invalid(),
+            };
+            match field {
+                Field::Context => entry.context.get_or_insert_with(String::new).push_str(&value),
+                Field::Id => entry.id.push_str(&value),
+                Field::Translation => entry.translation.push_str(&value),
+                Field::Other => {}
+            }
+        } else {
+            field = match keyword {
+                "msgctxt" => Field::Context,
+                "msgid" => Field::Id,
+                "msgstr" | "msgstr[0]" => Field::Translation,
+                _ => Field::Other,
+            };
+            started = true;
+            let value = match unquote(rest) {
+                Some(value) => value,
+                None => return invalid(),
+            };
+            match field {
+                Field::Context => entry.context = Some(value),
+                Field::Id => entry.id = value,
+                Field::Translation => entry.translation = value,
+                Field::Other => {}
+            }
+        }
+    }
+    if started {
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+fn quote(text: &str) -> String {
+    let escaped = text.replace('\\', "\\\\").replace('"', "\\\"").replace('\t', "\\t");
+    if !escaped.contains('\n') {
+        return format!("\"{}\"", escaped);
+    }
+    // Multiline strings start with an empty one, every row on its own line
+    let mut result = String::from("\"\"");
+    for row in escaped.split_inclusive('\n') {
+        write!(result, "\n\"{}\"", row.replace('\n', "\\n")).unwrap();
+    }
+    result
+}
+
+fn unquote(s: &str) -> Option<String> {
+    let inner = s.strip_prefix('"')?.strip_suffix('"')?;
+    let mut result = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next()? {
+            'n' => result.push('\n'),
+            't' => result.push('\t'),
+            'r' => result.push('\r'),
+            c => result.push(c),
+        }
+    }
+    Some(result)
+}
+
+fn timestamp(time: Timestamp) -> String {
+    format!("{:02}:{:02}:{:02},{:03}",
+            time.hours,
+            time.minutes,
+            time.seconds,
+            time.miliseconds)
+}
+
+#[cfg(test)]
+mod po_tests {
+    use super::*;
+    use crate::utils;
+
+    #[test]
+    fn round_trip() {
+        let mut subs = utils::subs_from_texts(&["Hello", "Say \"hi\"\r\nnow", "Hello", "Bye"]);
+        subs.inner[3].id = Some("outro".to_owned());
+
+        let po = to_string(&subs);
+        assert!(po.contains("\n#. 00:00:02,000 --> 00:00:03,000\n#: 2\nmsgctxt \"2\"\nmsgid \"\"\n\
+                             \"Say \\\"hi\\\"\\n\"\n\"now\"\nmsgstr \"\"\n"));
+        assert!(po.contains("msgctxt \"outro\"\nmsgid \"Bye\"\n"));
+        assert_eq!(merge(&mut subs.clone(), &po).unwrap(), 0);
+
+        let translate = |po: String, entry: &str, translation: &str| {
+            po.replace(&format!("{}\nmsgstr \"\"", entry), &format!("{}\nmsgstr {}", entry, translation))
+        };
+        let translated = translate(po.replace("#: 1\n", "#, fuzzy\n#: 1\n"),
+                                   "msgctxt \"1\"\nmsgid \"Hello\"",
+                                   "\"Salut\"");
+        let translated = translate(translated, "\"now\"", "\"\"\n\"Dis \\\"salut\\\"\\n\"\n\"maintenant\"");
+        let translated = translate(translated, "msgctxt \"3\"\nmsgid \"Hello\"", "\"Bonjour\"");
+        let translated = translate(translated, "msgid \"Bye\"", "\"Au revoir\"");
+
+        let mut french = subs.clone();
+        french.inner[3].index = 10;
+        assert_eq!(merge(&mut french, &translated).unwrap(), 3);
+        let texts: Vec<&str> = french.inner.iter().map(|line| &*line.text).collect();
+        assert_eq!(texts, vec!["Hello", "Dis \"salut\"\r\nmaintenant", "Bonjour", "Au revoir"]);
+
+        assert!(merge(&mut subs, "msgid \"Unterminated\nmsgstr \"\"").is_err());
+    }
+}
@@ -0,0 +1,87 @@
+//! QuickTime text (```.qt.txt```) format.
+
+use std::io::{Error, ErrorKind};
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::timestamp::Timestamp;
+use crate::subline::SubLine;
+use crate::subtitles::Subtitles;
+
+/// Parses QuickTime text content. Every ```[HH:MM:SS.ff]``` timestamp ends
+/// the text before it and starts the text after it, empty texts are gaps.
+/// Frames are counted at the ```{timeScale:...}``` of the header, 30 by default,
+/// and ```{...}``` descriptors inside the text are removed.
+pub fn from_str(content: &str) -> Result<Subtitles, Error> {
+    let invalid = |message: &str| Err(Error::new(ErrorKind::InvalidData, message.to_owned()));
+    let content = content.trim_start_matches('\u{feff}');
+    if !content.trim_start().starts_with("{QTtext}") {
+        return invalid("QuickTime text content must start with {QTtext}");
+    }
+    let time_scale = TIME_SCALE.captures(content)
+        .and_then(|cap| cap.get(1).and_then(|scale| scale.as_str().parse::<u64>().ok()))
+        .filter(|&scale| scale > 0)
+        .unwrap_or(30);
+
+    let mut subs = Subtitles::default();
+    let mut current: Option<(u64, Vec<&str>)> = None;
+    for line in content.lines() {
+        let line = line.trim();
+        if let Some(cap) = TIMESTAMP.captures(line) {
+            let number = |i: usize| cap.get(i).map_or("0", |m| m.as_str()).parse::<u64>().unwrap_or(0);
+            let time = ((number(1) * 60 + number(2)) * 60 + number(3)) * 1000 + number(4) * 1000 / time_scale;
+
+            if let Some((start, rows)) = current.take() {
+                let text = rows.join("\r\n");
+                if !text.trim().is_empty() {
+                    subs.inner.push(SubLine::new(subs.len() as u32 + 1,
+                                                 text,
+                                                 Timestamp::from_miliseconds(start),
+                                                 Timestamp::from_miliseconds(time.max(start))));
+                }
+            }
+            current = Some((time, Vec::new()));
+        } else if let Some((_, ref mut rows)) = current {
+            let row = DESCRIPTOR.replace_all(line, "");
+            if !row.trim().is_empty() {
+                rows.push(line);
+            }
+        }
+    }
+    if current.map(|(_, rows)| !rows.is_empty()).unwrap_or(false) {
+        return invalid("QuickTime text must end with a timestamp");
+    }
+
+    for line in &mut subs.inner {
+        line.text = DESCRIPTOR.replace_all(&line.text, "").trim().to_owned();
+    }
+    subs.metadata.source_format = Some("qttext".to_owned());
+    Ok(subs)
+}
+
+static TIMESTAMP: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\[(\d{1,2}):([0-5]\d):([0-5]\d)(?:\.(\d{1,3}))?\]$").unwrap());
+static TIME_SCALE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i)\{timeScale:\s*(\d+)\}").unwrap());
+static DESCRIPTOR: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\{[^}]*\}").unwrap());
+
+#[cfg(test)]
+mod qttext_tests {
+    use super::*;
+
+    #[test]
+    fn _from_str() {
+        let content = "{QTtext}{font:Tahoma}{plain}{size:20}{timeScale:25}{width:160}{timestamps:absolute}\n\
+                       [00:00:01.05]\nHello\n{italic}world\n[00:00:03.00]\n\n[00:00:05.00]\nNext\n[00:00:07.00]\n";
+        let subs = from_str(content).unwrap();
+        let lines: Vec<_> = subs.inner
+            .iter()
+            .map(|line| (line.index, line.start.total_miliseconds(), line.end.total_miliseconds(), &*line.text))
+            .collect();
+        assert_eq!(lines, vec![(1, 1200, 3000, "Hello\r\nworld"), (2, 5000, 7000, "Next")]);
+        assert_eq!(subs.metadata.source_format.as_ref().unwrap(), "qttext");
+
+        assert!(from_str("[00:00:01.05]\nHello\n").is_err());
+        assert!(from_str("{QTtext}\n[00:00:01.05]\nHello\n").is_err());
+    }
+}
@@ -0,0 +1,161 @@
+//! RealText (```.rt```) format of RealPlayer.
+
+use std::io::{Error, ErrorKind};
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::timestamp::Timestamp;
+use crate::subline::SubLine;
+use crate::subtitles::Subtitles;
+use crate::tags::{self, Tag, Token};
+use crate::entities;
+
+/// Parses RealText content. Every ```<time begin="...">``` starts a line which lasts
+/// until its ```end```, the next ```<time>``` or the ```duration``` of the window.
+/// ```<br/>``` becomes a new row, SubRip tags are kept and other tags are removed.
+pub fn from_str(content: &str) -> Result<Subtitles, Error> {
+    let invalid = |message: String| Err(Error::new(ErrorKind::InvalidData, message));
+    let window = match WINDOW.captures(content) {
+        Some(window) => window,
+        None => return invalid("RealText content must have <window> element".to_owned()),
+    };
+    let duration = attribute(window.get(1).map_or("", |m| m.as_str()), "duration").and_then(|duration| parse_time(&duration));
+
+    // Start, end and text of every <time>
+    let mut cues: Vec<(u64, Option<u64>, String)> = Vec::new();
+    let mut last = 0;
+    for cap in TIME.captures_iter(content) {
+        let whole = cap.get(0).unwrap();
+        let (begin, end) = (whole.start(), whole.end());
+        if let Some(cue) = cues.last_mut() {
+            cue.2.push_str(&content[last..begin]);
+        }
+        last = end;
+
+        let attributes = cap.get(1).map_or("", |m| m.as_str());
+        let start = match attribute(attributes, "begin").map(|begin| parse_time(&begin)) {
+            Some(Some(start)) => start,
+            Some(None) => return invalid(format!("Invalid RealText time at {}", begin)),
+            None => continue,
+        };
+        let end = attribute(attributes, "end").and_then(|end| parse_time(&end));
+        cues.push((start, end, String::new()));
+    }
+    if let Some(cue) = cues.last_mut() {
+        let window_end = content.rfind("</window>").unwrap_or(content.len()).max(last);
+        cue.2.push_str(&content[last..window_end]);
+    }
+
+    let mut subs = Subtitles::default();
+    for i in 0..cues.len() {
+        let (start, end, ref raw) = cues[i];
+        let text = text(raw);
+        if text.is_empty() {
+            continue;
+        }
+        let next = cues.get(i + 1).map(|cue| cue.0);
+        let end = end.or(next).or(duration).unwrap_or(start).max(start);
+        subs.inner.push(SubLine::new(subs.len() as u32 + 1,
+                                     text,
+                                     Timestamp::from_miliseconds(start),
+                                     Timestamp::from_miliseconds(end)));
+    }
+    subs.metadata.source_format = Some("realtext".to_owned());
+    Ok(subs)
+}
+
+/// Text after the last ```<clear/>```, with rows of ```<br/>``` and known tags only.
+fn text(raw: &str) -> String {
+    let raw = match raw.rfind("<clear") {
+        Some(clear) => &raw[clear..],
+        None => raw,
+    };
+    // Only <br/> breaks rows, newlines of the source are spaces
+    let raw = BREAK.replace_all(&raw.replace(['\r', '\n'], " "), "\n").into_owned();
+    let mut text = String::with_capacity(raw.len());
+    for token in tags::tokenize_markup(&raw) {
+        match token {
+            Token::Text(part) => text.push_str(part),
+            token => {
+                let known = match token {
+                    Token::Open { ref name, .. } |
+                    Token::Close { ref name, .. } => Tag::from_name(name).is_some(),
+                    Token::Text(_) => false,
+                };
+                if known {
+                    text.push_str(token.raw());
+                }
+            }
+        }
+    }
+    entities::decode(&text)
+        .lines()
+        .map(|row| row.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|row| !row.is_empty())
+        .collect::<Vec<_>>()
+        .join("\r\n")
+}
+
+fn attribute(attributes: &str, name: &str) -> Option<String> {
+    ATTRIBUTE.captures_iter(attributes)
+        .find(|cap| cap.get(1).is_some_and(|attribute| attribute.as_str().eq_ignore_ascii_case(name)))
+        .map(|cap| cap.get(2).or(cap.get(3)).map_or("", |m| m.as_str()).to_owned())
+}
+
+/// Parses ```[[[days:]hours:]minutes:]seconds[.fraction]``` into miliseconds.
+fn parse_time(s: &str) -> Option<u64> {
+    let mut parts: Vec<&str> = s.trim().split(':').collect();
+    if parts.len() > 4 {
+        return None;
+    }
+    let seconds = parts.pop()?;
+    let (seconds, fraction) = match seconds.find('.') {
+        Some(dot) => (&seconds[..dot], &seconds[dot + 1..]),
+        None => (seconds, ""),
+    };
+    if fraction.len() > 3 || !fraction.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let miliseconds = format!("{:0<3}", fraction).parse::<u64>().ok()?;
+
+    let mut total = 0u64;
+    for (part, multiplier) in parts.iter().rev().zip(&[60u64, 3600, 86400]) {
+        total = total.checked_add(part.parse::<u64>().ok()?.checked_mul(*multiplier)?)?;
+    }
+    total = total.checked_add(seconds.parse().ok()?)?;
+    total.checked_mul(1000)?.checked_add(miliseconds)
+}
+
+static WINDOW: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i)<window\b([^>]*)>").unwrap());
+static TIME: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i)<time\b([^>]*)/?>").unwrap());
+static BREAK: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i)<br\s*/?>").unwrap());
+static ATTRIBUTE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"([\w:-]+)\s*=\s*(?:"([^"]*)"|'([^']*)')"#).unwrap());
+
+#[cfg(test)]
+mod realtext_tests {
+    use super::*;
+
+    #[test]
+    fn _from_str() {
+        let content = "<window type=\"generic\" duration=\"00:00:10.0\" wordwrap=\"true\">\n\
+                       <time begin=\"1.5\"/><clear/>Hello<br/>  <i>world</i> &amp; <font face=\"Arial\">all</font> <ruby>x</ruby>\n\
+                       <time begin=\"00:03.25\" end='4'/><clear/>Next\n\
+                       <time begin=\"5\"/><clear/>\n\
+                       <time begin=\"0:00:00:07.123\"/><clear/>Last\n\
+                       </window>";
+        let subs = from_str(content).unwrap();
+        let lines: Vec<_> = subs.inner
+            .iter()
+            .map(|line| (line.index, line.start.total_miliseconds(), line.end.total_miliseconds(), &*line.text))
+            .collect();
+        assert_eq!(lines,
+                   vec![(1, 1500, 3250, "Hello\r\n<i>world</i> & <font face=\"Arial\">all</font> x"),
+                        (2, 3250, 4000, "Next"),
+                        (3, 7123, 10000, "Last")]);
+        assert_eq!(subs.metadata.source_format.as_ref().unwrap(), "realtext");
+
+        assert!(from_str("<time begin=\"1\"/>Text").is_err());
+        assert!(from_str("<window><time begin=\"1.2345\"/>Text</window>").is_err());
+    }
+}
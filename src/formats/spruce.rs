@@ -0,0 +1,60 @@
+//! Spruce STL (```.stl```) text subtitles of DVD Studio Pro.
+
+use std::fmt::Write;
+
+use crate::subtitles::Subtitles;
+use crate::metadata::FrameRate;
+use crate::tags::{self, Tag, Token};
+
+/// Formats ```Subtitles``` as Spruce STL with non-drop-frame timecodes at given frame rate.
+/// Rows are separated by ```|```, italic, bold and underline tags become
+/// ```^I```, ```^B``` and ```^U``` toggles, other tags are dropped.
+pub fn to_string(subs: &Subtitles, fps: FrameRate) -> String {
+    let mut result = String::from("$FontName = Arial\n$FontSize = 42\n$HorzAlign = Center\n$VertAlign = Bottom\n\
+                                   $Bold = False\n$Italic = False\n$Underlined = False\n\n");
+    for line in subs {
+        writeln!(result,
+                 "{} , {} , {}",
+                 fps.timecode(line.start, false),
+                 fps.timecode(line.end, false),
+                 text(&line.text))
+            .unwrap();
+    }
+    result
+}
+
+fn text(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for token in tags::tokenize(text) {
+        match token {
+            Token::Text(text) => {
+                let rows: Vec<&str> = text.split('\n').map(|row| row.trim_end_matches('\r')).collect();
+                result.push_str(&rows.join("|"));
+            }
+            Token::Open { ref name, .. } |
+            Token::Close { ref name, .. } => {
+                match Tag::from_name(name) {
+                    Some(Tag::Italic) => result.push_str("^I"),
+                    Some(Tag::Bold) => result.push_str("^B"),
+                    Some(Tag::Underline) => result.push_str("^U"),
+                    _ => {}
+                }
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod spruce_tests {
+    use super::*;
+    use crate::utils;
+
+    #[test]
+    fn _to_string() {
+        let subs = utils::subs_from_texts(&["<i>One</i>", "Two\r\n<font color=\"red\">lines</font>"]);
+        let stl = to_string(&subs, FrameRate::new(25, 1));
+        assert!(stl.starts_with("$FontName = Arial\n"));
+        assert!(stl.ends_with("\n\n00:00:00:00 , 00:00:01:00 , ^IOne^I\n00:00:02:00 , 00:00:03:00 , Two|lines\n"));
+    }
+}
@@ -0,0 +1,221 @@
+//! YouTube timedtext SRV3 (```.srv3```, ```.ytt```) format, as downloaded by yt-dlp.
+
+use std::collections::HashMap;
+use std::sync::LazyLock;
+use std::io::{Error, ErrorKind};
+
+use regex::Regex;
+
+use crate::timestamp::Timestamp;
+use crate::subline::{SubLine, Word};
+use crate::subtitles::Subtitles;
+use crate::entities;
+
+/// Text style of ```<pen>```.
+#[derive(Debug, Clone, Default)]
+struct Pen {
+    bold: bool,
+    italic: bool,
+    underline: bool,
+    color: Option<String>,
+}
+
+impl Pen {
+    /// Wraps every row of the text into tags, keeping surrounding spaces outside of them.
+    fn apply(&self, text: &str) -> String {
+        let rows: Vec<String> = text.split('\n')
+            .map(|row| {
+                let trimmed = row.trim();
+                if trimmed.is_empty() {
+                    return row.to_owned();
+                }
+                let leading = &row[..row.find(trimmed).unwrap_or(0)];
+                let trailing = &row[leading.len() + trimmed.len()..];
+
+                let mut styled = trimmed.to_owned();
+                if let Some(ref color) = self.color {
+                    styled = format!("<font color=\"{}\">{}</font>", color, styled);
+                }
+                for &(enabled, tag) in &[(self.underline, "u"), (self.italic, "i"), (self.bold, "b")] {
+                    if enabled {
+                        styled = format!("<{}>{}</{}>", tag, styled, tag);
+                    }
+                }
+                format!("{}{}{}", leading, styled, trailing)
+            })
+            .collect();
+        rows.join("\n")
+    }
+}
+
+/// Parses SRV3 content. Every ```<p>``` with text becomes a line,
+/// its ```<s>``` segments are joined and styled by their pens.
+/// Lines are numbered consecutively, and lines overlapping the next one
+/// (as in automatic captions, where rows roll up) are cut at its start.
+///
+/// If ```word_timing``` is ```true```, segments are kept as ```SubLine::words```.
+pub fn from_str(content: &str, word_timing: bool) -> Result<Subtitles, Error> {
+    if !content.contains("<timedtext") {
+        return Err(Error::new(ErrorKind::InvalidData, "SRV3 content must have <timedtext> element"));
+    }
+
+    let mut pens = HashMap::new();
+    for cap in PEN.captures_iter(content) {
+        let attrs = attributes(cap.get(1).map_or("", |m| m.as_str()));
+        let flag = |name: &str| attrs.get(name).map(|value| value == "1").unwrap_or(false);
+        let pen = Pen {
+            bold: flag("b"),
+            italic: flag("i"),
+            underline: flag("u"),
+            color: attrs.get("fc").cloned(),
+        };
+        if let Some(id) = attrs.get("id") {
+            pens.insert(id.clone(), pen);
+        }
+    }
+    let default_pen = Pen::default();
+    let pen = |attrs: &HashMap<String, String>| attrs.get("p").and_then(|id| pens.get(id)).unwrap_or(&default_pen);
+
+    let mut lines: Vec<SubLine> = Vec::new();
+    for cap in PARAGRAPH.captures_iter(content) {
+        let attrs = attributes(cap.get(1).map_or("", |m| m.as_str()));
+        let number = |name: &str| attrs.get(name).and_then(|value| value.parse::<u64>().ok());
+        let (start, duration) = match (number("t"), number("d")) {
+            (Some(start), Some(duration)) => (start, duration),
+            _ => continue,
+        };
+        let paragraph_pen = pen(&attrs);
+        let body = cap.get(2).map_or("", |m| m.as_str());
+
+        // Segments with their offsets, text outside of <s> is a segment too
+        let mut segments = Vec::new();
+        let mut last = 0;
+        for segment in SEGMENT.captures_iter(body) {
+            let whole = segment.get(0).unwrap();
+            let (begin, end) = (whole.start(), whole.end());
+            if !body[last..begin].trim().is_empty() {
+                segments.push((None, paragraph_pen, text(&body[last..begin])));
+            }
+            let attrs = attributes(segment.get(1).map_or("", |m| m.as_str()));
+            let offset = attrs.get("t").and_then(|value| value.parse::<u64>().ok());
+            let segment_pen = if attrs.contains_key("p") { pen(&attrs) } else { paragraph_pen };
+            segments.push((offset, segment_pen, text(segment.get(2).map_or("", |m| m.as_str()))));
+            last = end;
+        }
+        if !body[last..].trim().is_empty() {
+            segments.push((None, paragraph_pen, text(&body[last..])));
+        }
+
+        let joined: String = segments.iter().map(|(_, _, text)| text.as_str()).collect();
+        if joined.trim().is_empty() {
+            continue;
+        }
+        let styled: String = segments.iter().map(|&(_, pen, ref text)| pen.apply(text)).collect();
+        let styled = styled.trim().lines().map(str::trim).collect::<Vec<_>>().join("\r\n");
+
+        let end = start + duration;
+        let mut line = SubLine::new(lines.len() as u32 + 1,
+                                    styled,
+                                    Timestamp::from_miliseconds(start),
+                                    Timestamp::from_miliseconds(end));
+        if word_timing {
+            let mut offset = 0;
+            let mut words = Vec::new();
+            for (i, &(segment_offset, _, ref text)) in segments.iter().enumerate() {
+                offset = segment_offset.unwrap_or(offset);
+                let next = segments[i + 1..].iter().filter_map(|&(offset, _, _)| offset).next();
+                let word_end = next.map(|next| start + next).unwrap_or(end).min(end);
+                if !text.trim().is_empty() {
+                    words.push(Word {
+                        start: Timestamp::from_miliseconds((start + offset).min(end)),
+                        end: Timestamp::from_miliseconds(word_end),
+                        text: text.trim().to_owned(),
+                    });
+                }
+            }
+            line.words = words;
+        }
+        lines.push(line);
+    }
+
+    lines.sort_by_key(|line| line.start);
+    for i in 1..lines.len() {
+        let next_start = lines[i].start;
+        if lines[i - 1].end > next_start {
+            lines[i - 1].end = next_start;
+            for word in &mut lines[i - 1].words {
+                word.start = word.start.min(next_start);
+                word.end = word.end.min(next_start);
+            }
+        }
+    }
+
+    let mut subs = Subtitles::from(lines);
+    subs.renumber();
+    subs.metadata.source_format = Some("srv3".to_owned());
+    Ok(subs)
+}
+
+fn attributes(s: &str) -> HashMap<String, String> {
+    ATTRIBUTE.captures_iter(s)
+        .map(|cap| (cap.get(1).map_or("", |m| m.as_str()).to_owned(), entities::decode(cap.get(2).map_or("", |m| m.as_str())).into_owned()))
+        .collect()
+}
+
+/// Removes nested tags such as ```<br/>```, decodes entities.
+fn text(s: &str) -> String {
+    entities::decode(&TAG.replace_all(&s.replace("<br/>", "\n").replace("<br />", "\n"), "")).into_owned()
+}
+
+static PEN: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"<pen\b([^>]*)/?>").unwrap());
+static PARAGRAPH: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?s)<p\b([^>]*)>(.*?)</p>").unwrap());
+static SEGMENT: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?s)<s\b([^>]*)>(.*?)</s>").unwrap());
+static ATTRIBUTE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"([\w:-]+)\s*=\s*"([^"]*)""#).unwrap());
+static TAG: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"<[^>]*>").unwrap());
+
+#[cfg(test)]
+mod srv3_tests {
+    use super::*;
+
+    static CONTENT: &str = r##"<?xml version="1.0" encoding="utf-8" ?><timedtext format="3">
+<head>
+<pen id="1" fc="#FEFEFE"/>
+<pen id="2" b="1" i="1"/>
+<wp id="0" ap="7" ah="50" av="100"/>
+<ws id="0" ju="2"/>
+</head>
+<body>
+<w t="0" id="1" wp="0" ws="0"/>
+<p t="1000" d="4000" w="1"><s ac="0">Never</s><s t="500" ac="0"> gonna</s><s t="1200" p="2"> give</s></p>
+<p t="3000" d="10" w="1" a="1">
+</p>
+<p t="4000" d="2000" p="1">You &amp; me<br/>up</p>
+</body>
+</timedtext>"##;
+
+    #[test]
+    fn _from_str() {
+        let subs = from_str(CONTENT, false).unwrap();
+        assert_eq!(subs.len(), 2);
+        assert_eq!(subs.inner[0].text, "Never gonna <b><i>give</i></b>");
+        assert_eq!(subs.inner[0].start, Timestamp::new(0, 0, 1, 0));
+        assert_eq!(subs.inner[0].end, Timestamp::new(0, 0, 4, 0));
+        assert!(subs.inner[0].words.is_empty());
+        assert_eq!(subs.inner[1].text, "<font color=\"#FEFEFE\">You & me</font>\r\n<font color=\"#FEFEFE\">up</font>");
+        assert_eq!(subs.inner[1].index, 2);
+        assert_eq!(subs.metadata.source_format.as_ref().unwrap(), "srv3");
+
+        assert!(from_str("WEBVTT", false).is_err());
+    }
+
+    #[test]
+    fn word_timing() {
+        let subs = from_str(CONTENT, true).unwrap();
+        let words: Vec<_> = subs.inner[0]
+            .words
+            .iter()
+            .map(|word| (word.start.total_miliseconds(), word.end.total_miliseconds(), &*word.text))
+            .collect();
+        assert_eq!(words, vec![(1000, 1500, "Never"), (1500, 2200, "gonna"), (2200, 4000, "give")]);
+    }
+}
@@ -0,0 +1,300 @@
+//! EBU Tech 3264 subtitle files (```.stl```) for teletext (level 1.5) delivery.
+
+use crate::timestamp::Timestamp;
+use crate::subtitles::Subtitles;
+use crate::subline;
+use crate::utils;
+
+/// Size of the General Subtitle Information block.
+const GSI_SIZE: usize = 1024;
+/// Size of a Text and Timing Information block.
+const TTI_SIZE: usize = 128;
+/// Size of the text field of a TTI block.
+const TEXT_SIZE: usize = 112;
+
+const DOUBLE_HEIGHT: u8 = 0x0d;
+const START_BOX: u8 = 0x0b;
+const END_BOX: u8 = 0x0a;
+const NEWLINE: u8 = 0x8a;
+const UNUSED: u8 = 0x8f;
+
+/// Page layout and file information of the exported file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StlOptions {
+    /// Rows of a subtitle, longer texts are reduced.
+    pub max_rows: usize,
+    /// Characters of a row, without control codes.
+    pub max_chars: usize,
+    /// Rows take two teletext rows, as usual for subtitles.
+    pub double_height: bool,
+    /// Original programme title, ```Metadata::title``` is used if ```None```.
+    pub title: Option<String>,
+    /// Creation date as ```YYMMDD```.
+    pub creation_date: String,
+}
+
+impl Default for StlOptions {
+    fn default() -> StlOptions {
+        StlOptions {
+            max_rows: 2,
+            max_chars: 37,
+            double_height: true,
+            title: None,
+            creation_date: "000101".to_owned(),
+        }
+    }
+}
+
+/// Result of ```to_bytes```.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct StlExport {
+    pub data: Vec<u8>,
+    /// Indices of lines whose text didn't fit the rows and was cut.
+    pub reduced: Vec<u32>,
+}
+
+/// Writes lines as EBU STL with 25 fps timecodes and teletext display, using the Latin
+/// character table (ISO 6937). Tags are stripped, texts are wrapped to ```StlOptions::max_chars```
+/// and cut with ```...``` if they need more than ```StlOptions::max_rows``` rows.
+/// Characters the table lacks are written as ```?```.
+pub fn to_bytes(subs: &Subtitles, options: &StlOptions) -> StlExport {
+    let mut export = StlExport::default();
+    let mut blocks = Vec::new();
+    let mut subtitles = 0;
+
+    for (number, line) in subs.inner.iter().enumerate() {
+        let (rows, reduced) = reduce(&utils::strip_tags(&line.text), options);
+        if reduced {
+            export.reduced.push(line.index);
+        }
+        let row_height = if options.double_height { 2 } else { 1 };
+        let first_row = 22usize.saturating_sub(row_height * rows.len().saturating_sub(1)).max(1);
+
+        let mut text = Vec::new();
+        for (i, row) in rows.iter().enumerate() {
+            if i > 0 {
+                text.extend(if options.double_height { &[NEWLINE, NEWLINE][..] } else { &[NEWLINE][..] });
+            }
+            if options.double_height {
+                text.push(DOUBLE_HEIGHT);
+            }
+            text.extend(&[START_BOX, START_BOX]);
+            text.extend(encode(row));
+            text.extend(&[END_BOX, END_BOX]);
+        }
+
+        // Text longer than a block continues in extension blocks, the last one is 0xff
+        let chunks: Vec<&[u8]> = if text.is_empty() { vec![&[][..]] } else { text.chunks(TEXT_SIZE).collect() };
+        for (extension, chunk) in chunks.iter().enumerate() {
+            let mut block = vec![UNUSED; TTI_SIZE];
+            block[0] = 0;
+            block[1..3].copy_from_slice(&(number as u16).to_le_bytes());
+            block[3] = if extension + 1 == chunks.len() { 0xff } else { extension as u8 };
+            block[4] = 0;
+            block[5..9].copy_from_slice(&timecode(line.start));
+            block[9..13].copy_from_slice(&timecode(line.end));
+            block[13] = first_row as u8;
+            // Centered
+            block[14] = 2;
+            block[15] = 0;
+            block[16..16 + chunk.len()].copy_from_slice(chunk);
+            blocks.push(block);
+        }
+        subtitles += 1;
+    }
+
+    let title = options.title.as_deref().or(subs.metadata.title.as_deref()).unwrap_or("");
+    let first = subs.inner.first().map(|line| line.start).unwrap_or_default();
+    let mut gsi = vec![b' '; GSI_SIZE];
+    let mut field = |offset: usize, size: usize, value: &[u8]| {
+        let length = value.len().min(size);
+        gsi[offset..offset + length].copy_from_slice(&value[..length]);
+    };
+    field(0, 3, b"850");
+    field(3, 8, b"STL25.01");
+    field(11, 1, b"1");
+    field(12, 2, b"00");
+    field(14, 2, language_code(subs).as_bytes());
+    field(16, 32, &encode(title));
+    field(224, 6, options.creation_date.as_bytes());
+    field(230, 6, options.creation_date.as_bytes());
+    field(236, 2, b"00");
+    field(238, 5, format!("{:05}", blocks.len()).as_bytes());
+    field(243, 5, format!("{:05}", subtitles).as_bytes());
+    field(248, 3, b"001");
+    field(251, 2, b"40");
+    field(253, 2, b"23");
+    field(255, 1, b"1");
+    field(256, 8, b"00000000");
+    let first = timecode(first);
+    field(264, 8, format!("{:02}{:02}{:02}{:02}", first[0], first[1], first[2], first[3]).as_bytes());
+    field(272, 1, b"1");
+    field(273, 1, b"1");
+
+    export.data = gsi;
+    for block in blocks {
+        export.data.extend(block);
+    }
+    export
+}
+
+/// Rows of the text and whether it was cut to fit.
+fn reduce(text: &str, options: &StlOptions) -> (Vec<String>, bool) {
+    let max_chars = options.max_chars.max(4);
+    let mut rows: Vec<String> = Vec::new();
+    for paragraph in text.lines().filter(|row| !row.trim().is_empty()) {
+        let mut row = String::new();
+        for word in paragraph.split_whitespace() {
+            if !row.is_empty() && row.chars().count() + 1 + word.chars().count() > max_chars {
+                rows.push(::std::mem::take(&mut row));
+            }
+            if !row.is_empty() {
+                row.push(' ');
+            }
+            row.push_str(word);
+        }
+        rows.push(row);
+    }
+    let max_rows = options.max_rows.max(1);
+    if rows.len() <= max_rows && rows.iter().all(|row| row.chars().count() <= max_chars) {
+        return (rows, false);
+    }
+
+    let cut = |row: &str| if row.chars().count() <= max_chars {
+        row.to_owned()
+    } else {
+        subline::truncate(row, max_chars - 2).replace('…', "...")
+    };
+    if rows.len() > max_rows {
+        // The rest of the text goes to the last row, which is cut
+        let rest = rows.split_off(max_rows - 1).join(" ");
+        rows.push(rest);
+    }
+    (rows.iter().map(|row| cut(row)).collect(), true)
+}
+
+/// Hours, minutes, seconds and frames at 25 fps.
+fn timecode(time: Timestamp) -> [u8; 4] {
+    [time.hours.min(255) as u8, time.minutes as u8, time.seconds as u8, (time.miliseconds / 40) as u8]
+}
+
+/// Two digit hexadecimal language code of EBU Tech 3264 annex.
+fn language_code(subs: &Subtitles) -> &'static str {
+    let language = subs.metadata.language.as_ref().map(|language| language.as_str()).unwrap_or("");
+    LANGUAGES.iter().find(|&&(codes, _)| codes.split(' ').any(|code| code == language)).map_or("00", |&(_, code)| code)
+}
+
+static LANGUAGES: &[(&str, &str)] = &[("sq alb sqi", "01"),
+                                      ("br bre", "02"),
+                                      ("ca cat", "03"),
+                                      ("hr hrv", "04"),
+                                      ("cy wel cym", "05"),
+                                      ("cs cze ces", "06"),
+                                      ("da dan", "07"),
+                                      ("de ger deu", "08"),
+                                      ("en eng", "09"),
+                                      ("es spa", "0A"),
+                                      ("eo epo", "0B"),
+                                      ("et est", "0C"),
+                                      ("eu baq eus", "0D"),
+                                      ("fo fao", "0E"),
+                                      ("fr fre fra", "0F"),
+                                      ("fy fry", "10"),
+                                      ("ga gle", "11"),
+                                      ("gd gla", "12"),
+                                      ("gl glg", "13"),
+                                      ("is ice isl", "14"),
+                                      ("it ita", "15"),
+                                      ("lv lav", "19"),
+                                      ("lt lit", "1A"),
+                                      ("hu hun", "1B"),
+                                      ("mt mlt", "1C"),
+                                      ("nl dut nld", "1D"),
+                                      ("no nor nb nn", "1E"),
+                                      ("oc oci", "1F"),
+                                      ("pl pol", "20"),
+                                      ("pt por", "21"),
+                                      ("ro rum ron", "22"),
+                                      ("sr srp", "24"),
+                                      ("sk slo slk", "25"),
+                                      ("sl slv", "26"),
+                                      ("fi fin", "27"),
+                                      ("sv swe", "28"),
+                                      ("tr tur", "29")];
+
+/// Letters written as a non-spacing diacritic followed by the base letter.
+static DIACRITICS: &[(u8, &str, &str)] = &[(0xc1, "àèìòùÀÈÌÒÙ", "aeiouAEIOU"),
+                                           (0xc2, "áéíóúýćńśźÁÉÍÓÚÝĆŃŚŹ", "aeiouycnszAEIOUYCNSZ"),
+                                           (0xc3, "âêîôûÂÊÎÔÛ", "aeiouAEIOU"),
+                                           (0xc4, "ãñõÃÑÕ", "anoANO"),
+                                           (0xc8, "äëïöüÿÄËÏÖÜ", "aeiouyAEIOU"),
+                                           (0xca, "åůÅŮ", "auAU"),
+                                           (0xcb, "çşÇŞ", "csCS"),
+                                           (0xcf, "čďěňřšťžČĎĚŇŘŠŤŽ", "cdenrstzCDENRSTZ")];
+
+/// Characters of the ISO 6937 table with their own code.
+static SPECIAL: &[(char, u8)] = &[('¡', 0xa1), ('£', 0xa3), ('€', 0xa4), ('«', 0xab), ('°', 0xb0), ('»', 0xbb),
+                                  ('¿', 0xbf), ('–', 0xd0), ('‘', 0xa9), ('’', 0xb9), ('“', 0xaa), ('”', 0xba),
+                                  ('♪', 0xd5), ('Æ', 0xe1), ('Ø', 0xe9), ('Œ', 0xea), ('æ', 0xf1), ('ø', 0xf9),
+                                  ('œ', 0xf8), ('ß', 0xfb), ('…', 0x2e)];
+
+fn encode(text: &str) -> Vec<u8> {
+    let mut result = Vec::with_capacity(text.len());
+    for c in text.chars() {
+        if (' '..='~').contains(&c) {
+            result.push(c as u8);
+        } else if let Some(&(_, code)) = SPECIAL.iter().find(|&&(special, _)| special == c) {
+            result.push(code);
+        } else if let Some((diacritic, base)) = DIACRITICS.iter().find_map(|&(diacritic, letters, bases)| {
+            letters.chars().position(|letter| letter == c).and_then(|i| bases.chars().nth(i)).map(|base| (diacritic, base))
+        }) {
+            result.push(diacritic);
+            result.push(base as u8);
+        } else {
+            result.push(b'?');
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod stl_tests {
+    use super::*;
+    use std::str::FromStr;
+    use crate::language::LanguageTag;
+
+    #[test]
+    fn to_bytes() {
+        let mut subs = subtitles![(1000, 2500, "<i>Où est la gare?</i>"),
+                                  (3040, 4000, "This text is far too long to fit into two rows of teletext, \
+                                                so it has to be reduced")];
+        subs.metadata.language = Some(LanguageTag::from_str("fr").unwrap());
+        subs.metadata.title = Some("Film".to_owned());
+        let export = super::to_bytes(&subs, &StlOptions::default());
+        let data = &export.data;
+
+        assert_eq!(data.len(), GSI_SIZE + 2 * TTI_SIZE);
+        assert_eq!(&data[..14], b"850STL25.01100");
+        assert_eq!(&data[14..16], b"0F");
+        assert_eq!(&data[16..20], b"Film");
+        assert_eq!(&data[238..248], b"0000200002");
+        assert_eq!(&data[264..272], b"00000100");
+        assert_eq!(export.reduced, vec![2]);
+
+        let first = &data[GSI_SIZE..GSI_SIZE + TTI_SIZE];
+        assert_eq!(&first[..5], &[0, 0, 0, 0xff, 0]);
+        assert_eq!(&first[5..13], &[0, 0, 1, 0, 0, 0, 2, 12]);
+        assert_eq!(first[13], 22);
+        let text: Vec<u8> = first[16..].iter().cloned().take_while(|&byte| byte != UNUSED).collect();
+        assert_eq!(text, b"\x0d\x0b\x0bO\xc1u est la gare?\x0a\x0a".to_vec());
+
+        let second = &data[GSI_SIZE + TTI_SIZE..];
+        assert_eq!(second[13], 20);
+        assert_eq!(&second[5..9], &[0, 0, 3, 1]);
+        let text: Vec<u8> = second[16..].iter().cloned().take_while(|&byte| byte != UNUSED).collect();
+        let rows: Vec<&[u8]> = text.split(|&byte| byte == NEWLINE).filter(|row| !row.is_empty()).collect();
+        assert_eq!(rows.len(), 2);
+        assert!(rows.iter().all(|row| row.len() <= 37 + 5));
+        assert!(rows[1].ends_with(b"...\x0a\x0a"));
+    }
+}
@@ -0,0 +1,188 @@
+//! WebVTT (```.vtt```) format.
+
+use std::fmt::Write;
+use std::io::{Error, ErrorKind};
+use std::str::FromStr;
+
+use crate::timestamp::Timestamp;
+use crate::subline::SubLine;
+use crate::subtitles::Subtitles;
+use crate::cue_settings::CueSettings;
+use crate::entities;
+
+/// Formats ```Subtitles``` as WebVTT, lines' ids or, if not set, indices are used as cue identifiers.
+/// ```&```, ```<``` and ```>``` outside of tags are escaped.
+/// Lines' comments are written as ```NOTE``` blocks before them, without blank lines.
+/// Cue settings follow the timing.
+pub fn to_string(subs: &Subtitles) -> String {
+    let mut result = String::from("WEBVTT\n\n");
+    for line in subs {
+        if let Some(ref comment) = line.comment {
+            // A blank line would end the NOTE block
+            let comment = comment.lines().filter(|row| !row.trim().is_empty()).collect::<Vec<_>>().join("\n");
+            write!(result, "NOTE {}\n\n", comment.replace("-->", "->")).unwrap();
+        }
+        let settings = if line.settings.is_empty() {
+            String::new()
+        } else {
+            format!(" {}", line.settings)
+        };
+        let id = match line.id {
+            Some(ref id) => id.replace("-->", "->").replace('\n', " "),
+            None => line.index.to_string(),
+        };
+        write!(result,
+               "{}\n{} --> {}{}\n{}\n\n",
+               id,
+               timestamp(line.start),
+               timestamp(line.end),
+               settings,
+               entities::escape(&line.text.replace("\r\n", "\n")))
+            .unwrap();
+    }
+    result
+}
+
+/// Parses WebVTT content. Lines are numbered consecutively,
+/// cue identifiers become lines' ids unless they are the same as the index,
+/// ```NOTE``` blocks become comments of the following lines,
+/// entities in cue text (```&amp;```, ```&lt;```, ...) are decoded,
+/// ```STYLE``` and ```REGION``` blocks are skipped.
+pub fn from_str(content: &str) -> Result<Subtitles, Error> {
+    let invalid = |message: &str| Err(Error::new(ErrorKind::InvalidData, message.to_owned()));
+    let content = content.trim_start_matches('\u{feff}').replace("\r\n", "\n").replace('\r', "\n");
+
+    let mut blocks = content.split("\n\n").map(|block| block.trim_matches('\n')).filter(|block| !block.is_empty());
+    match blocks.next() {
+        Some(header) if header.starts_with("WEBVTT") => {}
+        _ => return invalid("WebVTT content must start with WEBVTT"),
+    }
+
+    let mut subs = Subtitles::default();
+    let mut comment: Option<String> = None;
+    for block in blocks {
+        if let Some(note) = block.strip_prefix("NOTE").filter(|note| note.is_empty() || note.starts_with(char::is_whitespace)) {
+            let note = note.trim();
+            comment = Some(match comment.take() {
+                Some(previous) => format!("{}\n{}", previous, note),
+                None => note.to_owned(),
+            });
+            continue;
+        }
+        if block.starts_with("STYLE") || block.starts_with("REGION") {
+            continue;
+        }
+
+        let index = subs.len() as u32 + 1;
+        let id = block.lines()
+            .next()
+            .filter(|line| !line.contains("-->"))
+            .map(str::trim)
+            .filter(|id| !id.is_empty() && *id != index.to_string())
+            .map(str::to_owned);
+        let mut lines = block.lines().skip_while(|line| !line.contains("-->"));
+        let (start, end, settings) = match lines.next().and_then(parse_timing) {
+            Some(timing) => timing,
+            None => return invalid("Invalid WebVTT cue timing"),
+        };
+        let text = entities::decode(&lines.collect::<Vec<_>>().join("\r\n")).into_owned();
+
+        let mut line = SubLine::new(index, text, start, end.max(start));
+        line.id = id;
+        line.comment = comment.take();
+        line.settings = settings;
+        subs.inner.push(line);
+    }
+    subs.metadata.source_format = Some("webvtt".to_owned());
+    Ok(subs)
+}
+
+fn parse_timing(line: &str) -> Option<(Timestamp, Timestamp, CueSettings)> {
+    let mut parts = line.splitn(2, "-->");
+    let start = parts.next().and_then(|start| parse_timestamp(start.trim()))?;
+    let mut rest = parts.next()?.trim_start().splitn(2, char::is_whitespace);
+    let end = rest.next().and_then(parse_timestamp)?;
+    let settings = CueSettings::from_str(rest.next().unwrap_or("")).unwrap_or_default();
+    Some((start, end, settings))
+}
+
+fn parse_timestamp(s: &str) -> Option<Timestamp> {
+    let parts: Vec<&str> = s.split(':').collect();
+    let (hours, minutes, rest) = match parts[..] {
+        [hours, minutes, rest] if hours.len() >= 2 => (hours, minutes, rest),
+        [minutes, rest] => ("0", minutes, rest),
+        _ => return None,
+    };
+    let (seconds, miliseconds) = rest.split_once('.')?;
+    let digits = |s: &str, len: usize| s.len() == len && s.bytes().all(|c| c.is_ascii_digit());
+    if !digits(hours, hours.len()) || !digits(minutes, 2) || !digits(seconds, 2) || !digits(miliseconds, 3) ||
+       minutes > "59" || seconds > "59" {
+        return None;
+    }
+    let number = |s: &str| s.parse::<u32>().ok();
+    let hours = number(hours)?;
+    if hours == u32::MAX {
+        return None;
+    }
+    Some(Timestamp::new(hours, number(minutes)?, number(seconds)?, number(miliseconds)?))
+}
+
+fn timestamp(time: Timestamp) -> String {
+    format!("{:02}:{:02}:{:02}.{:03}",
+            time.hours,
+            time.minutes,
+            time.seconds,
+            time.miliseconds)
+}
+
+#[cfg(test)]
+mod webvtt_tests {
+    use super::*;
+    use crate::utils;
+
+    #[test]
+    fn _to_string() {
+        let subs = utils::subs_from_texts(&["<i>One</i> & <3", "Two\r\nlines"]);
+        assert_eq!(to_string(&subs),
+                   "WEBVTT\n\n1\n00:00:00.000 --> 00:00:01.000\n<i>One</i> &amp; &lt;3\n\n\
+                    2\n00:00:02.000 --> 00:00:03.000\nTwo\nlines\n\n");
+    }
+
+    #[test]
+    fn _from_str() {
+        let content = "WEBVTT - Title\r\n\r\nSTYLE\r\n::cue { color: red }\r\n\r\n\
+                       NOTE Check the pun\r\n\r\n\
+                       intro\r\n00:01.000 --> 00:02.500 align:start\r\n<i>One</i>\r\n\r\n\
+                       01:00:03.000 --> 01:00:04.000\r\nTwo\r\nlines\r\n";
+        let subs = from_str(content).unwrap();
+        assert_eq!(subs.len(), 2);
+        assert_eq!(subs.inner[0].start, Timestamp::new(0, 0, 1, 0));
+        assert_eq!(subs.inner[0].end, Timestamp::new(0, 0, 2, 500));
+        assert_eq!(subs.inner[0].comment.as_ref().unwrap(), "Check the pun");
+        assert_eq!(subs.inner[0].settings.to_string(), "align:start");
+        assert_eq!(subs.inner[0].id.as_ref().unwrap(), "intro");
+        assert_eq!(subs.inner[1].id, None);
+        assert_eq!(subs.inner[1].text, "Two\r\nlines");
+        assert_eq!(subs.inner[1].index, 2);
+        assert_eq!(subs.metadata.source_format.as_ref().unwrap(), "webvtt");
+
+        let written = to_string(&subs);
+        assert!(written.starts_with("WEBVTT\n\nNOTE Check the pun\n\nintro\n00:00:01.000 --> 00:00:02.500 align:start\n"));
+        assert_eq!(from_str(&written).unwrap().inner, subs.inner);
+
+        let subs = utils::subs_from_texts(&["Tom & Jerry <3", "<i>a < b && c > d</i>"]);
+        let read = from_str(&to_string(&subs)).unwrap();
+        assert_eq!(read.inner[0].text, "Tom & Jerry <3");
+        assert_eq!(read.inner[1].text, "<i>a < b && c > d</i>");
+
+        let mut subs = utils::subs_from_texts(&["One", "Two"]);
+        subs.inner[0].comment = Some("First paragraph\r\n\r\nSecond\n  \nThird".to_owned());
+        let read = from_str(&to_string(&subs)).unwrap();
+        assert_eq!(read.len(), 2);
+        assert_eq!(read.inner[0].comment.as_deref(), Some("First paragraph\nSecond\nThird"));
+        assert_eq!(read.inner[1].text, "Two");
+
+        assert!(from_str("1\n00:01.000 --> 00:02.000\nText").is_err());
+        assert!(from_str("WEBVTT\n\n00:01.000 -> 00:02.000\nText").is_err());
+    }
+}
@@ -0,0 +1,142 @@
+//! XLIFF 1.2 (```.xlf```) export of lines' texts for translation tools.
+
+use std::fmt::Write;
+use std::sync::LazyLock;
+use std::io::{Error, ErrorKind};
+use std::str::FromStr;
+
+use regex::Regex;
+
+use crate::timestamp::Timestamp;
+use crate::subline::SubLine;
+use crate::subtitles::Subtitles;
+use crate::language::LanguageTag;
+use crate::parse;
+use crate::entities;
+
+/// Writes every line as a ```<trans-unit>``` with the text as ```<source>```,
+/// timing is kept in a ```<note from="timing">```. Lines' ids are written as ```resname```.
+/// ```source-language``` is ```Metadata::language``` or ```und``` if unknown.
+pub fn to_string(subs: &Subtitles, target_language: &LanguageTag) -> String {
+    let source_language = subs.metadata.language.as_ref().map(LanguageTag::as_str).unwrap_or("und");
+    let mut result = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+                                   <xliff version=\"1.2\" xmlns=\"urn:oasis:names:tc:xliff:document:1.2\">\n");
+    write!(result,
+           "  <file original=\"{}\" source-language=\"{}\" target-language=\"{}\" datatype=\"plaintext\">\n    <body>\n",
+           escape(subs.metadata.title.as_deref().unwrap_or("subtitles")),
+           source_language,
+           target_language)
+        .unwrap();
+    for line in subs {
+        let resname = match line.id {
+            Some(ref id) => format!(" resname=\"{}\"", escape(id)),
+            None => String::new(),
+        };
+        write!(result,
+               "      <trans-unit id=\"{}\"{} xml:space=\"preserve\">\n        \
+                <source>{}</source>\n        <note from=\"timing\">{} --> {}</note>\n      </trans-unit>\n",
+               line.index,
+               resname,
+               escape(&line.text.replace("\r\n", "\n")),
+               timestamp(line.start),
+               timestamp(line.end))
+            .unwrap();
+    }
+    result.push_str("    </body>\n  </file>\n</xliff>\n");
+    result
+}
+
+/// Parses XLIFF written by ```to_string```, translation units become lines with
+/// ```<target>``` text (```<source>``` if there is no target) and timing from the note.
+/// Lines are numbered consecutively, ```Metadata::language``` is the ```target-language```.
+pub fn from_str(content: &str) -> Result<Subtitles, Error> {
+    let invalid = |message: String| Err(Error::new(ErrorKind::InvalidData, message));
+
+    let mut subs = Subtitles::default();
+    if let Some(cap) = FILE.captures(content) {
+        subs.metadata.language = attribute(cap.get(1).map_or("", |m| m.as_str()), "target-language")
+            .and_then(|language| LanguageTag::from_str(&language).ok());
+    }
+
+    for cap in UNIT.captures_iter(content) {
+        let (attributes, body) = (cap.get(1).map_or("", |m| m.as_str()), cap.get(2).map_or("", |m| m.as_str()));
+        let unit = attribute(attributes, "id").unwrap_or_default();
+        let timing = NOTE.captures(body).and_then(|note| parse::parse_timing(note.get(1).map_or("", |m| m.as_str())));
+        let (start, end) = match timing {
+            Some(timing) => timing,
+            None => return invalid(format!("Missing timing note in translation unit {}", unit)),
+        };
+        let text = match TARGET.captures(body).or_else(|| SOURCE.captures(body)) {
+            Some(text) => text.get(1).map_or("", |m| m.as_str()).to_owned(),
+            None => return invalid(format!("Missing source in translation unit {}", unit)),
+        };
+
+        let mut line = SubLine::new(subs.len() as u32 + 1,
+                                    entities::decode(&text).replace("\r\n", "\n").replace('\n', "\r\n"),
+                                    start,
+                                    end.max(start));
+        line.id = attribute(attributes, "resname");
+        subs.inner.push(line);
+    }
+    subs.metadata.source_format = Some("xliff".to_owned());
+    Ok(subs)
+}
+
+/// Value of the attribute in ```<tag attributes>```.
+fn attribute(attributes: &str, name: &str) -> Option<String> {
+    ATTRIBUTE.captures_iter(attributes)
+        .find(|cap| cap.get(1).map(|m| m.as_str()) == Some(name))
+        .map(|cap| entities::decode(cap.get(2).map_or("", |m| m.as_str())).into_owned())
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn timestamp(time: Timestamp) -> String {
+    format!("{:02}:{:02}:{:02},{:03}",
+            time.hours,
+            time.minutes,
+            time.seconds,
+            time.miliseconds)
+}
+
+static FILE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"<file\b([^>]*)>").unwrap());
+static UNIT: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?s)<trans-unit\b([^>]*)>(.*?)</trans-unit>").unwrap());
+static SOURCE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?s)<source\b[^>]*>(.*?)</source>").unwrap());
+static TARGET: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?s)<target\b[^>]*>(.*?)</target>").unwrap());
+static NOTE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"(?s)<note\s+from="timing"\s*>(.*?)</note>"#).unwrap());
+static ATTRIBUTE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"([\w:-]+)\s*=\s*"([^"]*)""#).unwrap());
+
+#[cfg(test)]
+mod xliff_tests {
+    use super::*;
+    use crate::utils;
+
+    #[test]
+    fn round_trip() {
+        let mut subs = utils::subs_from_texts(&["<i>Fish & chips</i>", "Two\r\nlines"]);
+        subs.inner[1].id = Some("cue-2".to_owned());
+        subs.metadata.language = Some(LanguageTag::from_str("en").unwrap());
+        let french = LanguageTag::from_str("fr").unwrap();
+
+        let xliff = to_string(&subs, &french);
+        assert!(xliff.contains("source-language=\"en\" target-language=\"fr\""));
+        assert!(xliff.contains("<source>&lt;i&gt;Fish &amp; chips&lt;/i&gt;</source>"));
+        assert!(xliff.contains("<note from=\"timing\">00:00:02,000 --> 00:00:03,000</note>"));
+        assert!(xliff.contains("<trans-unit id=\"2\" resname=\"cue-2\""));
+
+        let untranslated = from_str(&xliff).unwrap();
+        assert_eq!(untranslated.inner, subs.inner);
+
+        let translated = xliff.replace("<source>Two\nlines</source>",
+                                       "<source>Two\nlines</source>\n        <target>Deux\nlignes</target>");
+        let translated = from_str(&translated).unwrap();
+        assert_eq!(translated.inner[1].text, "Deux\r\nlignes");
+        assert_eq!(translated.inner[1].start, subs.inner[1].start);
+        assert_eq!(translated.inner[1].id.as_ref().unwrap(), "cue-2");
+        assert_eq!(translated.metadata.language, Some(french));
+
+        assert!(from_str("<trans-unit id=\"1\"><source>Text</source></trans-unit>").is_err());
+    }
+}
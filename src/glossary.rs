@@ -0,0 +1,102 @@
+use std::borrow::Cow;
+use std::ops::Range;
+
+use regex::{Captures, Regex};
+
+use crate::subtitles::Subtitles;
+
+/// Term rendered differently from its approved translation, see ```Subtitles::enforce_glossary```.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GlossaryViolation {
+    /// Index of the line.
+    pub index: u32,
+    /// Position of the term in the glossary.
+    pub term: usize,
+    /// Byte range of the match in the line's text.
+    pub range: Range<usize>,
+    pub found: String,
+    pub expected: String,
+}
+
+impl Subtitles {
+    /// Checks that terms are translated consistently. Every glossary entry is a ```Regex```
+    /// matching all renderings of a term (e.g. ```(?i)\bdark lords?\b```) paired with
+    /// the approved one, matches which differ from it are violations.
+    /// Use ```Subtitles::apply_glossary``` to fix them.
+    ///
+    /// Returns violations in the order of lines and then of glossary entries.
+    pub fn enforce_glossary(&self, glossary: &[(Regex, String)]) -> Vec<GlossaryViolation> {
+        let mut violations = Vec::new();
+        for line in &self.inner {
+            for (term, (regex, expected)) in glossary.iter().enumerate() {
+                for found in regex.find_iter(&line.text).filter(|found| found.as_str() != expected) {
+                    violations.push(GlossaryViolation {
+                        index: line.index,
+                        term,
+                        range: found.range(),
+                        found: found.as_str().to_owned(),
+                        expected: expected.clone(),
+                    });
+                }
+            }
+        }
+        violations
+    }
+
+    /// Replaces every rendering of glossary terms with the approved one,
+    /// see ```Subtitles::enforce_glossary```. Approved renderings are inserted as they are,
+    /// ```$``` doesn't refer to capture groups. Returns the number of changed lines.
+    pub fn apply_glossary(&mut self, glossary: &[(Regex, String)]) -> usize {
+        self.edit_text(|text| {
+            let mut text = Cow::Borrowed(text);
+            for (regex, expected) in glossary {
+                if let Cow::Owned(replaced) = regex.replace_all(&text, |_: &Captures| expected.as_str()) {
+                    text = Cow::Owned(replaced);
+                }
+            }
+            text
+        })
+    }
+}
+
+#[cfg(test)]
+mod glossary_tests {
+    use super::*;
+    use crate::utils;
+
+    fn glossary() -> Vec<(Regex, String)> {
+        vec![(Regex::new(r"(?i)\bdark lords?\b").unwrap(), "Dark Lord".to_owned()),
+             (Regex::new(r"(?i)\bmuggles?\b").unwrap(), "Muggle".to_owned())]
+    }
+
+    #[test]
+    fn enforce_glossary() {
+        let subs = utils::subs_from_texts(&["The dark lord returns.", "A Muggle and a muggle.", "Nothing here."]);
+        let violations = subs.enforce_glossary(&glossary());
+        assert_eq!(violations,
+                   vec![GlossaryViolation {
+                            index: 1,
+                            term: 0,
+                            range: 4..13,
+                            found: "dark lord".to_owned(),
+                            expected: "Dark Lord".to_owned(),
+                        },
+                        GlossaryViolation {
+                            index: 2,
+                            term: 1,
+                            range: 15..21,
+                            found: "muggle".to_owned(),
+                            expected: "Muggle".to_owned(),
+                        }]);
+    }
+
+    #[test]
+    fn apply_glossary() {
+        let mut subs = utils::subs_from_texts(&["The dark lord returns.", "A Muggle and a muggle.", "Nothing here."]);
+        assert_eq!(subs.apply_glossary(&glossary()), 2);
+        assert_eq!(subs.inner[0].text, "The Dark Lord returns.");
+        assert_eq!(subs.inner[1].text, "A Muggle and a Muggle.");
+        assert!(subs.enforce_glossary(&glossary()).is_empty());
+        assert_eq!(subs.apply_glossary(&glossary()), 0);
+    }
+}
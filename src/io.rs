@@ -0,0 +1,155 @@
+//! Sidecar files next to videos, named the way Kodi, Plex and Jellyfin find them,
+//! e.g. ```Movie (2020).en.forced.srt``` for ```Movie (2020).mkv```.
+
+use std::fs;
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::subtitles::Subtitles;
+use crate::language::LanguageTag;
+
+/// Extensions of subtitle files recognized by ```find_sidecars```.
+static EXTENSIONS: &[&str] = &["srt", "vtt", "ass", "ssa"];
+
+/// Flags of a sidecar file name, written after the language.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SidecarFlags {
+    /// Track selected by default, ```.default```.
+    pub default: bool,
+    /// Subtitles for the deaf and hard of hearing, ```.sdh``` (```.cc``` is recognized too).
+    pub sdh: bool,
+    /// Only foreign dialogue and signs, ```.forced```.
+    pub forced: bool,
+}
+
+/// Subtitle file found next to a video by ```find_sidecars```.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sidecar {
+    pub path: PathBuf,
+    pub language: Option<LanguageTag>,
+    pub flags: SidecarFlags,
+}
+
+/// Path of the SubRip sidecar of ```video_path```: its name without the extension,
+/// followed by the language and flags, e.g. ```Movie (2020).en.default.forced.srt```.
+pub fn sidecar_path<P: AsRef<Path>>(video_path: P, language: Option<&LanguageTag>, flags: SidecarFlags) -> PathBuf {
+    let video_path = video_path.as_ref();
+    let mut name = video_path.file_stem().unwrap_or_default().to_os_string();
+    let parts = [(language.map(LanguageTag::as_str), true),
+                 (Some("default"), flags.default),
+                 (Some("sdh"), flags.sdh),
+                 (Some("forced"), flags.forced),
+                 (Some("srt"), true)];
+    for &(part, used) in &parts {
+        if let Some(part) = part.filter(|_| used) {
+            name.push(".");
+            name.push(part);
+        }
+    }
+    video_path.with_file_name(name)
+}
+
+/// Saves ```subs``` as the sidecar of ```video_path```, see ```sidecar_path```.
+/// Returns the path of the written file.
+pub fn save_sidecar<P: AsRef<Path>>(subs: &Subtitles,
+                                    video_path: P,
+                                    language: Option<&LanguageTag>,
+                                    flags: SidecarFlags)
+                                    -> Result<PathBuf, Error> {
+    let path = sidecar_path(video_path, language, flags);
+    subs.save_to_file(&path)?;
+    Ok(path)
+}
+
+/// Subtitle files in the directory of ```video_path``` named after it, sorted by path.
+/// Parts of the names between the video name and the extension are read as
+/// the language and flags, unknown parts (e.g. titles) are ignored.
+pub fn find_sidecars<P: AsRef<Path>>(video_path: P) -> Result<Vec<Sidecar>, Error> {
+    let video_path = video_path.as_ref();
+    let stem = match video_path.file_stem().and_then(|stem| stem.to_str()) {
+        Some(stem) => stem,
+        None => return Err(Error::new(ErrorKind::InvalidInput, "Video path has no valid file name")),
+    };
+    let directory = match video_path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+
+    let mut sidecars = Vec::new();
+    for entry in fs::read_dir(directory)? {
+        let path = entry?.path();
+        let name = match path.file_name().and_then(|name| name.to_str()) {
+            Some(name) => name,
+            None => continue,
+        };
+        let mut parts: Vec<&str> = match name.strip_prefix(stem).and_then(|rest| rest.strip_prefix('.')) {
+            Some(rest) => rest.split('.').collect(),
+            None => continue,
+        };
+        let extension = parts.pop().unwrap_or("").to_ascii_lowercase();
+        if !EXTENSIONS.contains(&&*extension) || !path.is_file() {
+            continue;
+        }
+
+        let mut sidecar = Sidecar {
+            path: path.clone(),
+            language: None,
+            flags: SidecarFlags::default(),
+        };
+        for part in parts {
+            match &*part.to_ascii_lowercase() {
+                "default" => sidecar.flags.default = true,
+                "sdh" | "cc" => sidecar.flags.sdh = true,
+                "forced" => sidecar.flags.forced = true,
+                _ if sidecar.language.is_none() && !part.contains(['-', '_']) => {
+                    sidecar.language = LanguageTag::from_str(part).ok();
+                }
+                _ => {}
+            }
+        }
+        sidecars.push(sidecar);
+    }
+    sidecars.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(sidecars)
+}
+
+#[cfg(test)]
+mod io_tests {
+    use super::*;
+    use std::env;
+    use crate::utils;
+
+    #[test]
+    fn sidecars() {
+        let directory = env::temp_dir().join(format!("srt_sidecars_{}", ::std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let video = directory.join("Movie (2020).mkv");
+        let english = LanguageTag::from_str("en").unwrap();
+
+        let forced = SidecarFlags { forced: true, ..SidecarFlags::default() };
+        assert_eq!(sidecar_path(&video, Some(&english), forced),
+                   directory.join("Movie (2020).en.forced.srt"));
+        assert_eq!(sidecar_path("Movie.mkv", None, SidecarFlags::default()), PathBuf::from("Movie.srt"));
+
+        let subs = utils::subs_from_texts(&["Hello"]);
+        let path = save_sidecar(&subs, &video, Some(&english), forced).unwrap();
+        assert_eq!(Subtitles::from_file(&path).unwrap(), subs);
+        for name in &["Movie (2020).fre.SDH.default.srt", "Movie (2020).Commentary.vtt",
+                      "Movie (2020).nfo", "Movie (2020) Extras.en.srt"] {
+            fs::write(directory.join(name), "").unwrap();
+        }
+
+        let found = find_sidecars(&video).unwrap();
+        fs::remove_dir_all(&directory).unwrap();
+        let found: Vec<_> = found.iter()
+            .map(|sidecar| (sidecar.path.file_name().unwrap().to_str().unwrap(), sidecar.language.clone(), sidecar.flags))
+            .collect();
+        assert_eq!(found,
+                   vec![("Movie (2020).Commentary.vtt", None, SidecarFlags::default()),
+                        ("Movie (2020).en.forced.srt", Some(english), forced),
+                        ("Movie (2020).fre.SDH.default.srt",
+                         Some(LanguageTag::from_str("fre").unwrap()),
+                         SidecarFlags { default: true, sdh: true, forced: false })]);
+    }
+}
@@ -0,0 +1,158 @@
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+use std::io::{Error, ErrorKind};
+
+#[cfg(feature = "language-detection")]
+use crate::subtitles::Subtitles;
+#[cfg(feature = "language-detection")]
+use crate::subline::SubLine;
+#[cfg(feature = "language-detection")]
+use crate::utils;
+
+/// Language of a track or a line, represented as lowercase BCP 47 primary language subtag:
+/// the shortest ISO 639 code, so ```"en"``` rather than ```"eng"```. Three-letter codes
+/// are kept only for languages without a two-letter one.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LanguageTag(String);
+
+impl LanguageTag {
+    /// Returns the language code.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Tag with the two-letter code of the language if ```code``` has one.
+    fn shortest(code: &str) -> LanguageTag {
+        let short = TWO_LETTER_CODES.iter().find(|&&(long, _)| long == code).map(|&(_, short)| short);
+        LanguageTag(short.unwrap_or(code).to_owned())
+    }
+}
+
+/// ISO 639-2 (both bibliographic and terminologic) and ISO 639-3 codes with their ISO 639-1 codes,
+/// covering the languages detected by whatlang and other common subtitle languages.
+static TWO_LETTER_CODES: &[(&str, &str)] = &[
+    ("afr", "af"), ("aka", "ak"), ("alb", "sq"), ("amh", "am"), ("ara", "ar"), ("arm", "hy"),
+    ("aze", "az"), ("baq", "eu"), ("bel", "be"), ("ben", "bn"), ("bod", "bo"), ("bos", "bs"),
+    ("bul", "bg"), ("bur", "my"), ("cat", "ca"), ("ces", "cs"), ("chi", "zh"), ("cmn", "zh"),
+    ("cym", "cy"), ("cze", "cs"), ("dan", "da"), ("deu", "de"), ("dut", "nl"), ("ell", "el"),
+    ("eng", "en"), ("epo", "eo"), ("est", "et"), ("eus", "eu"), ("fas", "fa"), ("fin", "fi"),
+    ("fra", "fr"), ("fre", "fr"), ("geo", "ka"), ("ger", "de"), ("gle", "ga"), ("glg", "gl"),
+    ("gre", "el"), ("guj", "gu"), ("heb", "he"), ("hin", "hi"), ("hrv", "hr"), ("hun", "hu"),
+    ("hye", "hy"), ("ice", "is"), ("ind", "id"), ("isl", "is"), ("ita", "it"), ("jav", "jv"),
+    ("jpn", "ja"), ("kan", "kn"), ("kat", "ka"), ("kaz", "kk"), ("khm", "km"), ("kor", "ko"),
+    ("lat", "la"), ("lav", "lv"), ("lit", "lt"), ("mac", "mk"), ("may", "ms"), ("mal", "ml"),
+    ("mar", "mr"), ("mkd", "mk"), ("mon", "mn"), ("msa", "ms"), ("mya", "my"), ("nep", "ne"),
+    ("nld", "nl"), ("nob", "nb"), ("nno", "nn"), ("nor", "no"), ("ori", "or"), ("pan", "pa"),
+    ("per", "fa"), ("pes", "fa"), ("pol", "pl"), ("por", "pt"), ("ron", "ro"), ("rum", "ro"),
+    ("rus", "ru"), ("sin", "si"), ("slk", "sk"), ("slo", "sk"), ("slv", "sl"), ("sna", "sn"),
+    ("spa", "es"), ("sqi", "sq"), ("srp", "sr"), ("swa", "sw"), ("swe", "sv"), ("tam", "ta"),
+    ("tel", "te"), ("tgl", "tl"), ("tha", "th"), ("tib", "bo"), ("tuk", "tk"), ("tur", "tr"),
+    ("ukr", "uk"), ("urd", "ur"), ("uzb", "uz"), ("vie", "vi"), ("wel", "cy"), ("yid", "yi"),
+    ("zho", "zh"), ("zul", "zu"),
+];
+
+impl FromStr for LanguageTag {
+    type Err = Error;
+    /// Constructs ```LanguageTag``` from the primary subtag of given tag,
+    /// so ```"en-US"``` becomes ```"en"```. Three-letter codes of languages
+    /// with a two-letter one are shortened, so ```"eng"``` and ```"ger"``` become ```"en"``` and ```"de"```.
+    fn from_str(tag: &str) -> Result<LanguageTag, Error> {
+        let primary = tag.trim().split(&['-', '_'][..]).next().unwrap_or("");
+
+        if primary.len() < 2 || primary.len() > 3 || !primary.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(Error::new(ErrorKind::InvalidInput, "Invalid language tag"));
+        }
+        Ok(LanguageTag::shortest(&primary.to_ascii_lowercase()))
+    }
+}
+
+impl Display for LanguageTag {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(feature = "language-detection")]
+impl From<whatlang::Lang> for LanguageTag {
+    /// Tag is the shortest code of the language, as for ```LanguageTag::from_str```.
+    fn from(lang: whatlang::Lang) -> LanguageTag {
+        LanguageTag::shortest(lang.code())
+    }
+}
+
+#[cfg(feature = "language-detection")]
+impl SubLine {
+    /// Detects language of the line's text.
+    pub fn detect_language(&self) -> Option<LanguageTag> {
+        whatlang::detect_lang(&utils::strip_tags(&self.text)).map(LanguageTag::from)
+    }
+}
+
+#[cfg(feature = "language-detection")]
+impl Subtitles {
+    /// Detects language of the whole track.
+    pub fn detect_language(&self) -> Option<LanguageTag> {
+        let mut text = String::new();
+        for line in self {
+            text.push_str(&utils::strip_tags(&line.text));
+            text.push('\n');
+        }
+        whatlang::detect_lang(&text).map(LanguageTag::from)
+    }
+
+    /// Returns lines that are detected to be in a language other than the language
+    /// of the whole track. Lines are detected with confidence (in range ```0.0...1.0```)
+    /// at least ```min_confidence```, lines are short so values about 0.5 work best.
+    pub fn foreign_lines(&self, min_confidence: f64) -> Vec<&SubLine> {
+        let language = match self.detect_language() {
+            Some(language) => language,
+            None => return Vec::new(),
+        };
+
+        self.inner
+            .iter()
+            .filter(|line| {
+                whatlang::detect(&utils::strip_tags(&line.text))
+                    .map(|info| info.confidence() >= min_confidence && LanguageTag::from(info.lang()) != language)
+                    .unwrap_or(false)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod language_tests {
+    use super::*;
+    use std::str::FromStr;
+    #[cfg(feature = "language-detection")]
+    use crate::utils;
+
+    #[test]
+    fn from_str() {
+        assert_eq!(LanguageTag::from_str("en-US").unwrap().as_str(), "en");
+        assert_eq!(LanguageTag::from_str("RUS").unwrap().as_str(), "ru");
+        assert_eq!(LanguageTag::from_str("ger").unwrap(), LanguageTag::from_str("de-DE").unwrap());
+        assert_eq!(LanguageTag::from_str("haw").unwrap().as_str(), "haw");
+        assert!(LanguageTag::from_str("english").is_err());
+        assert!(LanguageTag::from_str("").is_err());
+    }
+
+    #[cfg(feature = "language-detection")]
+    #[test]
+    fn detect_language() {
+        let subs = utils::subs_from_texts(&[
+            "The trial will be getting under way soon, and everyone must attend it.",
+            "You got my money? I told you to bring it here before the evening.",
+            "Я не знаю, где он сейчас находится, и мне это совершенно безразлично.",
+            "Later. Go away, I have a lot of work to do before the trial begins."]);
+
+        assert_eq!(subs.detect_language(), Some(LanguageTag::from_str("en-US").unwrap()));
+        assert_eq!(subs.detect_language().unwrap().as_str(), "en");
+        let foreign = subs.foreign_lines(0.5);
+        assert_eq!(foreign.len(), 1);
+        assert_eq!(foreign[0].index, 3);
+
+        assert!(whatlang::Lang::all().iter().all(|&lang| LanguageTag::from(lang).as_str().len() == 2));
+    }
+}
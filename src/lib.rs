@@ -1,12 +1,104 @@
+#[cfg(feature = "serde")]
 #[macro_use]
-extern crate lazy_static;
-extern crate regex;
+extern crate serde_derive;
 
+#[macro_use]
+mod macros;
 mod timestamp;
 mod utils;
 mod subline;
 mod subtitles;
+mod casing;
+#[cfg(feature = "text-tools")]
+mod censor;
+mod language;
+mod metadata;
+mod subtitle_set;
+mod subtitle_index;
+mod sync;
+mod patch;
+mod live;
+mod rollup;
+mod range;
+mod error;
+mod reorder;
+mod timeline;
+mod parse;
+mod tags;
+mod entities;
+mod color;
+mod cue_settings;
+mod adapters;
+mod compact;
+mod edit;
+mod editor;
+mod resegment;
+mod script;
+#[cfg(feature = "text-tools")]
+mod forced;
+mod roundtrip;
+mod fingerprint;
+#[cfg(feature = "text-tools")]
+mod speakers;
+mod cjk;
+mod punctuation;
+mod lyrics;
+mod credits;
+mod quality;
+mod names;
+#[cfg(feature = "text-tools")]
+mod glossary;
+#[cfg(feature = "rayon")]
+mod parallel;
+
+pub mod compare;
+pub mod formats;
+pub mod schema;
+pub mod merge;
+pub mod pipeline;
+pub mod render;
+#[cfg(feature = "io")]
+pub mod io;
+pub mod archive;
+pub mod lint;
+#[cfg(feature = "quickcheck")]
+pub mod arbitrary;
 
-pub use subtitles::Subtitles;
-pub use timestamp::Timestamp;
-pub use subline::SubLine;
+pub use crate::subtitles::{Subtitles, Boundary};
+pub use crate::timestamp::{Timestamp, Rounding};
+pub use crate::subline::{SubLine, Word};
+pub use crate::casing::CasingOptions;
+#[cfg(feature = "text-tools")]
+pub use crate::censor::{WordList, CensorStyle};
+pub use crate::language::LanguageTag;
+pub use crate::metadata::{Metadata, FrameRate};
+pub use crate::subtitle_set::{SubtitleSet, ConsistencyOptions, ConsistencyIssue};
+pub use crate::subtitle_index::{SubtitleIndex, Hit};
+pub use crate::sync::{SyncCorrection, AlignOptions};
+pub use crate::patch::{SubtitlePatch, PatchOperation};
+pub use crate::live::LiveTrack;
+pub use crate::range::RangeMut;
+pub use crate::error::{StructureError, EditError};
+pub use crate::reorder::{ReorderMode, RebuildReport};
+pub use crate::timeline::{ClampPolicy, ClampReport, ShiftReport, CoverageReport, MAX_BUCKETS};
+pub use crate::render::{RenderOptions, RenderStyle, Numbering, TerminalOptions};
+pub use crate::parse::{ParseOptions, Parsed, RawBlock, Renumber, DurationPolicy, ParseWarning};
+pub use crate::tags::{Tag, CueStyles};
+pub use crate::color::Color;
+pub use crate::cue_settings::{CueSettings, Percent, Vertical, Line, BoxAlign, TextAlign};
+pub use crate::adapters::{SubLineIterator, Shifted, Clipped, StrippedTags, Renumbered};
+pub use crate::compact::{CompactSubtitles, CompactLine};
+pub use crate::editor::Editor;
+pub use crate::pipeline::{TextProcessor, ProcessOutcome, Pipeline};
+pub use crate::resegment::SegmentLimits;
+pub use crate::script::ScriptOptions;
+pub use crate::roundtrip::{RoundTripReport, Difference, DifferenceKind};
+#[cfg(feature = "text-tools")]
+pub use crate::speakers::SpeakerStats;
+pub use crate::cjk::{CjkOptions, Width};
+pub use crate::punctuation::DashStyle;
+pub use crate::lyrics::LyricsStyle;
+#[cfg(feature = "text-tools")]
+pub use crate::glossary::GlossaryViolation;
+pub use crate::names::{NameCheckOptions, NameSpelling};
+pub use crate::quality::{QualityProfile, QualityCategory, QualityScore, CategoryScore, Rule, Violation};
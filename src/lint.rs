@@ -0,0 +1,315 @@
+//! Checking many files at once with reports for content pipelines and review tools.
+
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use crate::subtitles::Subtitles;
+use crate::quality::{QualityCategory, QualityProfile, Rule};
+use crate::parse::{self, ParseOptions};
+use crate::utils;
+
+/// Identifier of ```Diagnostic::rule``` for files which can't be read.
+pub const UNREADABLE: &str = "unreadable";
+/// Identifier of ```Diagnostic::rule``` for blocks which can't be parsed as lines.
+pub const INVALID_BLOCK: &str = "invalid-block";
+
+/// Rules checked by ```lint::run```.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Ruleset {
+    pub profile: QualityProfile,
+    /// Rules which aren't reported.
+    pub disabled: Vec<Rule>,
+}
+
+/// Severity of a ```Diagnostic```.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Level {
+    /// File can't be used as it is: it can't be read or parsed, or its structure is broken.
+    Error,
+    Warning,
+}
+
+impl Level {
+    fn as_str(&self) -> &'static str {
+        match *self {
+            Level::Error => "error",
+            Level::Warning => "warning",
+        }
+    }
+}
+
+/// Problem found in a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Diagnostic {
+    /// ```Rule::name``` or one of ```UNREADABLE``` and ```INVALID_BLOCK```.
+    pub rule: String,
+    pub level: Level,
+    pub message: String,
+    /// Line of the file where the block starts, ```None``` for the whole file.
+    pub line_number: Option<usize>,
+    /// Index of the subtitle line, if the block was parsed.
+    pub index: Option<u32>,
+}
+
+/// Diagnostics of a single file, in the order of their lines.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FileReport {
+    pub path: PathBuf,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Result of ```lint::run```, files are in the order they were given.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LintOutput {
+    pub files: Vec<FileReport>,
+}
+
+/// Checks SubRip files against ```ruleset```. Malformed blocks are reported and skipped,
+/// the rest of the file is still checked.
+pub fn run<P: AsRef<Path>>(paths: &[P], ruleset: &Ruleset) -> LintOutput {
+    let files = paths.iter()
+        .map(|path| {
+            let path = path.as_ref();
+            let diagnostics = match utils::read_file(path) {
+                Ok(content) => lint(&content, ruleset),
+                Err(error) => vec![Diagnostic {
+                    rule: UNREADABLE.to_owned(),
+                    level: Level::Error,
+                    message: error.to_string(),
+                    line_number: None,
+                    index: None,
+                }],
+            };
+            FileReport { path: path.to_owned(), diagnostics }
+        })
+        .collect();
+    LintOutput { files }
+}
+
+/// Diagnostics of SubRip ```content```.
+fn lint(content: &str, ruleset: &Ruleset) -> Vec<Diagnostic> {
+    let parsed = match Subtitles::parse(content, &ParseOptions::lenient()) {
+        Ok(parsed) => parsed,
+        Err(error) => {
+            return vec![Diagnostic {
+                            rule: INVALID_BLOCK.to_owned(),
+                            level: Level::Error,
+                            message: error.to_string(),
+                            line_number: None,
+                            index: None,
+                        }]
+        }
+    };
+    let line_numbers = parse::line_numbers(content);
+
+    let mut diagnostics: Vec<Diagnostic> = parsed.skipped
+        .iter()
+        .map(|block| Diagnostic {
+            rule: INVALID_BLOCK.to_owned(),
+            level: Level::Error,
+            message: "Block can't be parsed as a subtitle line".to_owned(),
+            line_number: Some(content[..block.range.start].matches('\n').count() + 1),
+            index: None,
+        })
+        .collect();
+    for (position, rule) in parsed.subtitles.violations(&ruleset.profile) {
+        if ruleset.disabled.contains(&rule) {
+            continue;
+        }
+        diagnostics.push(Diagnostic {
+            rule: rule.name().to_owned(),
+            level: if rule.category() == QualityCategory::Structure { Level::Error } else { Level::Warning },
+            message: rule.description().to_owned(),
+            line_number: line_numbers.get(position).cloned(),
+            index: Some(parsed.subtitles.inner[position].index),
+        });
+    }
+    diagnostics.sort_by_key(|diagnostic| diagnostic.line_number);
+    diagnostics
+}
+
+impl LintOutput {
+    /// Number of diagnostics with ```Level::Error``` in all files.
+    pub fn error_count(&self) -> usize {
+        self.count(Level::Error)
+    }
+
+    /// Number of diagnostics with ```Level::Warning``` in all files.
+    pub fn warning_count(&self) -> usize {
+        self.count(Level::Warning)
+    }
+
+    fn count(&self, level: Level) -> usize {
+        self.files.iter().flat_map(|file| &file.diagnostics).filter(|diagnostic| diagnostic.level == level).count()
+    }
+
+    /// Formats the output as JSON with ```files``` (```path``` and ```diagnostics```) and counts
+    /// of ```errors``` and ```warnings```. Diagnostics have the fields of ```Diagnostic```,
+    /// missing line numbers and indices are ```null```.
+    pub fn to_json(&self) -> String {
+        let mut json = String::from("{\"files\":[");
+        for (i, file) in self.files.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+            write!(json, "{{\"path\":{},\"diagnostics\":[", json_string(&file.path.to_string_lossy())).unwrap();
+            for (j, diagnostic) in file.diagnostics.iter().enumerate() {
+                if j > 0 {
+                    json.push(',');
+                }
+                write!(json,
+                       "{{\"rule\":{},\"level\":\"{}\",\"message\":{},\"line_number\":{},\"index\":{}}}",
+                       json_string(&diagnostic.rule),
+                       diagnostic.level.as_str(),
+                       json_string(&diagnostic.message),
+                       json_option(diagnostic.line_number),
+                       json_option(diagnostic.index))
+                    .unwrap();
+            }
+            json.push_str("]}");
+        }
+        write!(json, "],\"errors\":{},\"warnings\":{}}}", self.error_count(), self.warning_count()).unwrap();
+        json
+    }
+
+    /// Formats the output as SARIF 2.1.0 log with a single run, e.g. for code scanning
+    /// annotations. Diagnostics without a line number refer to the whole file.
+    pub fn to_sarif(&self) -> String {
+        let mut rules: Vec<(&str, &str)> = vec![(UNREADABLE, "File can't be read"),
+                                                (INVALID_BLOCK, "Block can't be parsed as a subtitle line")];
+        rules.extend(Rule::ALL.iter().map(|rule| (rule.name(), rule.description())));
+
+        let mut sarif = String::from("{\"$schema\":\"https://json.schemastore.org/sarif-2.1.0.json\",\"version\":\"2.1.0\",");
+        write!(sarif,
+               "\"runs\":[{{\"tool\":{{\"driver\":{{\"name\":\"srt\",\"version\":\"{}\",\"rules\":[",
+               env!("CARGO_PKG_VERSION"))
+            .unwrap();
+        for (i, &(id, description)) in rules.iter().enumerate() {
+            if i > 0 {
+                sarif.push(',');
+            }
+            write!(sarif, "{{\"id\":{},\"shortDescription\":{{\"text\":{}}}}}", json_string(id), json_string(description))
+                .unwrap();
+        }
+        sarif.push_str("]}},\"results\":[");
+        let mut first = true;
+        for file in &self.files {
+            let uri = json_string(&file.path.to_string_lossy().replace('\\', "/"));
+            for diagnostic in &file.diagnostics {
+                if !first {
+                    sarif.push(',');
+                }
+                first = false;
+                write!(sarif,
+                       "{{\"ruleId\":{},\"level\":\"{}\",\"message\":{{\"text\":{}}},\"locations\":[{{\"physicalLocation\":\
+                        {{\"artifactLocation\":{{\"uri\":{}}}",
+                       json_string(&diagnostic.rule),
+                       diagnostic.level.as_str(),
+                       json_string(&diagnostic.message),
+                       uri)
+                    .unwrap();
+                if let Some(line_number) = diagnostic.line_number {
+                    write!(sarif, ",\"region\":{{\"startLine\":{}}}", line_number).unwrap();
+                }
+                sarif.push_str("}}]}");
+            }
+        }
+        sarif.push_str("]}]}");
+        sarif
+    }
+}
+
+/// JSON string literal of ```s```.
+fn json_string(s: &str) -> String {
+    let mut result = String::with_capacity(s.len() + 2);
+    result.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(result, "\\u{:04x}", c as u32).unwrap(),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+fn json_option<T: ToString>(value: Option<T>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_else(|| "null".to_owned())
+}
+
+#[cfg(test)]
+mod lint_tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+
+    fn output(name: &str) -> LintOutput {
+        let path = env::temp_dir().join(name);
+        fs::write(&path,
+                  "1\r\n00:00:01,000 --> 00:00:03,000\r\nHello\r\n\r\n\
+                   2\r\nbroken\r\n\r\n\
+                   3\r\n00:00:02,000 --> 00:00:04,000\r\nOverlapping \"line\"\r\n")
+            .unwrap();
+        let ruleset = Ruleset { disabled: vec![Rule::IndexMismatch], ..Ruleset::default() };
+        let output = run(&[path.clone(), env::temp_dir().join("srt_lint_missing.srt")], &ruleset);
+        fs::remove_file(&path).unwrap();
+        output
+    }
+
+    #[test]
+    fn _run() {
+        let output = output("srt_lint_run.srt");
+        let diagnostics: Vec<(&str, Level, Option<usize>, Option<u32>)> = output.files[0]
+            .diagnostics
+            .iter()
+            .map(|d| (&*d.rule, d.level, d.line_number, d.index))
+            .collect();
+        assert_eq!(diagnostics,
+                   vec![(INVALID_BLOCK, Level::Error, Some(5), None),
+                        ("overlap", Level::Warning, Some(8), Some(3))]);
+        assert_eq!(output.files[1].diagnostics[0].rule, UNREADABLE);
+        assert_eq!((output.error_count(), output.warning_count()), (2, 1));
+    }
+
+    #[test]
+    fn to_json() {
+        let output = LintOutput {
+            files: vec![FileReport {
+                            path: PathBuf::from("a \"b\".srt"),
+                            diagnostics: vec![Diagnostic {
+                                                  rule: "overlap".to_owned(),
+                                                  level: Level::Warning,
+                                                  message: "Line overlaps\na previous line".to_owned(),
+                                                  line_number: Some(8),
+                                                  index: None,
+                                              }],
+                        }],
+        };
+        assert_eq!(output.to_json(),
+                   "{\"files\":[{\"path\":\"a \\\"b\\\".srt\",\"diagnostics\":[{\"rule\":\"overlap\",\"level\":\"warning\",\
+                    \"message\":\"Line overlaps\\na previous line\",\"line_number\":8,\"index\":null}]}],\
+                    \"errors\":0,\"warnings\":1}");
+    }
+
+    #[test]
+    fn to_sarif() {
+        let sarif = output("srt_lint_sarif.srt").to_sarif();
+        assert!(sarif.starts_with("{\"$schema\":\"https://json.schemastore.org/sarif-2.1.0.json\",\"version\":\"2.1.0\""));
+        assert!(sarif.contains("{\"id\":\"gap-too-short\",\"shortDescription\":{\"text\":\"Gap after the line is too short\"}}"));
+        assert!(sarif.contains("{\"ruleId\":\"overlap\",\"level\":\"warning\",\"message\":{\"text\":\"Line overlaps a previous line\"},\
+                                \"locations\":[{\"physicalLocation\":{\"artifactLocation\":{\"uri\":"));
+        assert!(sarif.contains("srt_lint_sarif.srt\"},\"region\":{\"startLine\":8}}}]}"));
+        assert!(sarif.contains("srt_lint_missing.srt\"}}}]}"));
+        assert!(sarif.ends_with("]}]}"));
+    }
+}
@@ -0,0 +1,131 @@
+use crate::timestamp::Timestamp;
+use crate::subline::SubLine;
+use crate::subtitles::Subtitles;
+#[cfg(feature = "formats-webvtt")]
+use crate::formats::webvtt;
+
+/// Track that grows during live captioning.
+///
+/// Lines are appended as provisional, they may be amended while recognition
+/// (or a stenographer) refines them, and become stable once finalized.
+/// Only the stable part is exported.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct LiveTrack {
+    stable: Subtitles,
+    provisional: Vec<(u64, SubLine)>,
+    next_id: u64,
+}
+
+impl LiveTrack {
+    /// Constructs an empty ```LiveTrack```.
+    pub fn new() -> LiveTrack {
+        LiveTrack::default()
+    }
+
+    /// Appends a provisional line and returns its id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if ```start``` timestamp is bigger than the ```end``` timestamp.
+    pub fn append(&mut self, start: Timestamp, end: Timestamp, text: String) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.provisional.push((id, SubLine::new(0, text, start, end)));
+        id
+    }
+
+    /// Replaces text and end of the provisional line.
+    /// Returns ```false``` if there is no such provisional line or ```end``` is before its start.
+    pub fn amend(&mut self, id: u64, text: String, end: Timestamp) -> bool {
+        match self.provisional.iter_mut().find(|&&mut (line_id, _)| line_id == id) {
+            Some(&mut (_, ref mut line)) if line.start <= end => {
+                line.text = text;
+                line.end = end;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Finalizes the provisional line and all provisional lines appended before it.
+    /// Returns ```false``` if there is no such provisional line.
+    pub fn finalize(&mut self, id: u64) -> bool {
+        match self.provisional.iter().position(|&(line_id, _)| line_id == id) {
+            Some(position) => {
+                self.stabilize(position + 1);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Finalizes provisional lines (in order of appending) ending no later than ```time```.
+    /// Returns the number of finalized lines.
+    pub fn finalize_until(&mut self, time: Timestamp) -> usize {
+        let count = self.provisional.iter().take_while(|&(_, line)| line.end <= time).count();
+        self.stabilize(count);
+        count
+    }
+
+    /// Finalized lines.
+    pub fn stable(&self) -> &Subtitles {
+        &self.stable
+    }
+
+    /// Provisional lines with their ids, in order of appending.
+    pub fn provisional(&self) -> &[(u64, SubLine)] {
+        &self.provisional
+    }
+
+    /// Formats finalized lines according srt subtitles format.
+    pub fn to_srt(&self) -> String {
+        self.stable.to_string()
+    }
+
+    /// Formats finalized lines according WebVTT format.
+    #[cfg(feature = "formats-webvtt")]
+    pub fn to_webvtt(&self) -> String {
+        webvtt::to_string(&self.stable)
+    }
+
+    fn stabilize(&mut self, count: usize) {
+        for (_, mut line) in self.provisional.drain(..count) {
+            line.index = self.stable.len() as u32 + 1;
+            self.stable.inner.push(line);
+        }
+    }
+}
+
+#[cfg(test)]
+mod live_tests {
+    use super::*;
+    use crate::timestamp::Timestamp;
+
+    #[test]
+    fn live_track() {
+        let mut track = LiveTrack::new();
+        let first = track.append(Timestamp::new(0, 0, 1, 0), Timestamp::new(0, 0, 2, 0), "THE TRIAL".to_owned());
+        let second = track.append(Timestamp::new(0, 0, 2, 0), Timestamp::new(0, 0, 3, 0), "WILL".to_owned());
+        let third = track.append(Timestamp::new(0, 0, 3, 0), Timestamp::new(0, 0, 4, 0), "SOON".to_owned());
+
+        assert!(track.amend(second, "WILL BE".to_owned(), Timestamp::new(0, 0, 2, 500)));
+        assert!(!track.amend(second, "WILL BE".to_owned(), Timestamp::new(0, 0, 1, 0)));
+        assert!(track.finalize(second));
+        assert!(!track.finalize(first));
+        assert!(!track.amend(first, "THE TRIAL".to_owned(), Timestamp::new(0, 0, 2, 0)));
+
+        assert_eq!(track.stable().len(), 2);
+        assert_eq!(track.stable()[1].text, "WILL BE");
+        assert_eq!(track.stable()[1].index, 2);
+        assert_eq!(track.provisional().len(), 1);
+        assert_eq!(track.provisional()[0].0, third);
+        assert_eq!(track.to_srt(),
+                   "1\r\n00:00:01,000 --> 00:00:02,000\r\nTHE TRIAL\r\n\r\n\
+                    2\r\n00:00:02,000 --> 00:00:02,500\r\nWILL BE\r\n\r\n\r\n\r\n");
+
+        assert_eq!(track.finalize_until(Timestamp::new(0, 0, 3, 999)), 0);
+        assert_eq!(track.finalize_until(Timestamp::new(0, 0, 4, 0)), 1);
+        #[cfg(feature = "formats-webvtt")]
+        assert!(track.to_webvtt().ends_with("3\n00:00:03.000 --> 00:00:04.000\nSOON\n\n"));
+    }
+}
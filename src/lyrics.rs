@@ -0,0 +1,174 @@
+use crate::subtitles::Subtitles;
+use crate::subline::SubLine;
+use crate::utils;
+
+/// Music notes used as lyrics markers, ```#``` is also recognized at the edges of rows.
+static NOTES: &[char] = &['♪', '♫', '♬', '♩'];
+
+/// How lyrics are formatted by ```Subtitles::format_lyrics```.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LyricsStyle {
+    /// Keep the markers, only replacing them with the given one: "# la la" becomes "♪ la la".
+    Keep,
+    /// Remove the markers and keep the text: "♪ la la ♪" becomes "la la".
+    StripMarkers,
+    /// Keep the markers and put the text in italics: "♪ la la ♪" becomes "<i>♪ la la ♪</i>".
+    Italicize,
+    /// Remove the whole line with lyrics.
+    RemoveLine,
+}
+
+impl SubLine {
+    /// Returns ```true``` if a row of the text starts or ends with a music note
+    /// (or ```#``` as in some broadcast captions), i.e. the line contains lyrics.
+    pub fn is_lyrics(&self) -> bool {
+        utils::strip_tags(&self.text).lines().any(|row| {
+            let row = row.trim();
+            row.starts_with(NOTES) || row.ends_with(NOTES) || row.starts_with("# ") || row.ends_with(" #") || row == "#"
+        })
+    }
+}
+
+impl Subtitles {
+    /// Formats lines with lyrics (see ```SubLine::is_lyrics```) according ```style```,
+    /// replacing different note conventions with ```marker``` (usually ```'♪'```) first.
+    /// Lines are renumbered if any of them were removed.
+    ///
+    /// Returns the number of changed or removed lines.
+    pub fn format_lyrics(&mut self, marker: char, style: LyricsStyle) -> usize {
+        if style == LyricsStyle::RemoveLine {
+            let len = self.inner.len();
+            self.inner.retain(|line| !line.is_lyrics());
+            let removed = len - self.inner.len();
+            if removed > 0 {
+                self.renumber();
+            }
+            return removed;
+        }
+
+        let mut changed = 0;
+        for line in &mut self.inner {
+            if !line.is_lyrics() {
+                continue;
+            }
+            let rows: Vec<String> = line.text.split('\n').map(|row| normalize_row(row, marker, style)).collect();
+            let mut text = rows.join("\n");
+            if style == LyricsStyle::Italicize && !text.trim_start().starts_with("<i>") {
+                text = format!("<i>{}</i>", text.trim_end());
+                if line.text.ends_with("\r\n") {
+                    text.push_str("\r\n");
+                }
+            }
+            if text != line.text {
+                line.text = text;
+                changed += 1;
+            }
+        }
+        changed
+    }
+}
+
+/// Replaces notes in ```row``` with single ```marker```s separated by spaces, or removes them.
+fn normalize_row(row: &str, marker: char, style: LyricsStyle) -> String {
+    let content = row.trim_end_matches('\r');
+    let visible: Vec<(usize, char)> = visible_chars(content);
+    let first = visible.iter().find(|&&(_, c)| !c.is_whitespace()).map(|&(i, _)| i);
+    let last = visible.iter().rev().find(|&&(_, c)| !c.is_whitespace()).map(|&(i, _)| i);
+
+    let mut result = String::with_capacity(row.len());
+    let mut in_tag = false;
+    let mut previous_marker = false;
+    for (i, c) in content.char_indices() {
+        in_tag = (in_tag || c == '<') && c != '>';
+        let is_marker = !in_tag && c != '>' && (NOTES.contains(&c) || (c == '#' && (Some(i) == first || Some(i) == last)));
+        if is_marker {
+            if !previous_marker {
+                if style == LyricsStyle::StripMarkers {
+                    trim_trailing_spaces(&mut result);
+                } else {
+                    if !result.is_empty() && !result.ends_with([' ', '>']) {
+                        result.push(' ');
+                    }
+                    result.push(marker);
+                }
+            }
+            previous_marker = true;
+        } else if c == ' ' && previous_marker {
+            // spaces between markers are collapsed
+        } else {
+            let separated = style != LyricsStyle::StripMarkers || !(result.is_empty() || result.ends_with([' ', '>']));
+            if previous_marker && separated && !in_tag && c != '<' {
+                result.push(' ');
+            }
+            previous_marker = false;
+            result.push(c);
+        }
+    }
+    if style == LyricsStyle::StripMarkers {
+        let start = result.len() - result.trim_start().len();
+        result.drain(..start);
+        trim_trailing_spaces(&mut result);
+    }
+    result.push_str(&row[content.len()..]);
+    result
+}
+
+fn visible_chars(text: &str) -> Vec<(usize, char)> {
+    let mut in_tag = false;
+    text.char_indices()
+        .filter(|&(_, c)| {
+            let visible = !in_tag && c != '<';
+            in_tag = (in_tag || c == '<') && c != '>';
+            visible
+        })
+        .collect()
+}
+
+/// Removes spaces at the end of ```text```.
+fn trim_trailing_spaces(text: &mut String) {
+    let trimmed = text.trim_end_matches(' ').len();
+    text.truncate(trimmed);
+}
+
+#[cfg(test)]
+mod lyrics_tests {
+    use super::*;
+
+    fn lyrics() -> Subtitles {
+        subtitles![(0, 1000, "♫ Hello darkness♫\r\n♫♫ my old friend ♫"),
+                   (2000, 3000, "<i># I've come to talk #</i>"),
+                   (4000, 5000, "Number #1 song."),
+                   (6000, 7000, "- What?\r\n- ♪ With you again ♪")]
+    }
+
+    #[test]
+    fn is_lyrics() {
+        let detected: Vec<bool> = lyrics().inner.iter().map(SubLine::is_lyrics).collect();
+        assert_eq!(detected, vec![true, true, false, true]);
+    }
+
+    #[test]
+    fn format_lyrics() {
+        let mut subs = lyrics();
+        assert_eq!(subs.format_lyrics('♪', LyricsStyle::Keep), 2);
+        assert_eq!(subs.inner[0].text, "♪ Hello darkness ♪\r\n♪ my old friend ♪");
+        assert_eq!(subs.inner[1].text, "<i>♪ I've come to talk ♪</i>");
+        assert_eq!(subs.format_lyrics('♪', LyricsStyle::Keep), 0);
+
+        let mut stripped = lyrics();
+        assert_eq!(stripped.format_lyrics('♪', LyricsStyle::StripMarkers), 3);
+        assert_eq!(stripped.inner[0].text, "Hello darkness\r\nmy old friend");
+        assert_eq!(stripped.inner[1].text, "<i>I've come to talk</i>");
+        assert_eq!(stripped.inner[3].text, "- What?\r\n- With you again");
+
+        let mut italic = lyrics();
+        assert_eq!(italic.format_lyrics('♪', LyricsStyle::Italicize), 3);
+        assert_eq!(italic.inner[0].text, "<i>♪ Hello darkness ♪\r\n♪ my old friend ♪</i>");
+        assert_eq!(italic.inner[1].text, "<i>♪ I've come to talk ♪</i>");
+
+        let mut removed = lyrics();
+        assert_eq!(removed.format_lyrics('♪', LyricsStyle::RemoveLine), 3);
+        assert_eq!(removed.inner.len(), 1);
+        assert_eq!(removed.inner[0].index, 1);
+    }
+}
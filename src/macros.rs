@@ -0,0 +1,52 @@
+/// Builds ```Subtitles``` from ```(start, end, text)``` tuples with times in miliseconds,
+/// lines are numbered from 1. Panics if lines end before they start, aren't sorted or overlap,
+/// so it's meant for tests and examples.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate srt;
+///
+/// # fn main() {
+/// let subs = subtitles![(1000, 2500, "First line"),
+///                       (3000, 4000, "Second\r\nline")];
+/// assert_eq!(subs.len(), 2);
+/// assert_eq!(subs.inner[1].index, 2);
+/// assert_eq!(subs.inner[1].start, srt::Timestamp::new(0, 0, 3, 0));
+/// # }
+/// ```
+#[macro_export]
+macro_rules! subtitles {
+    ($(($start:expr, $end:expr, $text:expr)),* $(,)*) => {{
+        #[allow(unused_mut)]
+        let mut subs = $crate::Subtitles::default();
+        $(
+            let index = subs.len() as u32 + 1;
+            subs.inner.push($crate::SubLine::new(index,
+                                                 ::std::string::String::from($text),
+                                                 $crate::Timestamp::from_miliseconds($start),
+                                                 $crate::Timestamp::from_miliseconds($end)));
+        )*
+        subs.edit().finish().expect("Invalid subtitles! fixture")
+    }};
+}
+
+#[cfg(test)]
+mod macros_tests {
+    use crate::subtitles::Subtitles;
+    use crate::utils;
+
+    #[test]
+    fn _subtitles() {
+        assert_eq!(subtitles![(0, 1000, "One"), (2000, 3000, "Two".to_owned()),],
+                   utils::subs_from_texts(&["One", "Two"]));
+        assert_eq!(subtitles![], Subtitles::default());
+    }
+
+    #[test]
+    #[should_panic]
+    fn overlapping() {
+        subtitles![(0, 1000, "One"), (500, 3000, "Two")];
+    }
+}
@@ -0,0 +1,286 @@
+//! Three-way merge of concurrently edited versions of a track.
+
+use crate::subline::SubLine;
+use crate::subtitles::Subtitles;
+use crate::metadata::Metadata;
+use crate::patch::PatchOperation;
+
+/// Change which can't be merged automatically.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Conflict {
+    /// Both versions changed the same part (text, timing, comment, settings or words)
+    /// of the line differently.
+    Edit {
+        base: SubLine,
+        ours: SubLine,
+        theirs: SubLine,
+    },
+    /// One version removed the line, while the other changed it.
+    RemovedEdited {
+        base: SubLine,
+        /// Changed line, ```ours``` tells which version has it.
+        edited: SubLine,
+        ours: bool,
+    },
+    /// Both versions inserted different lines at the same place.
+    Insert {
+        ours: Vec<SubLine>,
+        theirs: Vec<SubLine>,
+    },
+    /// Both versions changed ```Metadata``` differently.
+    Metadata {
+        base: Metadata,
+        ours: Metadata,
+        theirs: Metadata,
+    },
+}
+
+/// What happened to the lines of the base version in an edited version.
+struct Changes<'a> {
+    /// Line at every base position, ```None``` if it was removed.
+    lines: Vec<Option<&'a SubLine>>,
+    /// Lines inserted before every base position and at the end.
+    inserted: Vec<Vec<&'a SubLine>>,
+}
+
+impl<'a> Changes<'a> {
+    fn new(base: &Subtitles, edited: &'a Subtitles) -> Changes<'a> {
+        // Replays the diff on the base positions to find where every edited line came from
+        let mut origins: Vec<Option<usize>> = (0..base.len()).map(Some).collect();
+        for operation in base.diff(edited).operations {
+            match operation {
+                PatchOperation::Insert { position, .. } => origins.insert(position, None),
+                PatchOperation::Remove { position, .. } => {
+                    origins.remove(position);
+                }
+                PatchOperation::SetText { .. } |
+                PatchOperation::SetTiming { .. } |
+                PatchOperation::SetId { .. } |
+                PatchOperation::SetComment { .. } |
+                PatchOperation::SetSettings { .. } |
+                PatchOperation::SetWords { .. } => {}
+            }
+        }
+
+        let mut changes = Changes {
+            lines: vec![None; base.len()],
+            inserted: vec![Vec::new(); base.len() + 1],
+        };
+        let mut pending = Vec::new();
+        for (origin, line) in origins.into_iter().zip(&edited.inner) {
+            match origin {
+                Some(position) => {
+                    changes.lines[position] = Some(line);
+                    changes.inserted[position] = ::std::mem::take(&mut pending);
+                }
+                None => pending.push(line),
+            }
+        }
+        changes.inserted[base.len()] = pending;
+        changes
+    }
+}
+
+/// Picks the changed value, ```None``` if both sides changed it differently.
+fn pick<T: PartialEq + Clone>(base: &T, ours: &T, theirs: &T) -> Option<T> {
+    if ours == base || ours == theirs {
+        Some(theirs.clone())
+    } else if theirs == base {
+        Some(ours.clone())
+    } else {
+        None
+    }
+}
+
+/// Merges changes made in ```ours``` and ```theirs``` to their common ancestor ```base```.
+///
+/// Lines are matched as in ```Subtitles::diff```. Changes of different parts
+/// of the same line, e.g. text in one version and timing in the other, are merged.
+/// Lines are sorted by time and renumbered afterwards.
+///
+/// Returns all conflicts if any of the changes can't be merged.
+pub fn three_way(base: &Subtitles, ours: &Subtitles, theirs: &Subtitles) -> Result<Subtitles, Vec<Conflict>> {
+    let our_changes = Changes::new(base, ours);
+    let their_changes = Changes::new(base, theirs);
+    let mut conflicts = Vec::new();
+    let mut merged = Subtitles::default();
+
+    match pick(&base.metadata, &ours.metadata, &theirs.metadata) {
+        Some(metadata) => merged.metadata = metadata,
+        None => {
+            conflicts.push(Conflict::Metadata {
+                base: base.metadata.clone(),
+                ours: ours.metadata.clone(),
+                theirs: theirs.metadata.clone(),
+            })
+        }
+    }
+
+    for position in 0..base.len() + 1 {
+        let our_inserted = &our_changes.inserted[position];
+        let their_inserted = &their_changes.inserted[position];
+        if our_inserted.is_empty() || our_inserted == their_inserted {
+            merged.inner.extend(their_inserted.iter().map(|&line| line.clone()));
+        } else if their_inserted.is_empty() {
+            merged.inner.extend(our_inserted.iter().map(|&line| line.clone()));
+        } else {
+            conflicts.push(Conflict::Insert {
+                ours: our_inserted.iter().map(|&line| line.clone()).collect(),
+                theirs: their_inserted.iter().map(|&line| line.clone()).collect(),
+            });
+        }
+
+        let original = match base.inner.get(position) {
+            Some(line) => line,
+            None => break,
+        };
+        match (our_changes.lines[position], their_changes.lines[position]) {
+            (None, None) => {}
+            (Some(remaining), None) |
+            (None, Some(remaining)) => {
+                let unchanged = remaining.text == original.text && remaining.start == original.start &&
+                                remaining.end == original.end &&
+                                remaining.comment == original.comment &&
+                                remaining.settings == original.settings &&
+                                remaining.words == original.words;
+                if !unchanged {
+                    conflicts.push(Conflict::RemovedEdited {
+                        base: original.clone(),
+                        edited: remaining.clone(),
+                        ours: our_changes.lines[position].is_some(),
+                    });
+                }
+            }
+            (Some(our_line), Some(their_line)) => {
+                let text = pick(&original.text, &our_line.text, &their_line.text);
+                let timing = pick(&(original.start, original.end),
+                                  &(our_line.start, our_line.end),
+                                  &(their_line.start, their_line.end));
+                let comment = pick(&original.comment, &our_line.comment, &their_line.comment);
+                let settings = pick(&original.settings, &our_line.settings, &their_line.settings);
+                let words = pick(&original.words, &our_line.words, &their_line.words);
+                let id = pick(&original.id, &our_line.id, &their_line.id);
+
+                match (text, timing, comment, settings, words, id) {
+                    (Some(text), Some((start, end)), Some(comment), Some(settings), Some(words), Some(id)) => {
+                        merged.inner.push(SubLine {
+                            index: original.index,
+                            id,
+                            start,
+                            end,
+                            text,
+                            comment,
+                            settings,
+                            words,
+                        })
+                    }
+                    _ => {
+                        conflicts.push(Conflict::Edit {
+                            base: original.clone(),
+                            ours: our_line.clone(),
+                            theirs: their_line.clone(),
+                        })
+                    }
+                }
+            }
+        }
+    }
+
+    if !conflicts.is_empty() {
+        return Err(conflicts);
+    }
+    merged.rebuild();
+    Ok(merged)
+}
+
+#[cfg(test)]
+mod merge_tests {
+    use super::*;
+    use crate::timestamp::Timestamp;
+    use crate::utils;
+
+    fn texts(subs: &Subtitles) -> Vec<&str> {
+        subs.inner.iter().map(|line| &*line.text).collect()
+    }
+
+    #[test]
+    fn clean() {
+        let base = utils::subs_from_texts(&["One", "Two", "Three", "Four"]);
+
+        let mut ours = base.clone();
+        ours.inner[1].text = "Second".to_owned();
+        ours.inner[3].comment = Some("Check".to_owned());
+        ours.inner.remove(2);
+        ours.renumber();
+
+        let mut theirs = base.clone();
+        theirs.inner[1].end = Timestamp::new(0, 0, 3, 500);
+        let inserted = SubLine::new(0, "Five".to_owned(), Timestamp::new(0, 0, 8, 0), Timestamp::new(0, 0, 9, 0));
+        theirs.inner.push(inserted);
+        theirs.renumber();
+
+        let merged = three_way(&base, &ours, &theirs).unwrap();
+        assert_eq!(texts(&merged), vec!["One", "Second", "Four", "Five"]);
+        assert_eq!(merged.inner[1].end, Timestamp::new(0, 0, 3, 500));
+        assert_eq!(merged.inner[2].comment, Some("Check".to_owned()));
+        assert_eq!(merged.inner.iter().map(|line| line.index).collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+
+        assert_eq!(three_way(&base, &ours, &ours).unwrap(), ours);
+        assert_eq!(three_way(&base, &base, &theirs).unwrap(), theirs);
+    }
+
+    #[test]
+    fn conflicts() {
+        let base = utils::subs_from_texts(&["One", "Two", "Three"]);
+
+        let mut ours = base.clone();
+        ours.inner[0].text = "First".to_owned();
+        ours.inner[2].text = "Third".to_owned();
+
+        let mut theirs = base.clone();
+        theirs.inner[0].text = "Uno".to_owned();
+        theirs.inner.remove(2);
+        theirs.metadata.title = Some("Title".to_owned());
+
+        let conflicts = three_way(&base, &ours, &theirs).unwrap_err();
+        assert_eq!(conflicts,
+                   vec![Conflict::Edit {
+                            base: base.inner[0].clone(),
+                            ours: ours.inner[0].clone(),
+                            theirs: theirs.inner[0].clone(),
+                        },
+                        Conflict::RemovedEdited {
+                            base: base.inner[2].clone(),
+                            edited: ours.inner[2].clone(),
+                            ours: true,
+                        }]);
+    }
+
+    #[test]
+    fn removed_next_to_edited() {
+        let base = utils::subs_from_texts(&["One", "Two", "Three"]);
+
+        let mut ours = base.clone();
+        ours.inner.remove(1);
+        ours.inner[1].text = "Third".to_owned();
+        ours.renumber();
+
+        let mut theirs = base.clone();
+        theirs.inner[1].comment = Some("Check".to_owned());
+        theirs.inner[2].end = Timestamp::new(0, 0, 5, 500);
+
+        let conflicts = three_way(&base, &ours, &theirs).unwrap_err();
+        assert_eq!(conflicts,
+                   vec![Conflict::RemovedEdited {
+                            base: base.inner[1].clone(),
+                            edited: theirs.inner[1].clone(),
+                            ours: false,
+                        }]);
+
+        theirs.inner[1].comment = None;
+        let merged = three_way(&base, &ours, &theirs).unwrap();
+        assert_eq!(texts(&merged), vec!["One", "Third"]);
+        assert_eq!(merged.inner[1].end, Timestamp::new(0, 0, 5, 500));
+        assert_eq!(merged.inner[1].comment, None);
+    }
+}
@@ -0,0 +1,215 @@
+use std::str::FromStr;
+use std::io::{Error, ErrorKind};
+
+use crate::timestamp::Timestamp;
+use crate::language::LanguageTag;
+
+/// Frame rate as a rational number, e.g. ```24000/1001``` for NTSC film.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FrameRate {
+    pub numerator: u32,
+    pub denominator: u32,
+}
+
+impl FrameRate {
+    /// Constructs a new ```FrameRate```.
+    ///
+    /// # Panics
+    ///
+    /// Panics if ```denominator``` is zero.
+    pub fn new(numerator: u32, denominator: u32) -> FrameRate {
+        if denominator == 0 {
+            panic!("frame rate denominator can't be zero");
+        }
+        FrameRate {
+            numerator,
+            denominator,
+        }
+    }
+
+    /// Frames per second.
+    pub fn as_f64(&self) -> f64 {
+        self.numerator as f64 / self.denominator as f64
+    }
+
+    /// Returns ```true``` for NTSC rates (```30000/1001```, ```60000/1001```),
+    /// which use drop-frame timecodes.
+    pub fn is_drop_frame(&self) -> bool {
+        self.denominator == 1001 && self.numerator.is_multiple_of(30000)
+    }
+
+    /// Formats the time as ```HH:MM:SS:FF``` timecode, frames are counted
+    /// at the nominal (rounded up) rate.
+    /// Drop-frame timecodes of NTSC rates are written as ```HH:MM:SS;FF```
+    /// if ```drop_frame``` is ```true```.
+    pub fn timecode(&self, time: Timestamp, drop_frame: bool) -> String {
+        let nominal = self.numerator.div_ceil(self.denominator).max(1) as u64;
+        let mut frames = time.total_miliseconds() * self.numerator as u64 / (self.denominator as u64 * 1000);
+
+        let drop_frame = drop_frame && self.is_drop_frame();
+        if drop_frame {
+            // Frame numbers 0 and 1 (0-3 for 60 fps) are skipped every minute except every tenth one
+            let dropped = nominal / 15;
+            let per_minute = nominal * 60 - dropped;
+            let per_ten_minutes = nominal * 600 - dropped * 9;
+            let (tens, rest) = (frames / per_ten_minutes, frames % per_ten_minutes);
+            frames += dropped * 9 * tens;
+            if rest > dropped {
+                frames += dropped * ((rest - dropped) / per_minute);
+            }
+        }
+
+        let seconds = frames / nominal;
+        format!("{:02}:{:02}:{:02}{}{:02}",
+                seconds / 3600,
+                seconds / 60 % 60,
+                seconds % 60,
+                if drop_frame { ';' } else { ':' },
+                frames % nominal)
+    }
+}
+
+impl FromStr for FrameRate {
+    type Err = Error;
+    /// Parses frame rate written as a fraction (```"24000/1001"```),
+    /// an integer (```"25"```) or a decimal number (```"23.976"```).
+    fn from_str(s: &str) -> Result<FrameRate, Error> {
+        let invalid = || Error::new(ErrorKind::InvalidData, "Invalid frame rate");
+        let s = s.trim();
+
+        let (numerator, denominator) = if let Some(slash) = s.find('/') {
+            let numerator: u32 = s[..slash].trim().parse().map_err(|_| invalid())?;
+            let denominator: u32 = s[slash + 1..].trim().parse().map_err(|_| invalid())?;
+            (numerator, denominator)
+        } else if let Some(dot) = s.find('.') {
+            let fraction = &s[dot + 1..];
+            if fraction.is_empty() || fraction.len() > 6 {
+                return Err(invalid());
+            }
+            let digits = format!("{}{}", &s[..dot], fraction);
+            let numerator: u32 = digits.parse().map_err(|_| invalid())?;
+            (numerator, 10u32.pow(fraction.len() as u32))
+        } else {
+            (s.parse().map_err(|_| invalid())?, 1)
+        };
+
+        if numerator == 0 || denominator == 0 {
+            return Err(invalid());
+        }
+        Ok(FrameRate::new(numerator, denominator))
+    }
+}
+
+/// Track-level information, carried along with the ```Subtitles```.
+/// SubRip files have no headers, so it's filled only by formats that have them
+/// or by the user.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Metadata {
+    pub language: Option<LanguageTag>,
+    pub title: Option<String>,
+    /// Author, translator and other credits.
+    pub credits: Option<String>,
+    /// Name of the format the track was read from, e.g. ```"ass"```.
+    pub source_format: Option<String>,
+    /// Frame rate of the video the track was made for.
+    pub fps: Option<FrameRate>,
+}
+
+impl Metadata {
+    /// Returns ```true``` if no information is set.
+    pub fn is_empty(&self) -> bool {
+        *self == Metadata::default()
+    }
+
+    /// Fills the corresponding field from a header, such as ASS ```[Script Info]```
+    /// entry (```"Title"```, ```"Original Translation"```, ...) or TTML
+    /// attribute (```"xml:lang"```, ```"ttp:frameRate"```, ...).
+    /// Keys are case-insensitive, credits are accumulated.
+    ///
+    /// Returns ```false``` if the header is unknown or its value is invalid.
+    pub fn set_header(&mut self, key: &str, value: &str) -> bool {
+        let value = value.trim();
+        if value.is_empty() {
+            return false;
+        }
+
+        match &*key.trim().to_lowercase() {
+            "title" | "ttm:title" => self.title = Some(value.to_owned()),
+            "language" | "lang" | "xml:lang" => {
+                match LanguageTag::from_str(value) {
+                    Ok(language) => self.language = Some(language),
+                    Err(_) => return false,
+                }
+            }
+            "original script" | "original translation" | "original editing" | "original timing" |
+            "script updated by" | "ttm:copyright" | "ttm:agent" => {
+                let credit = format!("{}: {}", key.trim(), value);
+                self.credits = Some(match self.credits.take() {
+                    Some(credits) => format!("{}\n{}", credits, credit),
+                    None => credit,
+                });
+            }
+            "fps" | "framerate" | "ttp:framerate" => {
+                match FrameRate::from_str(value) {
+                    Ok(fps) => self.fps = Some(fps),
+                    Err(_) => return false,
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod metadata_tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn frame_rate() {
+        assert_eq!(FrameRate::from_str("24000/1001").unwrap(), FrameRate::new(24000, 1001));
+        assert_eq!(FrameRate::from_str("25").unwrap(), FrameRate::new(25, 1));
+        assert_eq!(FrameRate::from_str("23.976").unwrap(), FrameRate::new(23976, 1000));
+        assert!(FrameRate::from_str("0").is_err());
+        assert!(FrameRate::from_str("25/0").is_err());
+        assert!(FrameRate::from_str("fast").is_err());
+        assert!((FrameRate::new(30000, 1001).as_f64() - 29.97).abs() < 0.001);
+    }
+
+    #[test]
+    fn timecode() {
+        let time = Timestamp::new(0, 10, 0, 500);
+        assert_eq!(FrameRate::new(25, 1).timecode(time, true), "00:10:00:12");
+
+        let ntsc = FrameRate::new(30000, 1001);
+        assert!(ntsc.is_drop_frame() && !FrameRate::new(24000, 1001).is_drop_frame());
+        assert_eq!(ntsc.timecode(time, false), "00:09:59:27");
+        assert_eq!(ntsc.timecode(time, true), "00:10:00;15");
+        assert_eq!(ntsc.timecode(Timestamp::new(0, 1, 0, 0), true), "00:00:59;28");
+        assert_eq!(ntsc.timecode(Timestamp::new(0, 1, 0, 60), true), "00:01:00;02");
+        assert_eq!(ntsc.timecode(Timestamp::new(1, 0, 0, 0), true), "01:00:00;00");
+    }
+
+    #[test]
+    fn set_header() {
+        let mut metadata = Metadata::default();
+        assert!(metadata.is_empty());
+
+        assert!(metadata.set_header("Title", "Episode 1"));
+        assert!(metadata.set_header("Original Translation", "obj"));
+        assert!(metadata.set_header("Original Timing", "someone"));
+        assert!(metadata.set_header("xml:lang", "en-US"));
+        assert!(metadata.set_header("ttp:frameRate", "25"));
+        assert!(!metadata.set_header("ScaledBorderAndShadow", "yes"));
+        assert!(!metadata.set_header("xml:lang", "english"));
+
+        assert_eq!(metadata.title.as_ref().unwrap(), "Episode 1");
+        assert_eq!(metadata.credits.as_ref().unwrap(),
+                   "Original Translation: obj\nOriginal Timing: someone");
+        assert_eq!(metadata.language.as_ref().unwrap().as_str(), "en");
+        assert_eq!(metadata.fps, Some(FrameRate::new(25, 1)));
+    }
+}
@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+
+use crate::subtitles::Subtitles;
+use crate::compare;
+use crate::utils;
+
+/// Options for ```Subtitles::name_variants```.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NameCheckOptions {
+    /// Shorter capitalized words are ignored.
+    pub min_length: usize,
+    /// Names are variants of each other if their edit distance is at most
+    /// this part of the longer name's length, e.g. 1 edit for 4 letters at 0.25.
+    pub max_ratio: f64,
+}
+
+impl Default for NameCheckOptions {
+    fn default() -> NameCheckOptions {
+        NameCheckOptions {
+            min_length: 3,
+            max_ratio: 0.25,
+        }
+    }
+}
+
+/// Spelling of a name and indices of lines where it's used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NameSpelling {
+    pub name: String,
+    /// Number of times the name is used.
+    pub count: usize,
+    pub lines: Vec<u32>,
+}
+
+impl Subtitles {
+    /// Finds names spelled in several similar ways, e.g. "Jon" and "John".
+    /// Names are capitalized words which occur in the middle of a sentence at least once,
+    /// so ordinary words capitalized at the start of sentences aren't taken for names.
+    /// Similar names are grouped together even if they are only similar through another one.
+    ///
+    /// Returns groups of two or more spellings, the most used spelling first,
+    /// in the order of the first line using them.
+    pub fn name_variants(&self, options: &NameCheckOptions) -> Vec<Vec<NameSpelling>> {
+        let mut names: Vec<(NameSpelling, bool)> = Vec::new();
+        let mut positions: HashMap<String, usize> = HashMap::new();
+        for line in &self.inner {
+            for (name, sentence_start) in capitalized(&utils::strip_tags(&line.text), options.min_length) {
+                let position = *positions.entry(name.to_owned()).or_insert_with(|| {
+                    names.push((NameSpelling { name: name.to_owned(), count: 0, lines: Vec::new() }, false));
+                    names.len() - 1
+                });
+                let (spelling, mid_sentence) = &mut names[position];
+                spelling.count += 1;
+                if spelling.lines.last() != Some(&line.index) {
+                    spelling.lines.push(line.index);
+                }
+                *mid_sentence |= !sentence_start;
+            }
+        }
+        let names: Vec<NameSpelling> = names.into_iter()
+            .filter(|&(_, mid_sentence)| mid_sentence)
+            .map(|(name, _)| name)
+            .collect();
+
+        // Single-linkage clustering, every name points to a member of its group
+        let lowercase: Vec<Vec<char>> = names.iter()
+            .map(|name| name.name.to_lowercase().chars().collect())
+            .collect();
+        let mut group: Vec<usize> = (0..names.len()).collect();
+        for i in 0..names.len() {
+            for j in i + 1..names.len() {
+                let longer = lowercase[i].len().max(lowercase[j].len());
+                let distance = compare::edit_distance(&lowercase[i], &lowercase[j]);
+                if distance as f64 <= options.max_ratio * longer as f64 {
+                    let (a, b) = (root(&group, i), root(&group, j));
+                    group[a.max(b)] = a.min(b);
+                }
+            }
+        }
+
+        let mut groups: Vec<Vec<NameSpelling>> = Vec::new();
+        let mut group_positions: HashMap<usize, usize> = HashMap::new();
+        for (i, name) in names.into_iter().enumerate() {
+            let position = *group_positions.entry(root(&group, i)).or_insert_with(|| {
+                groups.push(Vec::new());
+                groups.len() - 1
+            });
+            groups[position].push(name);
+        }
+        groups.retain(|spellings| spellings.len() > 1);
+        for spellings in &mut groups {
+            spellings.sort_by_key(|spelling| ::std::cmp::Reverse(spelling.count));
+        }
+        groups
+    }
+}
+
+fn root(group: &[usize], mut i: usize) -> usize {
+    while group[i] != i {
+        i = group[i];
+    }
+    i
+}
+
+/// Capitalized words of at least ```min_length``` letters, which aren't all uppercase,
+/// paired with ```true``` if they start a sentence.
+fn capitalized(text: &str, min_length: usize) -> Vec<(&str, bool)> {
+    let mut words = Vec::new();
+    let mut sentence_start = true;
+    let mut row_start = true;
+    let mut word_start = None;
+    for (i, c) in text.char_indices().chain(Some((text.len(), ' '))) {
+        if c.is_alphabetic() {
+            word_start = word_start.or(Some(i));
+            continue;
+        }
+        if let Some(start) = word_start.take() {
+            let word = &text[start..i];
+            let mut chars = word.chars();
+            let capitalized = chars.next().is_some_and(char::is_uppercase) && chars.any(char::is_lowercase);
+            if capitalized && word.chars().count() >= min_length {
+                words.push((word, sentence_start));
+            }
+            sentence_start = false;
+            row_start = false;
+        }
+        match c {
+            '.' | '!' | '?' | '…' | ':' | '♪' => sentence_start = true,
+            '-' if row_start => sentence_start = true,
+            '\n' => row_start = true,
+            _ => {}
+        }
+    }
+    words
+}
+
+#[cfg(test)]
+mod names_tests {
+    use super::*;
+    use crate::utils;
+
+    #[test]
+    fn name_variants() {
+        let subs = utils::subs_from_texts(&["Where is Jon?",
+                                            "- Ask John.\r\n- John knows, Daenerys too.",
+                                            "Where is Danaerys? Ask Tom.",
+                                            "Tim went home with JOHN and Daenerys."]);
+        let variants = subs.name_variants(&NameCheckOptions::default());
+        let variants: Vec<Vec<(&str, Vec<u32>)>> = variants.iter()
+            .map(|spellings| spellings.iter().map(|spelling| (&*spelling.name, spelling.lines.clone())).collect())
+            .collect();
+        assert_eq!(variants,
+                   vec![vec![("John", vec![2]), ("Jon", vec![1])],
+                        vec![("Daenerys", vec![2, 4]), ("Danaerys", vec![3])]]);
+    }
+
+    #[test]
+    fn _capitalized() {
+        assert_eq!(capitalized("Where is Jon?\r\nHe and I left. - Ned... Wait!\n- Hodor", 3),
+                   vec![("Where", true), ("Jon", false), ("Ned", true), ("Wait", true), ("Hodor", true)]);
+    }
+}
@@ -0,0 +1,33 @@
+use rayon::prelude::*;
+
+use crate::subtitles::Subtitles;
+
+impl Subtitles {
+    /// Applies ```f``` to every line's text in parallel, for expensive
+    /// per-line processing of large tracks. Only texts are changed,
+    /// so the order and timing of lines are kept.
+    pub fn par_map_text<F>(&mut self, f: F)
+        where F: Fn(&mut String) + Sync + Send
+    {
+        self.inner.par_iter_mut().for_each(|line| f(&mut line.text));
+    }
+}
+
+#[cfg(test)]
+mod parallel_tests {
+    use crate::utils;
+
+    #[test]
+    fn par_map_text() {
+        let texts: Vec<String> = (0..1000).map(|i| format!("<i>Line {}</i>", i)).collect();
+        let texts: Vec<&str> = texts.iter().map(|text| &**text).collect();
+        let mut subs = utils::subs_from_texts(&texts);
+        let original = subs.clone();
+
+        subs.par_map_text(|text| *text = utils::strip_tags(text).to_uppercase());
+        for (line, original) in subs.inner.iter().zip(&original.inner) {
+            assert_eq!(line.text, utils::strip_tags(&original.text).to_uppercase());
+            assert_eq!((line.index, line.start, line.end), (original.index, original.start, original.end));
+        }
+    }
+}
@@ -0,0 +1,406 @@
+use std::ops::Range;
+use std::path::Path;
+use std::fs;
+use std::io::{Error, ErrorKind};
+
+use crate::timestamp::Timestamp;
+use crate::subline::SubLine;
+use crate::subtitles::Subtitles;
+use crate::cue_settings::CueSettings;
+use crate::utils;
+
+/// What to do with indices from the file, which often start at 0,
+/// skip numbers or repeat. Original indices are always kept in ```Parsed::original_indices```.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Renumber {
+    /// Lines keep indices from the file, so ```Subtitles::by_index``` may fail.
+    #[default]
+    KeepOriginal,
+    /// Lines are numbered consecutively from the given number.
+    RenumberFrom(u32),
+    /// Indices from the file must go consecutively from 1, otherwise it's an error.
+    ErrorOnGap,
+}
+
+/// What to do with lines ending before they start, they are reported in ```Parsed::warnings``` anyway.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DurationPolicy {
+    /// Lines are kept as they are, so ```SubLine::duration``` panics for them.
+    #[default]
+    Keep,
+    /// Start and end are swapped.
+    Swap,
+    /// End is moved to the start, so the line lasts no time.
+    Clamp,
+}
+
+/// Options of ```Subtitles::parse```.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ParseOptions {
+    /// Skip blocks which can't be parsed instead of returning an error,
+    /// they are kept in ```Parsed::skipped```.
+    pub lenient: bool,
+    pub renumber: Renumber,
+    pub durations: DurationPolicy,
+    /// Replace html entities (```&amp;```, ```&#8217;```) in texts with characters.
+    pub decode_entities: bool,
+    /// Text rows starting with this marker (e.g. ```"#"```) are moved to ```SubLine::comment```.
+    pub comment_marker: Option<String>,
+    /// Maximal number of blocks, including skipped ones, for untrusted input.
+    pub max_cues: Option<usize>,
+    /// Maximal length of a block in bytes, for untrusted input.
+    pub max_text_length: Option<usize>,
+    /// Maximal size of the content in bytes, for untrusted input.
+    pub max_size: Option<usize>,
+}
+
+impl ParseOptions {
+    /// Options with ```lenient``` set.
+    pub fn lenient() -> ParseOptions {
+        ParseOptions { lenient: true, ..ParseOptions::default() }
+    }
+}
+
+/// Block of the input which couldn't be parsed as a line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawBlock {
+    /// Byte range of the block in the parsed content.
+    pub range: Range<usize>,
+    pub text: String,
+}
+
+/// Suspicious but parsed line, ```line_number``` is the line of the content where its block starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseWarning {
+    /// Line starts and ends at the same time.
+    ZeroDuration { line_number: usize, index: u32 },
+    /// Line ends before it starts, see ```DurationPolicy```.
+    NegativeDuration { line_number: usize, index: u32 },
+}
+
+/// Result of ```Subtitles::parse```.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Parsed {
+    pub subtitles: Subtitles,
+    /// Blocks skipped in lenient mode, in the order of appearance.
+    pub skipped: Vec<RawBlock>,
+    /// Indices of the parsed lines as they were in the file.
+    pub original_indices: Vec<u32>,
+    /// Anomalies of parsed lines, in the order of appearance.
+    pub warnings: Vec<ParseWarning>,
+}
+
+impl Subtitles {
+    /// Parses SubRip content with any newline style, blocks are separated by empty lines.
+    ///
+    /// In strict mode the first malformed block is an error, in lenient mode
+    /// malformed blocks are skipped and returned along with the parsed lines.
+    /// Lines lasting no time or ending before they start are reported as warnings
+    /// and handled according to ```ParseOptions::durations```.
+    /// Exceeding any of the limits is an ```InvalidInput``` error, checked
+    /// before the content is parsed further. Never panics.
+    pub fn parse(content: &str, options: &ParseOptions) -> Result<Parsed, Error> {
+        let too_big = |message: String| Err(Error::new(ErrorKind::InvalidInput, message));
+        if let Some(max_size) = options.max_size.filter(|&max_size| content.len() > max_size) {
+            return too_big(format!("Content is larger than {} bytes", max_size));
+        }
+        let mut parsed = Parsed::default();
+        // Line number of the current block, counted incrementally
+        let mut line_number = 1;
+        let mut counted = 0;
+
+        for (number, range) in blocks(content).into_iter().enumerate() {
+            line_number += content[counted..range.start].matches('\n').count();
+            counted = range.start;
+            if let Some(max_cues) = options.max_cues.filter(|&max_cues| number >= max_cues) {
+                return too_big(format!("Content has more than {} blocks", max_cues));
+            }
+            if let Some(max_text_length) = options.max_text_length.filter(|&max_length| range.len() > max_length) {
+                return too_big(format!("Block at line {} is longer than {} bytes", line_number, max_text_length));
+            }
+            let block = &content[range.clone()];
+            match parse_block(block, options.comment_marker.as_deref()) {
+                Some(mut line) => {
+                    if line.start == line.end {
+                        parsed.warnings.push(ParseWarning::ZeroDuration { line_number, index: line.index });
+                    } else if line.start > line.end {
+                        parsed.warnings.push(ParseWarning::NegativeDuration { line_number, index: line.index });
+                        match options.durations {
+                            DurationPolicy::Keep => {}
+                            DurationPolicy::Swap => ::std::mem::swap(&mut line.start, &mut line.end),
+                            DurationPolicy::Clamp => line.end = line.start,
+                        }
+                    }
+                    parsed.subtitles.inner.push(line)
+                }
+                None if options.lenient => {
+                    parsed.skipped.push(RawBlock {
+                        range,
+                        text: block.to_owned(),
+                    })
+                }
+                None => {
+                    return Err(Error::new(ErrorKind::InvalidData,
+                                          format!("Invalid subtitle block at line {}", line_number)));
+                }
+            }
+        }
+
+        if options.decode_entities {
+            parsed.subtitles.decode_entities();
+        }
+
+        parsed.original_indices = parsed.subtitles.inner.iter().map(|line| line.index).collect();
+        match options.renumber {
+            Renumber::KeepOriginal => {}
+            Renumber::RenumberFrom(first) => {
+                for (i, line) in parsed.subtitles.inner.iter_mut().enumerate() {
+                    line.index = first.saturating_add(i as u32);
+                }
+            }
+            Renumber::ErrorOnGap => {
+                let gap = parsed.original_indices.iter().enumerate().find(|&(i, &index)| index as usize != i + 1);
+                if let Some((i, &index)) = gap {
+                    return Err(Error::new(ErrorKind::InvalidData,
+                                          format!("Line {} has index {}, {} expected", i + 1, index, i + 1)));
+                }
+            }
+        }
+        Ok(parsed)
+    }
+
+    /// Reads and parses given file, see ```Subtitles::parse```.
+    /// Files larger than ```ParseOptions::max_size``` aren't read.
+    pub fn from_file_with<P: AsRef<Path>>(path: P, options: &ParseOptions) -> Result<Parsed, Error> {
+        if let Some(max_size) = options.max_size {
+            if fs::metadata(&path)?.len() > max_size as u64 {
+                return Err(Error::new(ErrorKind::InvalidInput, format!("File is larger than {} bytes", max_size)));
+            }
+        }
+        let content = utils::read_file(&path)?;
+        Subtitles::parse(&content, options)
+    }
+}
+
+/// Byte ranges of blocks of non-blank lines, without trailing newlines.
+fn blocks(content: &str) -> Vec<Range<usize>> {
+    let mut result = Vec::new();
+    let mut block: Option<Range<usize>> = None;
+    let mut offset = 0;
+
+    for line in content.split('\n') {
+        let end = offset + line.trim_end_matches('\r').len();
+        if line.trim().is_empty() {
+            result.extend(block.take());
+        } else {
+            let start = block.take().map(|block| block.start).unwrap_or(offset);
+            block = Some(start..end);
+        }
+        offset += line.len() + 1;
+    }
+    result.extend(block);
+    result
+}
+
+/// Lines of ```content``` where blocks parsed as lines by ```Subtitles::parse``` start.
+pub(crate) fn line_numbers(content: &str) -> Vec<usize> {
+    let mut line_number = 1;
+    let mut counted = 0;
+    let mut result = Vec::new();
+    for range in blocks(content) {
+        line_number += content[counted..range.start].matches('\n').count();
+        counted = range.start;
+        if parse_block(&content[range], None).is_some() {
+            result.push(line_number);
+        }
+    }
+    result
+}
+
+fn parse_block(block: &str, comment_marker: Option<&str>) -> Option<SubLine> {
+    let mut lines = block.lines().map(|line| line.trim_end_matches('\r'));
+
+    let index = match lines.next().map(|line| line.trim_start_matches('\u{feff}').trim().parse()) {
+        Some(Ok(index)) => index,
+        _ => return None,
+    };
+    let (start, end) = lines.next().and_then(parse_timing)?;
+
+    let mut text = Vec::new();
+    let mut comment = Vec::new();
+    for line in lines {
+        match comment_marker.filter(|marker| !marker.is_empty()).and_then(|marker| line.strip_prefix(marker)) {
+            Some(note) => comment.push(note.trim()),
+            None => text.push(line),
+        }
+    }
+
+    Some(SubLine {
+        index,
+        id: None,
+        start,
+        end,
+        text: text.join("\r\n"),
+        comment: if comment.is_empty() { None } else { Some(comment.join("\n")) },
+        settings: CueSettings::default(),
+        words: Vec::new(),
+    })
+}
+
+pub(crate) fn parse_timing(line: &str) -> Option<(Timestamp, Timestamp)> {
+    let (start, rest) = utils::split_timestamp(line.trim_start(), ',')?;
+    let arrow = rest.trim_start();
+    let after = arrow.strip_prefix("-->")?.trim_start();
+    if arrow.len() == rest.len() || after.len() + 3 == arrow.len() {
+        return None;
+    }
+    let (end, rest) = utils::split_timestamp(after, ',')?;
+    if !rest.trim().is_empty() {
+        return None;
+    }
+
+    let mut numbers = [0u32; 8];
+    for (number, part) in numbers.iter_mut().zip(start.iter().chain(end.iter())) {
+        *number = part.parse().ok()?;
+    }
+    // Minutes are at most 99, so normalizing them adds an hour at most
+    if numbers[0] == u32::MAX || numbers[4] == u32::MAX {
+        return None;
+    }
+    Some((Timestamp::new(numbers[0], numbers[1], numbers[2], numbers[3]),
+          Timestamp::new(numbers[4], numbers[5], numbers[6], numbers[7])))
+}
+
+#[cfg(test)]
+mod parse_tests {
+    use super::*;
+    use std::str::FromStr;
+
+    static PATH: &str = "example.srt";
+
+    #[test]
+    fn parse() {
+        let parsed = Subtitles::from_file_with(PATH, &ParseOptions::default()).unwrap();
+        assert!(parsed.skipped.is_empty());
+        assert_eq!(parsed.subtitles, Subtitles::from_file(PATH).unwrap());
+
+        let content = utils::read_file(PATH).unwrap().replace("\r\n", "\n");
+        assert_eq!(Subtitles::parse(&content, &ParseOptions::default()).unwrap(), parsed);
+    }
+
+    #[test]
+    fn lenient() {
+        let content = "1\n00:00:01,000 --> 00:00:02,000\nOne\n\n\
+                       2\n00:00:03,000 -> 00:00:04,000\nBroken\n\n\n\
+                       3\r\n00:00:05,000 --> 00:00:06,000\r\nThree\r\nlines\r\n";
+
+        let error = Subtitles::parse(content, &ParseOptions::default()).unwrap_err();
+        assert!(error.to_string().contains("line 5"));
+
+        let parsed = Subtitles::parse(content, &ParseOptions::lenient()).unwrap();
+        assert_eq!(parsed.subtitles.len(), 2);
+        assert_eq!(parsed.subtitles.inner[1].text, "Three\r\nlines");
+        assert_eq!(parsed.skipped.len(), 1);
+        let skipped = &parsed.skipped[0];
+        assert_eq!(skipped.text, "2\n00:00:03,000 -> 00:00:04,000\nBroken");
+        assert_eq!(&content[skipped.range.clone()], skipped.text);
+
+        let same = Subtitles::from_str(&utils::prepare(content)).unwrap();
+        assert_eq!(same.len(), 2);
+    }
+
+    #[test]
+    fn renumber() {
+        let content = "0\n00:00:01,000 --> 00:00:02,000\nOne\n\n\
+                       0\n00:00:03,000 --> 00:00:04,000\nTwo\n\n\
+                       5\n00:00:05,000 --> 00:00:06,000\nThree\n";
+        let indices = |parsed: &Parsed| parsed.subtitles.inner.iter().map(|line| line.index).collect::<Vec<_>>();
+
+        let kept = Subtitles::parse(content, &ParseOptions::default()).unwrap();
+        assert_eq!(indices(&kept), vec![0, 0, 5]);
+        assert_eq!(kept.original_indices, vec![0, 0, 5]);
+
+        let options = ParseOptions { renumber: Renumber::RenumberFrom(1), ..ParseOptions::default() };
+        let renumbered = Subtitles::parse(content, &options).unwrap();
+        assert_eq!(indices(&renumbered), vec![1, 2, 3]);
+        assert_eq!(renumbered.original_indices, vec![0, 0, 5]);
+        assert_eq!(renumbered.subtitles.by_index(3).unwrap().text, "Three");
+
+        let options = ParseOptions { renumber: Renumber::ErrorOnGap, ..ParseOptions::default() };
+        assert!(Subtitles::parse(content, &options).is_err());
+        assert!(Subtitles::from_file_with(PATH, &options).is_ok());
+    }
+
+    #[test]
+    fn decode_entities() {
+        let content = "1\n00:00:01,000 --> 00:00:02,000\nTom &amp; Jerry\n";
+        let options = ParseOptions { decode_entities: true, ..ParseOptions::default() };
+        assert_eq!(Subtitles::parse(content, &options).unwrap().subtitles.inner[0].text, "Tom & Jerry");
+        assert_eq!(Subtitles::parse(content, &ParseOptions::default()).unwrap().subtitles.inner[0].text,
+                   "Tom &amp; Jerry");
+    }
+
+    #[test]
+    fn comments() {
+        let content = "1\n00:00:01,000 --> 00:00:02,000\n# Check the pun\nText\n#  and the name\n\n\
+                       2\n00:00:03,000 --> 00:00:04,000\nNo comment\n";
+        let options = ParseOptions { comment_marker: Some("#".to_owned()), ..ParseOptions::default() };
+        let subs = Subtitles::parse(content, &options).unwrap().subtitles;
+        assert_eq!(subs.inner[0].text, "Text");
+        assert_eq!(subs.inner[0].comment.as_ref().unwrap(), "Check the pun\nand the name");
+        assert_eq!(subs.inner[1].comment, None);
+        assert!(!subs.to_string().contains("pun"));
+
+        let subs = Subtitles::parse(content, &ParseOptions::default()).unwrap().subtitles;
+        assert_eq!(subs.inner[0].text, "# Check the pun\r\nText\r\n#  and the name");
+    }
+
+    #[test]
+    fn durations() {
+        let content = "1\n00:00:01,000 --> 00:00:01,000\nZero\n\n\
+                       2\n00:00:04,000 --> 00:00:03,000\nNegative\n\n\
+                       3\n00:00:05,000 --> 00:00:06,000\nFine\n";
+        let parse = |durations| Subtitles::parse(content, &ParseOptions { durations, ..ParseOptions::default() });
+        let timing = |parsed: &Parsed| {
+            let line = &parsed.subtitles.inner[1];
+            (line.start.total_miliseconds(), line.end.total_miliseconds())
+        };
+
+        let kept = parse(DurationPolicy::Keep).unwrap();
+        assert_eq!(kept.warnings,
+                   vec![ParseWarning::ZeroDuration { line_number: 1, index: 1 },
+                        ParseWarning::NegativeDuration { line_number: 5, index: 2 }]);
+        assert_eq!(timing(&kept), (4000, 3000));
+
+        let swapped = parse(DurationPolicy::Swap).unwrap();
+        assert_eq!(swapped.warnings, kept.warnings);
+        assert_eq!(timing(&swapped), (3000, 4000));
+        assert_eq!(swapped.subtitles.inner[1].duration().total_miliseconds(), 1000);
+        assert_eq!(timing(&parse(DurationPolicy::Clamp).unwrap()), (4000, 4000));
+    }
+
+    #[test]
+    fn limits() {
+        let content = "1\n00:00:01,000 --> 00:00:02,000\nOne\n\n\
+                       broken\n\n\
+                       3\n00:00:05,000 --> 00:00:06,000\nThree, the longest\n";
+        let parse = |options: ParseOptions| Subtitles::parse(content, &ParseOptions { lenient: true, ..options });
+        let options = ParseOptions {
+            max_cues: Some(3),
+            max_text_length: Some(50),
+            max_size: Some(200),
+            ..ParseOptions::default()
+        };
+        assert!(parse(options).is_ok());
+
+        let error = parse(ParseOptions { max_cues: Some(2), ..ParseOptions::default() }).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidInput);
+        assert_eq!(error.to_string(), "Content has more than 2 blocks");
+        let error = parse(ParseOptions { max_text_length: Some(40), ..ParseOptions::default() }).unwrap_err();
+        assert_eq!(error.to_string(), "Block at line 7 is longer than 40 bytes");
+        assert!(parse(ParseOptions { max_size: Some(50), ..ParseOptions::default() }).is_err());
+
+        let options = ParseOptions { max_size: Some(1000), ..ParseOptions::default() };
+        assert_eq!(Subtitles::from_file_with(PATH, &options).unwrap_err().kind(), ErrorKind::InvalidInput);
+    }
+}
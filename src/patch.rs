@@ -0,0 +1,485 @@
+use std::io::{Error, ErrorKind};
+
+use crate::timestamp::Timestamp;
+use crate::subline::{SubLine, Word};
+use crate::cue_settings::CueSettings;
+use crate::subtitles::Subtitles;
+
+/// Single line-level edit. Positions are indices in the inner vector
+/// at the moment the operation is applied, so operations must be applied in order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PatchOperation {
+    Insert { position: usize, line: SubLine },
+    Remove { position: usize, line: SubLine },
+    SetText {
+        position: usize,
+        old: String,
+        new: String,
+    },
+    SetTiming {
+        position: usize,
+        old: (Timestamp, Timestamp),
+        new: (Timestamp, Timestamp),
+    },
+    SetId {
+        position: usize,
+        old: Option<String>,
+        new: Option<String>,
+    },
+    SetComment {
+        position: usize,
+        old: Option<String>,
+        new: Option<String>,
+    },
+    SetSettings {
+        position: usize,
+        old: CueSettings,
+        new: CueSettings,
+    },
+    SetWords {
+        position: usize,
+        old: Vec<Word>,
+        new: Vec<Word>,
+    },
+}
+
+/// List of line-level edits between two versions of ```Subtitles```,
+/// see ```Subtitles::diff``` and ```Subtitles::apply_patch```.
+///
+/// Operations keep the replaced values, so a patch is only applied
+/// to the same version it was made from.
+/// Lines' indices are not a part of the patch, lines are renumbered on apply.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SubtitlePatch {
+    pub operations: Vec<PatchOperation>,
+}
+
+impl SubtitlePatch {
+    /// Returns ```true``` if the patch changes nothing.
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+}
+
+impl Subtitles {
+    /// Makes a patch which turns ```self``` into ```other```, applying it to ```self```
+    /// gives lines equal to ```other```'s except for their indices.
+    ///
+    /// Lines are matched by start, end and text in linear memory,
+    /// other changes of matched lines become ```Set*``` operations.
+    pub fn diff(&self, other: &Subtitles) -> SubtitlePatch {
+        let old = &self.inner;
+        let new = &other.inner;
+
+        let prefix = old.iter().zip(new).take_while(|&(a, b)| same(a, b)).count();
+        let suffix = old[prefix..]
+            .iter()
+            .rev()
+            .zip(new[prefix..].iter().rev())
+            .take_while(|&(a, b)| same(a, b))
+            .count();
+        let old_middle = &old[prefix..old.len() - suffix];
+        let new_middle = &new[prefix..new.len() - suffix];
+
+        let mut operations = Vec::new();
+        for (position, (a, b)) in old.iter().zip(new).take(prefix).enumerate() {
+            set_details(&mut operations, position, a, b);
+        }
+
+        // Longest common subsequence of the changed middle parts
+        let mut common = Vec::new();
+        common_lines(old_middle, new_middle, 0, 0, &mut common);
+        common.push((old_middle.len(), new_middle.len()));
+
+        let mut position = prefix;
+        let (mut i, mut j) = (0, 0);
+        for (next_i, next_j) in common {
+            let removed: Vec<&SubLine> = old_middle[i..next_i].iter().collect();
+            let inserted: Vec<&SubLine> = new_middle[j..next_j].iter().collect();
+            position = flush(&mut operations, position, &removed, &inserted);
+            if next_i == old_middle.len() {
+                break;
+            }
+            set_details(&mut operations, position, &old_middle[next_i], &new_middle[next_j]);
+            position += 1;
+            i = next_i + 1;
+            j = next_j + 1;
+        }
+
+        let old_suffix = &old[old.len() - suffix..];
+        let new_suffix = &new[new.len() - suffix..];
+        for (offset, (a, b)) in old_suffix.iter().zip(new_suffix).enumerate() {
+            set_details(&mut operations, position + offset, a, b);
+        }
+
+        SubtitlePatch { operations }
+    }
+
+    /// Runs ```operation``` on a copy of the subtitles, returning the patch it would make
+    /// along with the operation's result. Nothing is changed, so e.g. an editor can show
+    /// the changes of ```clamp_to``` or ```Pipeline::run``` and apply the patch if accepted.
+    pub fn preview<T, F>(&self, operation: F) -> (SubtitlePatch, T)
+        where F: FnOnce(&mut Subtitles) -> T
+    {
+        let mut changed = self.clone();
+        let result = operation(&mut changed);
+        (self.diff(&changed), result)
+    }
+
+    /// Applies the patch made by ```Subtitles::diff```. Lines are renumbered after that.
+    ///
+    /// Nothing is changed if the patch doesn't match the current lines.
+    pub fn apply_patch(&mut self, patch: &SubtitlePatch) -> Result<(), Error> {
+        let mut lines = self.inner.clone();
+
+        for (number, operation) in patch.operations.iter().enumerate() {
+            let conflict = || {
+                Error::new(ErrorKind::InvalidData,
+                           format!("Patch operation {} doesn't match the subtitles", number + 1))
+            };
+
+            match *operation {
+                PatchOperation::Insert { position, ref line } => {
+                    if position > lines.len() {
+                        return Err(conflict());
+                    }
+                    lines.insert(position, line.clone());
+                }
+                PatchOperation::Remove { position, ref line } => {
+                    if lines.get(position).map(|current| !same(current, line)).unwrap_or(true) {
+                        return Err(conflict());
+                    }
+                    lines.remove(position);
+                }
+                PatchOperation::SetText { position, ref old, ref new } => {
+                    match lines.get_mut(position) {
+                        Some(ref mut current) if current.text == *old => current.text = new.clone(),
+                        _ => return Err(conflict()),
+                    }
+                }
+                PatchOperation::SetTiming { position, old, new } => {
+                    match lines.get_mut(position) {
+                        Some(ref mut current) if (current.start, current.end) == old => {
+                            current.start = new.0;
+                            current.end = new.1;
+                        }
+                        _ => return Err(conflict()),
+                    }
+                }
+                PatchOperation::SetId { position, ref old, ref new } => {
+                    match lines.get_mut(position) {
+                        Some(ref mut current) if current.id == *old => current.id = new.clone(),
+                        _ => return Err(conflict()),
+                    }
+                }
+                PatchOperation::SetComment { position, ref old, ref new } => {
+                    match lines.get_mut(position) {
+                        Some(ref mut current) if current.comment == *old => current.comment = new.clone(),
+                        _ => return Err(conflict()),
+                    }
+                }
+                PatchOperation::SetSettings { position, ref old, ref new } => {
+                    match lines.get_mut(position) {
+                        Some(ref mut current) if current.settings == *old => current.settings = new.clone(),
+                        _ => return Err(conflict()),
+                    }
+                }
+                PatchOperation::SetWords { position, ref old, ref new } => {
+                    match lines.get_mut(position) {
+                        Some(ref mut current) if current.words == *old => current.words = new.clone(),
+                        _ => return Err(conflict()),
+                    }
+                }
+            }
+        }
+
+        self.inner = lines;
+        self.renumber();
+        Ok(())
+    }
+}
+
+/// Lines are equal regardless of their indices.
+fn same(a: &SubLine, b: &SubLine) -> bool {
+    a.start == b.start && a.end == b.end && a.text == b.text
+}
+
+/// Finds the longest common subsequence of lines with Hirschberg's algorithm,
+/// pushing positions of its lines in ```old``` and ```new``` (shifted by offsets) in order.
+/// Uses memory linear in the number of lines.
+fn common_lines(old: &[SubLine],
+                new: &[SubLine],
+                old_offset: usize,
+                new_offset: usize,
+                common: &mut Vec<(usize, usize)>) {
+    if old.is_empty() || new.is_empty() {
+        return;
+    }
+    if old.len() == 1 {
+        if let Some(j) = new.iter().position(|line| same(&old[0], line)) {
+            common.push((old_offset, new_offset + j));
+        }
+        return;
+    }
+
+    let middle = old.len() / 2;
+    let forward = lcs_lengths(&old[..middle], new, false);
+    let backward = lcs_lengths(&old[middle..], new, true);
+    // Split of new where the common subsequences of both halves of old add up to the longest one
+    let split = (0..new.len() + 1)
+        .max_by_key(|&j| (forward[j] + backward[new.len() - j], ::std::cmp::Reverse(j)))
+        .unwrap_or(0);
+    common_lines(&old[..middle], &new[..split], old_offset, new_offset, common);
+    common_lines(&old[middle..], &new[split..], old_offset + middle, new_offset + split, common);
+}
+
+/// Lengths of the longest common subsequences of ```old``` and every prefix of ```new```,
+/// or of suffixes (by their length) if ```reversed```.
+fn lcs_lengths(old: &[SubLine], new: &[SubLine], reversed: bool) -> Vec<usize> {
+    let at = |lines: &[SubLine], i: usize| if reversed { lines.len() - 1 - i } else { i };
+    let mut previous = vec![0; new.len() + 1];
+    let mut current = vec![0; new.len() + 1];
+    for i in 0..old.len() {
+        let a = &old[at(old, i)];
+        for j in 0..new.len() {
+            current[j + 1] = if same(a, &new[at(new, j)]) {
+                previous[j] + 1
+            } else {
+                previous[j + 1].max(current[j])
+            };
+        }
+        ::std::mem::swap(&mut previous, &mut current);
+    }
+    previous
+}
+
+/// Adds operations changing id, comment, settings and words of ```old``` to those of ```new```.
+fn set_details(operations: &mut Vec<PatchOperation>, position: usize, old: &SubLine, new: &SubLine) {
+    if old.id != new.id {
+        operations.push(PatchOperation::SetId {
+            position,
+            old: old.id.clone(),
+            new: new.id.clone(),
+        });
+    }
+    if old.comment != new.comment {
+        operations.push(PatchOperation::SetComment {
+            position,
+            old: old.comment.clone(),
+            new: new.comment.clone(),
+        });
+    }
+    if old.settings != new.settings {
+        operations.push(PatchOperation::SetSettings {
+            position,
+            old: old.settings.clone(),
+            new: new.settings.clone(),
+        });
+    }
+    if old.words != new.words {
+        operations.push(PatchOperation::SetWords {
+            position,
+            old: old.words.clone(),
+            new: new.words.clone(),
+        });
+    }
+}
+
+/// Turns a run of removed and inserted lines into operations starting at ```position```,
+/// lines are paired up as modifications first: lines with the same id, start or text,
+/// and then the rest in order. Returns the position after the run.
+fn flush(operations: &mut Vec<PatchOperation>,
+         mut position: usize,
+         removed: &[&SubLine],
+         inserted: &[&SubLine])
+         -> usize {
+    let mut pairs = Vec::new();
+    let mut next = 0;
+    for (i, old) in removed.iter().enumerate() {
+        if let Some(offset) = inserted[next..].iter().position(|new| identical(old, new)) {
+            pairs.push((i, next + offset));
+            next += offset + 1;
+        }
+    }
+    pairs.push((removed.len(), inserted.len()));
+
+    let (mut i, mut j) = (0, 0);
+    for (next_i, next_j) in pairs {
+        position = pair_up(operations, position, &removed[i..next_i], &inserted[j..next_j]);
+        if next_i == removed.len() {
+            break;
+        }
+        modify(operations, position, removed[next_i], inserted[next_j]);
+        position += 1;
+        i = next_i + 1;
+        j = next_j + 1;
+    }
+    position
+}
+
+/// Whether ```new``` is likely ```old``` after editing.
+fn identical(old: &SubLine, new: &SubLine) -> bool {
+    (old.id.is_some() && old.id == new.id) || old.start == new.start || old.text == new.text
+}
+
+/// Pairs up removed and inserted lines in order, returns the position after them.
+fn pair_up(operations: &mut Vec<PatchOperation>,
+           mut position: usize,
+           removed: &[&SubLine],
+           inserted: &[&SubLine])
+           -> usize {
+    for (old, new) in removed.iter().zip(inserted) {
+        modify(operations, position, old, new);
+        position += 1;
+    }
+    for line in removed.iter().skip(inserted.len()) {
+        operations.push(PatchOperation::Remove {
+            position,
+            line: (*line).clone(),
+        });
+    }
+    for line in inserted.iter().skip(removed.len()) {
+        operations.push(PatchOperation::Insert {
+            position,
+            line: (*line).clone(),
+        });
+        position += 1;
+    }
+    position
+}
+
+/// Adds operations turning ```old``` into ```new```.
+fn modify(operations: &mut Vec<PatchOperation>, position: usize, old: &SubLine, new: &SubLine) {
+    if old.text != new.text {
+        operations.push(PatchOperation::SetText {
+            position,
+            old: old.text.clone(),
+            new: new.text.clone(),
+        });
+    }
+    if (old.start, old.end) != (new.start, new.end) {
+        operations.push(PatchOperation::SetTiming {
+            position,
+            old: (old.start, old.end),
+            new: (new.start, new.end),
+        });
+    }
+    set_details(operations, position, old, new);
+}
+
+#[cfg(test)]
+mod patch_tests {
+    use std::sync::LazyLock;
+
+    use crate::subtitles::Subtitles;
+    use crate::subline::{SubLine, Word};
+    use crate::timestamp::Timestamp;
+    use crate::timeline::ClampPolicy;
+
+    static PATH: &str = "example.srt";
+
+    static SUBS: LazyLock<Subtitles> = LazyLock::new(|| Subtitles::from_file(PATH).unwrap());
+
+    #[test]
+    fn diff_and_apply() {
+        let original = SUBS.clone();
+        let mut edited = SUBS.clone();
+
+        edited.inner[3].text = "Changed text".to_owned();
+        edited.inner[10].end += Timestamp::new(0, 0, 0, 300);
+        edited.inner.remove(20);
+        edited.inner.remove(20);
+        let line = SubLine::new(0, "Inserted".to_owned(), edited.inner[99].end, edited.inner[100].start);
+        edited.inner.insert(100, line);
+        edited.inner.pop();
+        edited.renumber();
+
+        let patch = original.diff(&edited);
+        assert_eq!(patch.operations.len(), 6);
+
+        let mut patched = original.clone();
+        patched.apply_patch(&patch).unwrap();
+        assert_eq!(patched, edited);
+
+        assert!(original.diff(&original).is_empty());
+        assert!(Subtitles::default().diff(&original).operations.len() == original.len());
+    }
+
+    #[test]
+    fn details() {
+        let original = SUBS.clone();
+        let mut edited = SUBS.clone();
+        edited.inner[0].id = Some("first".to_owned());
+        edited.inner[5].comment = Some("Check the pun".to_owned());
+        edited.inner[5].text = "Changed text".to_owned();
+        edited.inner[50].settings = "align:start".parse().unwrap();
+        let last = edited.inner.last_mut().unwrap();
+        last.words = vec![Word {
+                              start: last.start,
+                              end: last.end,
+                              text: "Word".to_owned(),
+                          }];
+
+        let patch = original.diff(&edited);
+        assert_eq!(patch.operations.len(), 5);
+        let mut patched = original.clone();
+        patched.apply_patch(&patch).unwrap();
+        assert_eq!(patched, edited);
+    }
+
+    #[test]
+    fn common_lines() {
+        let line = |text: &str| SubLine::new(0, text.to_owned(), Timestamp::default(), Timestamp::default());
+        let old: Vec<SubLine> = "abcabba".chars().map(|c| line(&c.to_string())).collect();
+        let new: Vec<SubLine> = "cbabac".chars().map(|c| line(&c.to_string())).collect();
+        let mut common = Vec::new();
+        super::common_lines(&old, &new, 0, 0, &mut common);
+        assert_eq!(common.len(), 4);
+        assert!(common.windows(2).all(|pair| pair[0].0 < pair[1].0 && pair[0].1 < pair[1].1));
+        assert!(common.iter().all(|&(i, j)| old[i].text == new[j].text));
+
+        let many = |shift: u32| -> Subtitles {
+            let mut subs = Subtitles::default();
+            for i in 0..2000 {
+                subs.inner.push(line(&((i * 7 + shift) % 2000).to_string()));
+            }
+            subs
+        };
+        let (a, b) = (many(0), many(1));
+        let mut patched = a.clone();
+        patched.apply_patch(&a.diff(&b)).unwrap();
+        assert_eq!(patched.inner.iter().map(|line| &line.text).collect::<Vec<_>>(),
+                   b.inner.iter().map(|line| &line.text).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn preview() {
+        let original = SUBS.clone();
+        let duration = original.inner[99].end;
+        let (patch, report) = original.preview(|subs| subs.clamp_to(duration, ClampPolicy::Drop));
+        assert_eq!(original, *SUBS);
+        assert_eq!(report.dropped.len(), original.len() - 100);
+        assert_eq!(patch.operations.len(), original.len() - 100);
+
+        let mut clamped = original.clone();
+        clamped.apply_patch(&patch).unwrap();
+        assert_eq!(clamped.len(), 100);
+    }
+
+    #[test]
+    fn conflict() {
+        let original = SUBS.clone();
+        let mut edited = SUBS.clone();
+        edited.inner[3].text = "Changed text".to_owned();
+        let patch = original.diff(&edited);
+
+        let mut other = SUBS.clone();
+        other.inner[3].text = "Concurrently changed text".to_owned();
+        let before = other.clone();
+
+        assert!(other.apply_patch(&patch).is_err());
+        assert_eq!(other, before);
+    }
+}
@@ -0,0 +1,224 @@
+//! Chain of text cleanup steps run over every line, see ```Pipeline```.
+
+use std::borrow::Cow;
+use std::fmt;
+
+use crate::subline::SubLine;
+use crate::subtitles::Subtitles;
+use crate::entities;
+use crate::utils;
+
+/// What a ```TextProcessor``` did with a line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessOutcome {
+    Unchanged,
+    Changed,
+    /// The line must be removed, following processors don't see it.
+    Remove,
+}
+
+/// Cleanup step of a ```Pipeline```.
+pub trait TextProcessor {
+    /// Name of the step in ```PipelineReport```.
+    fn name(&self) -> &str;
+
+    fn process(&self, cue: &mut SubLine) -> ProcessOutcome;
+}
+
+/// Removes tags, see ```utils::strip_tags```.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StripTags;
+
+impl TextProcessor for StripTags {
+    fn name(&self) -> &str {
+        "strip tags"
+    }
+
+    fn process(&self, cue: &mut SubLine) -> ProcessOutcome {
+        let stripped = utils::strip_tags(&cue.text);
+        if stripped == cue.text {
+            return ProcessOutcome::Unchanged;
+        }
+        cue.text = stripped;
+        ProcessOutcome::Changed
+    }
+}
+
+/// Replaces html entities with characters, see ```Subtitles::decode_entities```.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DecodeEntities;
+
+impl TextProcessor for DecodeEntities {
+    fn name(&self) -> &str {
+        "decode entities"
+    }
+
+    fn process(&self, cue: &mut SubLine) -> ProcessOutcome {
+        match entities::decode(&cue.text) {
+            Cow::Owned(decoded) => cue.text = decoded,
+            Cow::Borrowed(_) => return ProcessOutcome::Unchanged,
+        }
+        ProcessOutcome::Changed
+    }
+}
+
+/// Removes lines left without text, e.g. by other steps.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RemoveEmpty;
+
+impl TextProcessor for RemoveEmpty {
+    fn name(&self) -> &str {
+        "remove empty"
+    }
+
+    fn process(&self, cue: &mut SubLine) -> ProcessOutcome {
+        if cue.text.trim().is_empty() {
+            ProcessOutcome::Remove
+        } else {
+            ProcessOutcome::Unchanged
+        }
+    }
+}
+
+/// Changes made by a single step of ```Pipeline```.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RuleStats {
+    pub name: String,
+    /// Indices of changed lines, before renumbering.
+    pub changed: Vec<u32>,
+    /// Indices of removed lines, before renumbering.
+    pub removed: Vec<u32>,
+}
+
+/// Statistics of ```Pipeline::run```, a step per processor in the order they run.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PipelineReport {
+    pub rules: Vec<RuleStats>,
+}
+
+impl PipelineReport {
+    /// Returns ```true``` if no step changed anything.
+    pub fn is_empty(&self) -> bool {
+        self.rules.iter().all(|rule| rule.changed.is_empty() && rule.removed.is_empty())
+    }
+}
+
+/// Runs ```TextProcessor```s over every line in the order they were added.
+#[derive(Default)]
+pub struct Pipeline {
+    processors: Vec<Box<dyn TextProcessor>>,
+    dry_run: bool,
+}
+
+impl fmt::Debug for Pipeline {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Pipeline")
+            .field("processors", &self.processors.iter().map(|processor| processor.name()).collect::<Vec<_>>())
+            .field("dry_run", &self.dry_run)
+            .finish()
+    }
+}
+
+impl Pipeline {
+    pub fn new() -> Pipeline {
+        Pipeline::default()
+    }
+
+    /// Adds the step after the already added ones.
+    pub fn with<P: TextProcessor + 'static>(mut self, processor: P) -> Pipeline {
+        self.processors.push(Box::new(processor));
+        self
+    }
+
+    /// With dry run ```run``` only reports what would be changed.
+    pub fn dry_run(mut self, dry_run: bool) -> Pipeline {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Processes every line, removed lines are dropped and the rest renumbered.
+    pub fn run(&self, subs: &mut Subtitles) -> PipelineReport {
+        let mut report = PipelineReport {
+            rules: self.processors
+                .iter()
+                .map(|processor| RuleStats { name: processor.name().to_owned(), ..RuleStats::default() })
+                .collect(),
+        };
+
+        let mut kept = Vec::with_capacity(subs.inner.len());
+        for line in &subs.inner {
+            let mut processed = line.clone();
+            let mut removed = false;
+            for (processor, stats) in self.processors.iter().zip(&mut report.rules) {
+                match processor.process(&mut processed) {
+                    ProcessOutcome::Unchanged => {}
+                    ProcessOutcome::Changed => stats.changed.push(line.index),
+                    ProcessOutcome::Remove => {
+                        stats.removed.push(line.index);
+                        removed = true;
+                        break;
+                    }
+                }
+            }
+            if !removed {
+                kept.push(processed);
+            }
+        }
+
+        if !self.dry_run {
+            let renumber = kept.len() != subs.inner.len();
+            subs.inner = kept;
+            if renumber {
+                subs.renumber();
+            }
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod pipeline_tests {
+    use super::*;
+    use crate::utils;
+
+    /// Removes sound descriptions in brackets.
+    struct RemoveSounds;
+
+    impl TextProcessor for RemoveSounds {
+        fn name(&self) -> &str {
+            "remove sounds"
+        }
+
+        fn process(&self, cue: &mut SubLine) -> ProcessOutcome {
+            if cue.text.starts_with('[') && cue.text.ends_with(']') {
+                cue.text.clear();
+                ProcessOutcome::Changed
+            } else {
+                ProcessOutcome::Unchanged
+            }
+        }
+    }
+
+    #[test]
+    fn run() {
+        let subs = utils::subs_from_texts(&["<i>[door slams]</i>", "Tom &amp; Jerry", "Plain"]);
+        let pipeline = Pipeline::new().with(StripTags).with(DecodeEntities).with(RemoveSounds).with(RemoveEmpty);
+
+        let mut preview = subs.clone();
+        let report = pipeline.dry_run(true).run(&mut preview);
+        assert_eq!(preview, subs);
+        let pipeline = Pipeline::new().with(StripTags).with(DecodeEntities).with(RemoveSounds).with(RemoveEmpty);
+        let mut cleaned = subs.clone();
+        assert_eq!(pipeline.run(&mut cleaned), report);
+
+        let stats: Vec<_> = report.rules.iter().map(|rule| (&*rule.name, &rule.changed[..], &rule.removed[..])).collect();
+        assert_eq!(stats,
+                   vec![("strip tags", &[1][..], &[][..]),
+                        ("decode entities", &[2][..], &[][..]),
+                        ("remove sounds", &[1][..], &[][..]),
+                        ("remove empty", &[][..], &[1][..])]);
+        let texts: Vec<_> = cleaned.inner.iter().map(|line| (line.index, &*line.text)).collect();
+        assert_eq!(texts, vec![(1, "Tom & Jerry"), (2, "Plain")]);
+        assert!(Pipeline::new().with(RemoveEmpty).run(&mut cleaned).is_empty());
+    }
+}
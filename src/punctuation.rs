@@ -0,0 +1,286 @@
+use std::borrow::Cow;
+
+use crate::subtitles::Subtitles;
+use crate::language::LanguageTag;
+
+const NO_BREAK_SPACE: char = '\u{a0}';
+const NARROW_NO_BREAK_SPACE: char = '\u{202f}';
+
+/// Dialogue dash convention for ```Subtitles::normalize_dialogue_dashes```.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DashStyle {
+    /// ```-Text```
+    Hyphen,
+    /// ```- Text```
+    #[default]
+    HyphenSpace,
+    /// ```– Text```
+    EnDash,
+    /// ```—Text```, as in Spanish.
+    EmDash,
+}
+
+impl DashStyle {
+    /// Prefix of a dialogue row in this style.
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            DashStyle::Hyphen => "-",
+            DashStyle::HyphenSpace => "- ",
+            DashStyle::EnDash => "– ",
+            DashStyle::EmDash => "—",
+        }
+    }
+}
+
+impl Subtitles {
+    /// Applies punctuation rules of ```locale```, e.g. after a machine or a hurried translation.
+    ///
+    /// * French (```fr```): straight and curly quotes become guillemets with no-break spaces
+    ///   inside (```« Oui »```), a narrow no-break space is put before ```?```, ```!``` and ```;```,
+    ///   a no-break space before ```:```, dialogue dashes are ```– ```.
+    /// * Spanish (```es```): opening ```¿``` and ```¡``` are added to questions and exclamations
+    ///   without them, dialogue dashes are ```—``` without a space.
+    /// * English (```en```): dialogue dashes are ```- ```.
+    ///
+    /// Other languages are left as they are. Returns the number of changed lines.
+    pub fn fix_punctuation(&mut self, locale: &LanguageTag) -> usize {
+        let rules: fn(&str) -> String = match locale.as_str() {
+            "fr" => french,
+            "es" => spanish,
+            "en" => english,
+            _ => return 0,
+        };
+        self.edit_text(|text| Cow::Owned(rules(text)))
+    }
+
+    /// Rewrites dialogue dashes at the start of rows in ```style```, and makes them mark speakers:
+    /// a dash is removed from a single-row line and from a line where only the first row has one
+    /// (a single wrapped sentence), and added to the first row of a line where only later rows
+    /// have one (```Hi.\n- Hello.```). Returns the number of changed lines.
+    pub fn normalize_dialogue_dashes(&mut self, style: DashStyle) -> usize {
+        self.edit_text(|text| {
+            let dashed: Vec<bool> = text.split('\n').map(|row| dialogue_dash(row).is_some()).collect();
+            if !dashed.contains(&true) {
+                return Cow::Borrowed(text);
+            }
+            let speakers = dashed.len() > 1 && dashed[1..].contains(&true);
+            let rows: Vec<String> = text.split('\n')
+                .enumerate()
+                .map(|(i, row)| match dialogue_dash(row) {
+                    Some((prefix, rest)) if speakers => format!("{}{}{}", prefix, style.as_str(), rest),
+                    Some((prefix, rest)) => format!("{}{}", prefix, rest),
+                    None if speakers && i == 0 && !row.trim().is_empty() => {
+                        let prefix = tags_prefix(row);
+                        format!("{}{}{}", &row[..prefix], style.as_str(), &row[prefix..])
+                    }
+                    None => row.to_owned(),
+                })
+                .collect();
+            Cow::Owned(rows.join("\n"))
+        })
+    }
+}
+
+fn french(text: &str) -> String {
+    let text = dialogue_dashes(text, DashStyle::EnDash);
+    let quotes = text.chars().filter(|&c| c == '"').count();
+    let mut result = String::with_capacity(text.len());
+    let mut opened = false;
+    let mut chars = text.chars().peekable();
+    let mut in_tag = false;
+    while let Some(c) = chars.next() {
+        if in_tag || c == '<' {
+            in_tag = c != '>';
+            result.push(c);
+            continue;
+        }
+        match c {
+            '"' if quotes % 2 == 0 => {
+                opened = !opened;
+                if opened {
+                    open_guillemet(&mut result, &mut chars);
+                } else {
+                    close_guillemet(&mut result);
+                }
+            }
+            '“' | '«' => open_guillemet(&mut result, &mut chars),
+            '”' | '»' => close_guillemet(&mut result),
+            '?' | '!' | ';' | ':' => {
+                let space = if c == ':' { NO_BREAK_SPACE } else { NARROW_NO_BREAK_SPACE };
+                let url = c == ':' && chars.peek() == Some(&'/');
+                match result.chars().next_back() {
+                    Some(' ') | Some(NO_BREAK_SPACE) | Some(NARROW_NO_BREAK_SPACE) if !url => {
+                        result.pop();
+                        result.push(space);
+                    }
+                    Some(last) if !url && (last.is_alphabetic() || last == '»' || last == ')') => result.push(space),
+                    _ => {}
+                }
+                result.push(c);
+            }
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+fn open_guillemet<I: Iterator<Item = char>>(result: &mut String, chars: &mut ::std::iter::Peekable<I>) {
+    while chars.peek().is_some_and(|&c| c == ' ' || c == NO_BREAK_SPACE) {
+        chars.next();
+    }
+    result.push('«');
+    result.push(NO_BREAK_SPACE);
+}
+
+fn close_guillemet(result: &mut String) {
+    let trimmed = result.trim_end_matches([' ', NO_BREAK_SPACE]).len();
+    result.truncate(trimmed);
+    result.push(NO_BREAK_SPACE);
+    result.push('»');
+}
+
+fn spanish(text: &str) -> String {
+    let text = dialogue_dashes(text, DashStyle::EmDash);
+    let mut result = String::with_capacity(text.len() + 2);
+    let mut sentence_start = 0;
+    let mut in_tag = false;
+    let mut chars = text.char_indices().peekable();
+    while let Some((_, c)) = chars.next() {
+        in_tag = (in_tag || c == '<') && c != '>';
+        if in_tag || !(c == '?' || c == '!' || c == '.' || c == '…' || c == '\n') {
+            continue;
+        }
+        while chars.peek().is_some_and(|&(_, next)| next == '?' || next == '!' || next == '.') {
+            chars.next();
+        }
+        let end = chars.peek().map(|&(end, _)| end).unwrap_or(text.len());
+        push_sentence(&mut result, &text[sentence_start..end], c);
+        sentence_start = end;
+    }
+    push_sentence(&mut result, &text[sentence_start..], ' ');
+    result
+}
+
+/// Pushes ```sentence``` ending with ```last```, adding the inverted mark at its first letter if needed.
+fn push_sentence(result: &mut String, sentence: &str, last: char) {
+    let inverted = match last {
+        '?' => '¿',
+        '!' => '¡',
+        _ => {
+            result.push_str(sentence);
+            return;
+        }
+    };
+    if sentence.contains(inverted) {
+        result.push_str(sentence);
+        return;
+    }
+    let mut in_tag = false;
+    let start = sentence.char_indices()
+        .find(|&(_, c)| {
+            in_tag = (in_tag || c == '<') && c != '>';
+            !in_tag && c != '>' && c.is_alphanumeric()
+        })
+        .map(|(i, _)| i)
+        .unwrap_or(0);
+    result.push_str(&sentence[..start]);
+    result.push(inverted);
+    result.push_str(&sentence[start..]);
+}
+
+fn english(text: &str) -> String {
+    dialogue_dashes(text, DashStyle::HyphenSpace)
+}
+
+/// Replaces dashes at the start of rows (after opening tags) with ```style```.
+fn dialogue_dashes(text: &str, style: DashStyle) -> String {
+    text.split('\n')
+        .map(|row| match dialogue_dash(row) {
+            Some((prefix, rest)) => format!("{}{}{}", prefix, style.as_str(), rest),
+            None => row.to_owned(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Splits a row starting with a dialogue dash (after opening tags) into the tags and the text after the dash.
+fn dialogue_dash(row: &str) -> Option<(&str, &str)> {
+    let prefix = tags_prefix(row);
+    let rest = &row[prefix..];
+    let stripped = rest.trim_start_matches(['-', '–', '—']);
+    if stripped.len() != rest.len() && rest.len() - stripped.len() <= '—'.len_utf8() && !stripped.starts_with('-') {
+        Some((&row[..prefix], stripped.trim_start()))
+    } else {
+        None
+    }
+}
+
+/// Length of the tags at the start of ```row```.
+fn tags_prefix(row: &str) -> usize {
+    let mut prefix = 0;
+    while row[prefix..].starts_with('<') {
+        match row[prefix..].find('>') {
+            Some(end) => prefix += end + 1,
+            None => break,
+        }
+    }
+    prefix
+}
+
+#[cfg(test)]
+mod punctuation_tests {
+    use std::str::FromStr;
+
+    use crate::language::LanguageTag;
+    use crate::utils;
+    use super::DashStyle;
+
+    fn fixed(locale: &str, text: &str) -> String {
+        let mut subs = utils::subs_from_texts(&[text]);
+        subs.fix_punctuation(&LanguageTag::from_str(locale).unwrap());
+        subs.inner[0].text.clone()
+    }
+
+    #[test]
+    fn french() {
+        assert_eq!(fixed("fr", "Il a dit \"bonjour\"?!"), "Il a dit «\u{a0}bonjour\u{a0}»\u{202f}?!");
+        assert_eq!(fixed("fr-CA", "Attention : voir http://a.fr à 12:30;"), "Attention\u{a0}: voir http://a.fr à 12:30;");
+        assert_eq!(fixed("fra", "<i>- Oui!</i>\r\n-Non ; jamais."), "<i>– Oui\u{202f}!</i>\r\n– Non\u{202f}; jamais.");
+        assert_eq!(fixed("fr", "«Déjà»"), "«\u{a0}Déjà\u{a0}»");
+    }
+
+    #[test]
+    fn spanish() {
+        assert_eq!(fixed("es", "- Qué pasa? Nada!\r\n- <i>Cómo estás?</i>"), "—¿Qué pasa? ¡Nada!\r\n—<i>¿Cómo estás?</i>");
+        assert_eq!(fixed("spa", "¿Vienes? Sí."), "¿Vienes? Sí.");
+    }
+
+    #[test]
+    fn other_locales() {
+        assert_eq!(fixed("en", "-Hi.\r\n– Hello. Well-known."), "- Hi.\r\n- Hello. Well-known.");
+        let mut subs = utils::subs_from_texts(&["\"Hi\"?"]);
+        assert_eq!(subs.fix_punctuation(&LanguageTag::from_str("de").unwrap()), 0);
+        assert_eq!(subs.fix_punctuation(&LanguageTag::from_str("en").unwrap()), 0);
+    }
+
+    #[test]
+    fn normalize_dialogue_dashes() {
+        let mut subs = utils::subs_from_texts(&["-Hi.\r\n– <i>Hello.</i>",
+                                                "<i>Hi.</i>\r\n-Hello.",
+                                                "- I was going\r\nto say something.",
+                                                "- Alone.",
+                                                "Well-known fact.",
+                                                "—Hola.\r\n—¿Qué?"]);
+        assert_eq!(subs.normalize_dialogue_dashes(DashStyle::EnDash), 5);
+        let texts: Vec<&str> = subs.inner.iter().map(|line| line.text.as_str()).collect();
+        assert_eq!(texts,
+                   vec!["– Hi.\r\n– <i>Hello.</i>",
+                        "<i>– Hi.</i>\r\n– Hello.",
+                        "I was going\r\nto say something.",
+                        "Alone.",
+                        "Well-known fact.",
+                        "– Hola.\r\n– ¿Qué?"]);
+        assert_eq!(subs.normalize_dialogue_dashes(DashStyle::Hyphen), 3);
+        assert_eq!(subs.inner[0].text, "-Hi.\r\n-<i>Hello.</i>");
+    }
+}
@@ -0,0 +1,454 @@
+use crate::timestamp::Timestamp;
+use crate::subtitles::Subtitles;
+use crate::subline::SubLine;
+use crate::utils;
+
+/// Limits checked by ```Subtitles::validate``` and weights of ```Subtitles::quality_score```,
+/// by default as common broadcast and streaming guidelines for adult audience.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QualityProfile {
+    /// Highest reading speed in characters per second, see ```SubLine::chars_per_second```.
+    pub max_cps: f64,
+    /// Longest row of a line in characters, not counting tags.
+    pub max_line_length: usize,
+    /// Most rows of a line.
+    pub max_rows: usize,
+    pub min_duration: Timestamp,
+    pub max_duration: Timestamp,
+    /// Shortest gap between consecutive lines which don't overlap.
+    pub min_gap: Timestamp,
+    /// Weight of ```QualityCategory::Timing``` in the total score.
+    pub timing_weight: f64,
+    /// Weight of ```QualityCategory::ReadingSpeed``` in the total score.
+    pub reading_speed_weight: f64,
+    /// Weight of ```QualityCategory::Layout``` in the total score.
+    pub layout_weight: f64,
+    /// Weight of ```QualityCategory::Structure``` in the total score.
+    pub structure_weight: f64,
+}
+
+impl Default for QualityProfile {
+    fn default() -> QualityProfile {
+        QualityProfile {
+            max_cps: 20.0,
+            max_line_length: 42,
+            max_rows: 2,
+            min_duration: Timestamp::new(0, 0, 0, 833),
+            max_duration: Timestamp::new(0, 0, 7, 0),
+            min_gap: Timestamp::new(0, 0, 0, 83),
+            timing_weight: 1.0,
+            reading_speed_weight: 1.0,
+            layout_weight: 1.0,
+            structure_weight: 2.0,
+        }
+    }
+}
+
+/// Group of rules scored together by ```Subtitles::quality_score```.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum QualityCategory {
+    Timing,
+    ReadingSpeed,
+    Layout,
+    Structure,
+}
+
+impl QualityCategory {
+    /// All categories in the order of ```QualityScore::categories```.
+    pub const ALL: [QualityCategory; 4] = [QualityCategory::Timing,
+                                           QualityCategory::ReadingSpeed,
+                                           QualityCategory::Layout,
+                                           QualityCategory::Structure];
+}
+
+/// Rule broken by a line, see ```Subtitles::validate```.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Rule {
+    /// Line's index isn't its position plus one.
+    IndexMismatch,
+    /// Line starts before the previous line.
+    Unsorted,
+    /// Line's start is bigger than its end.
+    InvalidTiming,
+    /// Line has no visible text.
+    EmptyText,
+    /// Line starts before one of the previous lines ends.
+    Overlap,
+    /// Line is shown shorter than ```QualityProfile::min_duration```.
+    TooShort,
+    /// Line is shown longer than ```QualityProfile::max_duration```.
+    TooLong,
+    /// Gap after the line is shorter than ```QualityProfile::min_gap```.
+    GapTooShort,
+    /// Reading speed is above ```QualityProfile::max_cps```.
+    ReadingSpeed,
+    /// A row is longer than ```QualityProfile::max_line_length```.
+    LineTooLong,
+    /// Line has more than ```QualityProfile::max_rows``` rows.
+    TooManyRows,
+}
+
+impl Rule {
+    /// All rules in the order of ```Subtitles::validate``` checks.
+    pub const ALL: [Rule; 11] = [Rule::IndexMismatch,
+                                 Rule::Unsorted,
+                                 Rule::InvalidTiming,
+                                 Rule::EmptyText,
+                                 Rule::Overlap,
+                                 Rule::TooShort,
+                                 Rule::TooLong,
+                                 Rule::GapTooShort,
+                                 Rule::ReadingSpeed,
+                                 Rule::LineTooLong,
+                                 Rule::TooManyRows];
+
+    /// Category in which the rule is scored.
+    pub fn category(&self) -> QualityCategory {
+        match *self {
+            Rule::IndexMismatch | Rule::Unsorted | Rule::InvalidTiming | Rule::EmptyText => QualityCategory::Structure,
+            Rule::Overlap | Rule::TooShort | Rule::TooLong | Rule::GapTooShort => QualityCategory::Timing,
+            Rule::ReadingSpeed => QualityCategory::ReadingSpeed,
+            Rule::LineTooLong | Rule::TooManyRows => QualityCategory::Layout,
+        }
+    }
+
+    /// Short description of the rule.
+    pub fn description(&self) -> &'static str {
+        match *self {
+            Rule::IndexMismatch => "Line's index doesn't match its position",
+            Rule::Unsorted => "Line starts before the previous line",
+            Rule::InvalidTiming => "Line ends before it starts",
+            Rule::EmptyText => "Line has no text",
+            Rule::Overlap => "Line overlaps a previous line",
+            Rule::TooShort => "Line is shown too short",
+            Rule::TooLong => "Line is shown too long",
+            Rule::GapTooShort => "Gap after the line is too short",
+            Rule::ReadingSpeed => "Reading speed is too high",
+            Rule::LineTooLong => "Row of the line is too long",
+            Rule::TooManyRows => "Line has too many rows",
+        }
+    }
+
+    /// Stable kebab-case identifier of the rule, e.g. ```"gap-too-short"```.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Rule::IndexMismatch => "index-mismatch",
+            Rule::Unsorted => "unsorted",
+            Rule::InvalidTiming => "invalid-timing",
+            Rule::EmptyText => "empty-text",
+            Rule::Overlap => "overlap",
+            Rule::TooShort => "too-short",
+            Rule::TooLong => "too-long",
+            Rule::GapTooShort => "gap-too-short",
+            Rule::ReadingSpeed => "reading-speed",
+            Rule::LineTooLong => "line-too-long",
+            Rule::TooManyRows => "too-many-rows",
+        }
+    }
+}
+
+/// Rule broken by the line with ```index```.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Violation {
+    pub index: u32,
+    pub rule: Rule,
+}
+
+/// Score of a single category, see ```QualityScore```.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CategoryScore {
+    pub category: QualityCategory,
+    /// Percentage of lines without violations in the category, from 0 to 100.
+    pub score: f64,
+    /// Number of violations in the category.
+    pub violations: usize,
+}
+
+/// Result of ```Subtitles::quality_score```.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QualityScore {
+    /// Weighted mean of category scores, from 0 (worst) to 100 (no violations).
+    pub score: f64,
+    /// Scores of all categories in the order of ```QualityCategory::ALL```.
+    pub categories: Vec<CategoryScore>,
+    pub violations: Vec<Violation>,
+}
+
+impl QualityScore {
+    /// Score of given ```category```.
+    pub fn category(&self, category: QualityCategory) -> Option<&CategoryScore> {
+        self.categories.iter().find(|score| score.category == category)
+    }
+}
+
+impl SubLine {
+    /// Reading speed: characters of the text without tags and line breaks per second shown.
+    /// Lines with text shown for no time are infinitely fast.
+    pub fn chars_per_second(&self) -> f64 {
+        let chars = utils::strip_tags(&self.text).lines().map(|row| row.chars().count()).sum::<usize>();
+        let duration = self.end.total_miliseconds().saturating_sub(self.start.total_miliseconds());
+        if chars == 0 {
+            0.0
+        } else if duration == 0 {
+            f64::INFINITY
+        } else {
+            chars as f64 * 1000.0 / duration as f64
+        }
+    }
+}
+
+impl Subtitles {
+    /// Checks every line against the rules of ```profile```.
+    /// Returns violations in the order of lines.
+    pub fn validate(&self, profile: &QualityProfile) -> Vec<Violation> {
+        self.violations(profile)
+            .into_iter()
+            .map(|(position, rule)| Violation { index: self.inner[position].index, rule })
+            .collect()
+    }
+
+    /// Rules broken by lines at inner vector positions, in the order of lines.
+    pub(crate) fn violations(&self, profile: &QualityProfile) -> Vec<(usize, Rule)> {
+        let mut violations = Vec::new();
+        let mut max_end: Option<Timestamp> = None;
+        for (position, line) in self.inner.iter().enumerate() {
+            let mut violated = |rule| violations.push((position, rule));
+            let previous = position.checked_sub(1).map(|previous| &self.inner[previous]);
+
+            if line.index as usize != position + 1 {
+                violated(Rule::IndexMismatch);
+            }
+            if previous.is_some_and(|previous| line.start < previous.start) {
+                violated(Rule::Unsorted);
+            }
+            if line.start > line.end {
+                violated(Rule::InvalidTiming);
+            }
+            let text = utils::strip_tags(&line.text);
+            if text.trim().is_empty() {
+                violated(Rule::EmptyText);
+            }
+
+            if max_end.is_some_and(|end| line.start < end) {
+                violated(Rule::Overlap);
+            }
+            if line.start <= line.end {
+                let duration = line.end - line.start;
+                if duration < profile.min_duration {
+                    violated(Rule::TooShort);
+                }
+                if duration > profile.max_duration {
+                    violated(Rule::TooLong);
+                }
+            }
+            let gap_too_short = self.inner
+                .get(position + 1)
+                .is_some_and(|next| next.start >= line.end && next.start - line.end < profile.min_gap);
+            if gap_too_short {
+                violated(Rule::GapTooShort);
+            }
+
+            if line.chars_per_second() > profile.max_cps {
+                violated(Rule::ReadingSpeed);
+            }
+            let rows: Vec<&str> = text.lines().collect();
+            if rows.iter().any(|row| row.chars().count() > profile.max_line_length) {
+                violated(Rule::LineTooLong);
+            }
+            if rows.len() > profile.max_rows {
+                violated(Rule::TooManyRows);
+            }
+
+            max_end = max_end.max(Some(line.end));
+        }
+        violations
+    }
+
+    /// Aggregates violations of ```profile```'s rules into a score from 0 to 100,
+    /// e.g. to reject or flag uploads below a threshold. Every category scores
+    /// the percentage of lines without violations in it, the total score is the mean
+    /// of category scores weighted by ```profile```. Subtitles without lines score 0.
+    pub fn quality_score(&self, profile: &QualityProfile) -> QualityScore {
+        let violations = self.validate(profile);
+        let lines = self.inner.len();
+
+        let categories: Vec<CategoryScore> = QualityCategory::ALL
+            .iter()
+            .map(|&category| {
+                let in_category: Vec<&Violation> = violations.iter()
+                    .filter(|violation| violation.rule.category() == category)
+                    .collect();
+                let mut violating: Vec<u32> = in_category.iter().map(|violation| violation.index).collect();
+                violating.dedup();
+                let score = if lines == 0 {
+                    0.0
+                } else {
+                    100.0 * (lines - violating.len().min(lines)) as f64 / lines as f64
+                };
+                CategoryScore { category, score, violations: in_category.len() }
+            })
+            .collect();
+
+        let weighted: Vec<(f64, f64)> = categories.iter()
+            .map(|category| (category.score, weight(profile, category.category).max(0.0)))
+            .collect();
+        let total_weight: f64 = weighted.iter().map(|&(_, weight)| weight).sum();
+        let score = if total_weight > 0.0 {
+            weighted.iter().map(|&(score, weight)| score * weight).sum::<f64>() / total_weight
+        } else {
+            weighted.iter().map(|&(score, _)| score).sum::<f64>() / weighted.len() as f64
+        };
+
+        QualityScore { score, categories, violations }
+    }
+
+    /// Table for manual quality control with a row per line: index, start and end (```00:01:02.500```),
+    /// duration and gap to the next line in miliseconds (negative if they overlap),
+    /// reading speed, lengths of rows, names of broken rules of ```profile``` and text
+    /// without tags with rows separated by ``` / ```. Fields are separated by commas
+    /// and quoted if needed, the first row is a header.
+    pub fn to_qc_csv(&self, profile: &QualityProfile) -> String {
+        let mut rules: Vec<Vec<&str>> = vec![Vec::new(); self.inner.len()];
+        for (position, rule) in self.violations(profile) {
+            rules[position].push(rule.name());
+        }
+
+        let mut csv = String::from("index,start,end,duration_ms,cps,line_lengths,gap_to_next_ms,violations,text\r\n");
+        for (position, line) in self.inner.iter().enumerate() {
+            let text = utils::strip_tags(&line.text);
+            let rows: Vec<&str> = text.lines().collect();
+            let lengths: Vec<String> = rows.iter().map(|row| row.chars().count().to_string()).collect();
+            let gap = self.inner
+                .get(position + 1)
+                .map(|next| (next.start.total_miliseconds() as i64 - line.end.total_miliseconds() as i64).to_string())
+                .unwrap_or_default();
+            let fields = [line.index.to_string(),
+                          timestamp(line.start),
+                          timestamp(line.end),
+                          (line.end.total_miliseconds() as i64 - line.start.total_miliseconds() as i64).to_string(),
+                          format!("{:.1}", line.chars_per_second()),
+                          lengths.join("/"),
+                          gap,
+                          rules[position].join(";"),
+                          rows.join(" / ")];
+            let fields: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+            csv.push_str(&fields.join(","));
+            csv.push_str("\r\n");
+        }
+        csv
+    }
+}
+
+/// Quotes ```field``` if it contains commas, quotes or line breaks.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+/// ```time``` with a dot before miliseconds, so the field doesn't need quoting.
+fn timestamp(time: Timestamp) -> String {
+    format!("{:02}:{:02}:{:02}.{:03}",
+            time.hours,
+            time.minutes,
+            time.seconds,
+            time.miliseconds)
+}
+
+fn weight(profile: &QualityProfile, category: QualityCategory) -> f64 {
+    match category {
+        QualityCategory::Timing => profile.timing_weight,
+        QualityCategory::ReadingSpeed => profile.reading_speed_weight,
+        QualityCategory::Layout => profile.layout_weight,
+        QualityCategory::Structure => profile.structure_weight,
+    }
+}
+
+#[cfg(test)]
+mod quality_tests {
+    use super::*;
+
+    static TEXTS: [&str; 4] = ["Fine.",
+                               "Way too much text for half a second",
+                               "This row is much longer than forty-two characters\r\nTwo\r\nThree",
+                               "<i></i>"];
+
+    #[test]
+    fn validate() {
+        let mut subs = utils::subs_from_texts(&TEXTS);
+        // No gap after the first line, the second one is too short and the last one too long
+        subs.inner[0].end = Timestamp::new(0, 0, 2, 0);
+        subs.inner[1].end = Timestamp::new(0, 0, 2, 500);
+        subs.inner[3].end = Timestamp::new(0, 0, 16, 0);
+        let violations: Vec<(u32, &str)> = subs.validate(&QualityProfile::default())
+            .iter()
+            .map(|violation| (violation.index, violation.rule.name()))
+            .collect();
+        assert_eq!(violations,
+                   vec![(1, "gap-too-short"),
+                        (2, "too-short"),
+                        (2, "reading-speed"),
+                        (3, "reading-speed"),
+                        (3, "line-too-long"),
+                        (3, "too-many-rows"),
+                        (4, "empty-text"),
+                        (4, "too-long")]);
+
+        let mut unsorted = subs.clone();
+        unsorted.inner.swap(0, 1);
+        let violations = unsorted.validate(&QualityProfile::default());
+        let rules: Vec<Rule> = violations.iter().map(|violation| violation.rule).take(4).collect();
+        assert_eq!(rules, vec![Rule::IndexMismatch, Rule::TooShort, Rule::ReadingSpeed, Rule::IndexMismatch]);
+        assert!(violations.contains(&Violation { index: 1, rule: Rule::Unsorted }));
+        assert!(violations.contains(&Violation { index: 1, rule: Rule::Overlap }));
+    }
+
+    #[test]
+    fn quality_score() {
+        let mut subs = utils::subs_from_texts(&TEXTS);
+        // No gap after the first line, the second one is too short and the last one too long
+        subs.inner[0].end = Timestamp::new(0, 0, 2, 0);
+        subs.inner[1].end = Timestamp::new(0, 0, 2, 500);
+        subs.inner[3].end = Timestamp::new(0, 0, 16, 0);
+        let score = subs.quality_score(&QualityProfile::default());
+        let categories: Vec<(f64, usize)> = score.categories.iter().map(|c| (c.score, c.violations)).collect();
+        assert_eq!(categories, vec![(25.0, 3), (50.0, 2), (75.0, 2), (75.0, 1)]);
+        assert_eq!(score.score, (25.0 + 50.0 + 75.0 + 2.0 * 75.0) / 5.0);
+        assert_eq!(score.category(QualityCategory::Layout).unwrap().score, 75.0);
+        assert_eq!(score.violations.len(), 8);
+
+        let clean = utils::subs_from_texts(&["One", "Two"]);
+        assert_eq!(clean.quality_score(&QualityProfile::default()).score, 100.0);
+        assert_eq!(Subtitles::default().quality_score(&QualityProfile::default()).score, 0.0);
+    }
+
+    #[test]
+    fn to_qc_csv() {
+        let mut subs = utils::subs_from_texts(&TEXTS);
+        // No gap after the first line, the second one is too short and the last one too long
+        subs.inner[0].end = Timestamp::new(0, 0, 2, 0);
+        subs.inner[1].end = Timestamp::new(0, 0, 2, 500);
+        subs.inner[3].end = Timestamp::new(0, 0, 16, 0);
+        subs.inner[0].text = "<i>Fine</i>, \"fine\".\r\nOK".to_owned();
+        let csv = subs.to_qc_csv(&QualityProfile::default());
+        let rows: Vec<&str> = csv.split("\r\n").collect();
+        assert_eq!(rows[0], "index,start,end,duration_ms,cps,line_lengths,gap_to_next_ms,violations,text");
+        assert_eq!(rows[1], "1,00:00:00.000,00:00:02.000,2000,7.5,13/2,0,gap-too-short,\"Fine, \"\"fine\"\". / OK\"");
+        assert_eq!(rows[3],
+                   "3,00:00:04.000,00:00:05.000,1000,57.0,49/3/5,1000,reading-speed;line-too-long;too-many-rows,\
+                    This row is much longer than forty-two characters / Two / Three");
+        assert_eq!(rows[4], "4,00:00:06.000,00:00:16.000,10000,0.0,,,empty-text;too-long,");
+        assert_eq!(rows.len(), 6);
+    }
+
+    #[test]
+    fn chars_per_second() {
+        let line = SubLine::new(1,
+                                "<i>Hello</i>\r\nworld".to_owned(),
+                                Timestamp::new(0, 0, 1, 0),
+                                Timestamp::new(0, 0, 3, 0));
+        assert_eq!(line.chars_per_second(), 5.0);
+    }
+}
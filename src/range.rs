@@ -0,0 +1,166 @@
+use std::ops::Range;
+use std::io::{Error, ErrorKind};
+
+use crate::timestamp::Timestamp;
+use crate::subline::SubLine;
+use crate::subtitles::Subtitles;
+
+/// Mutable view of consecutive lines, see ```Subtitles::range_mut```.
+///
+/// Every operation checks that lines stay sorted and don't overlap lines
+/// outside of the range, otherwise nothing is changed and an error is returned.
+#[derive(Debug)]
+pub struct RangeMut<'a> {
+    subs: &'a mut Subtitles,
+    range: Range<usize>,
+}
+
+impl Subtitles {
+    /// Get mutable view of lines at given inner vector positions.
+    /// Returns ```None``` if the range is out of bounds.
+    pub fn range_mut(&mut self, range: Range<usize>) -> Option<RangeMut<'_>> {
+        if range.start > range.end || range.end > self.inner.len() {
+            return None;
+        }
+        Some(RangeMut {
+            subs: self,
+            range,
+        })
+    }
+}
+
+impl<'a> RangeMut<'a> {
+    /// Lines in the range.
+    pub fn lines(&self) -> &[SubLine] {
+        &self.subs.inner[self.range.clone()]
+    }
+
+    /// Moves lines by ```offset``` miliseconds (negative offset moves them earlier).
+    pub fn shift(&mut self, offset: i64) -> Result<(), Error> {
+        self.map_timing(|time| time + offset)
+    }
+
+    /// Stretches (or squeezes, if ```factor``` is less than 1) lines' timing
+    /// relative to the start of the first line in the range.
+    pub fn rescale(&mut self, factor: f64) -> Result<(), Error> {
+        if !factor.is_finite() || factor <= 0.0 {
+            return Err(Error::new(ErrorKind::InvalidInput, "Scale factor must be positive"));
+        }
+        let origin = match self.lines().first() {
+            Some(line) => line.start.total_miliseconds() as i64,
+            None => return Ok(()),
+        };
+        self.map_timing(|time| origin + ((time - origin) as f64 * factor).round() as i64)
+    }
+
+    /// Replaces lines in the range with given lines, which are renumbered accordingly.
+    /// The range then covers the new lines.
+    pub fn replace(&mut self, lines: Vec<SubLine>) -> Result<(), Error> {
+        self.check(&lines)?;
+
+        let count = lines.len();
+        let _ = self.subs.inner.splice(self.range.clone(), lines);
+        self.range = self.range.start..self.range.start + count;
+        self.subs.renumber();
+        Ok(())
+    }
+
+    fn map_timing<F: Fn(i64) -> i64>(&mut self, f: F) -> Result<(), Error> {
+        let mut lines = self.lines().to_vec();
+        for line in &mut lines {
+            let start = f(line.start.total_miliseconds() as i64);
+            let end = f(line.end.total_miliseconds() as i64);
+            if start < 0 || end < 0 {
+                return Err(Error::new(ErrorKind::InvalidInput, "Timestamp can't be negative"));
+            }
+            line.start = Timestamp::from_miliseconds(start as u64);
+            line.end = Timestamp::from_miliseconds(end as u64);
+        }
+
+        self.check(&lines)?;
+        for (line, changed) in self.subs.inner[self.range.clone()].iter_mut().zip(lines) {
+            line.start = changed.start;
+            line.end = changed.end;
+        }
+        Ok(())
+    }
+
+    /// Checks that ```lines``` fit between the neighbours of the range.
+    fn check(&self, lines: &[SubLine]) -> Result<(), Error> {
+        let invalid = |message: &str| Err(Error::new(ErrorKind::InvalidInput, message));
+
+        for (i, line) in lines.iter().enumerate() {
+            if line.start > line.end {
+                return invalid("Line's start is bigger than its end");
+            }
+            if i > 0 && lines[i - 1].start > line.start {
+                return invalid("Lines are not sorted by start time");
+            }
+        }
+
+        if let (Some(first), Some(previous)) = (lines.first(), self.range.start.checked_sub(1)) {
+            if self.subs.inner[previous].end > first.start {
+                return invalid("Range overlaps the previous line");
+            }
+        }
+        if let (Some(last), Some(next)) = (lines.last(), self.subs.inner.get(self.range.end)) {
+            if last.end > next.start {
+                return invalid("Range overlaps the next line");
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod range_tests {
+    use crate::timestamp::Timestamp;
+    use crate::subline::SubLine;
+    use crate::utils;
+
+    #[test]
+    fn shift() {
+        let mut subs = utils::subs_from_texts(&["One", "Two", "Three", "Four"]);
+        let before = subs.clone();
+
+        {
+            let mut range = subs.range_mut(1..3).unwrap();
+            assert!(range.shift(1001).is_err());
+            assert!(range.shift(-1001).is_err());
+            range.shift(-500).unwrap();
+        }
+        assert_eq!(subs.inner[1].start, Timestamp::new(0, 0, 1, 500));
+        assert_eq!(subs.inner[2].end, Timestamp::new(0, 0, 4, 500));
+        assert_eq!(subs.inner[3], before.inner[3]);
+
+        assert!(subs.range_mut(3..5).is_none());
+    }
+
+    #[test]
+    fn rescale() {
+        let mut subs = utils::subs_from_texts(&["One", "Two", "Three", "Four"]);
+
+        subs.range_mut(1..3).unwrap().rescale(1.25).unwrap();
+        assert_eq!(subs.inner[1].end, Timestamp::new(0, 0, 3, 250));
+        assert_eq!(subs.inner[2].start, Timestamp::new(0, 0, 4, 500));
+        assert_eq!(subs.inner[2].end, Timestamp::new(0, 0, 5, 750));
+
+        assert!(subs.range_mut(0..2).unwrap().rescale(1.5).is_err());
+        assert!(subs.range_mut(0..2).unwrap().rescale(-1.0).is_err());
+    }
+
+    #[test]
+    fn replace() {
+        let mut subs = utils::subs_from_texts(&["One", "Two", "Three"]);
+
+        let lines = vec![SubLine::new(0, "Two a".to_owned(), Timestamp::new(0, 0, 1, 0), Timestamp::new(0, 0, 2, 0)),
+                         SubLine::new(0, "Two b".to_owned(), Timestamp::new(0, 0, 2, 0), Timestamp::new(0, 0, 3, 0))];
+        subs.range_mut(1..2).unwrap().replace(lines).unwrap();
+        assert_eq!(subs.len(), 4);
+        assert_eq!(subs.by_index(3).unwrap().text, "Two b");
+        assert_eq!(subs.by_index(4).unwrap().text, "Three");
+
+        let overlapping = vec![SubLine::new(0, "".to_owned(), Timestamp::new(0, 0, 3, 0), Timestamp::new(0, 0, 5, 0))];
+        assert!(subs.range_mut(1..3).unwrap().replace(overlapping).is_err());
+    }
+}
@@ -0,0 +1,170 @@
+//! Rasterizing of shown lines for thumbnails and previews, a plain substitute for libass.
+
+use ab_glyph::{point, Font, FontArc, PxScale, ScaleFont};
+use image::{Rgba, RgbaImage};
+
+use crate::timestamp::Timestamp;
+use crate::subtitles::Subtitles;
+use crate::color::Color;
+use crate::utils;
+
+/// Look of lines drawn by ```frame```.
+#[derive(Debug, Clone)]
+pub struct FrameStyle {
+    pub font: FontArc,
+    /// Font size in pixels.
+    pub size: f32,
+    pub color: Color,
+    pub outline_color: Color,
+    /// Width of the outline in pixels, ```0``` for none.
+    pub outline: u32,
+    /// Distance between the bottom row and the bottom edge of the canvas in pixels.
+    pub margin: u32,
+}
+
+impl FrameStyle {
+    /// White text with a 2 pixels wide black outline, 5% of the height from the bottom
+    /// of a 1080 pixels high canvas.
+    pub fn new(font: FontArc, size: f32) -> FrameStyle {
+        FrameStyle {
+            font,
+            size,
+            color: Color::rgb(255, 255, 255),
+            outline_color: Color::rgb(0, 0, 0),
+            outline: 2,
+            margin: 54,
+        }
+    }
+}
+
+/// Draws lines shown at ```time``` (exclusive end) on a transparent canvas of
+/// ```canvas_size``` (width and height in pixels). Tags are stripped and every row
+/// is centered horizontally, rows of all shown lines are stacked at the bottom
+/// with the last line lowest. Rows wider than the canvas are cut.
+pub fn frame(subs: &Subtitles, time: Timestamp, canvas_size: (u32, u32), style: &FrameStyle) -> RgbaImage {
+    let (width, height) = canvas_size;
+    let mut image = RgbaImage::new(width, height);
+    let rows: Vec<String> = subs.inner[..subs.position_by_start(time)]
+        .iter()
+        .filter(|line| line.end > time)
+        .flat_map(|line| utils::strip_tags(&line.text).lines().map(str::to_owned).collect::<Vec<_>>())
+        .collect();
+    if rows.is_empty() || width == 0 || height == 0 {
+        return image;
+    }
+
+    // Coverage of the text, then of the text with the outline around it
+    let mut text = vec![0f32; (width * height) as usize];
+    let font = style.font.as_scaled(PxScale::from(style.size));
+    let row_height = font.height() + font.line_gap();
+    let mut baseline = height as f32 - style.margin as f32 + font.descent();
+    for row in rows.iter().rev() {
+        let glyphs: Vec<_> = row.chars().map(|c| font.glyph_id(c)).collect();
+        let row_width: f32 = glyphs.iter().map(|&id| font.h_advance(id)).sum::<f32>() +
+                             glyphs.windows(2).map(|pair| font.kern(pair[0], pair[1])).sum::<f32>();
+        let mut x = ((width as f32 - row_width) / 2.0).round();
+        for (i, &id) in glyphs.iter().enumerate() {
+            if i > 0 {
+                x += font.kern(glyphs[i - 1], id);
+            }
+            let glyph = id.with_scale_and_position(font.scale(), point(x, baseline));
+            x += font.h_advance(id);
+            if let Some(outlined) = style.font.outline_glyph(glyph) {
+                let bounds = outlined.px_bounds();
+                outlined.draw(|gx, gy, coverage| {
+                    let (px, py) = (bounds.min.x as i64 + gx as i64, bounds.min.y as i64 + gy as i64);
+                    if px >= 0 && py >= 0 && (px as u32) < width && (py as u32) < height {
+                        let pixel = &mut text[(py as u32 * width + px as u32) as usize];
+                        *pixel = pixel.max(coverage.min(1.0));
+                    }
+                });
+            }
+        }
+        baseline -= row_height;
+    }
+
+    let outline = dilate(&text, width, height, style.outline);
+    for (i, pixel) in image.pixels_mut().enumerate() {
+        if style.outline > 0 {
+            blend(pixel, style.outline_color, outline[i]);
+        }
+        blend(pixel, style.color, text[i]);
+    }
+    image
+}
+
+/// Coverage grown by ```radius``` pixels in every direction.
+fn dilate(coverage: &[f32], width: u32, height: u32, radius: u32) -> Vec<f32> {
+    if radius == 0 {
+        return coverage.to_vec();
+    }
+    let (width, height, radius) = (width as i64, height as i64, radius as i64);
+    let mut result = vec![0f32; coverage.len()];
+    for y in 0..height {
+        for x in 0..width {
+            let value = coverage[(y * width + x) as usize];
+            if value == 0.0 {
+                continue;
+            }
+            for dy in -radius..radius + 1 {
+                for dx in -radius..radius + 1 {
+                    let (nx, ny) = (x + dx, y + dy);
+                    if dx * dx + dy * dy <= radius * radius && nx >= 0 && ny >= 0 && nx < width && ny < height {
+                        let pixel = &mut result[(ny * width + nx) as usize];
+                        *pixel = pixel.max(value);
+                    }
+                }
+            }
+        }
+    }
+    result
+}
+
+/// Puts ```color``` with ```coverage``` over the pixel.
+fn blend(pixel: &mut Rgba<u8>, color: Color, coverage: f32) {
+    let source = coverage * color.alpha as f32 / 255.0;
+    if source <= 0.0 {
+        return;
+    }
+    let destination = pixel[3] as f32 / 255.0 * (1.0 - source);
+    let alpha = source + destination;
+    let mix = |over: u8, under: u8| ((over as f32 * source + under as f32 * destination) / alpha).round() as u8;
+    *pixel = Rgba([mix(color.red, pixel[0]),
+                   mix(color.green, pixel[1]),
+                   mix(color.blue, pixel[2]),
+                   (alpha * 255.0).round() as u8]);
+}
+
+#[cfg(test)]
+mod frame_tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn frame() {
+        // No font is bundled, the test needs DejaVu Sans installed
+        let font = match fs::read("/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf") {
+            Ok(data) => FontArc::try_from_vec(data).unwrap(),
+            Err(_) => return,
+        };
+        let subs = subtitles![(0, 1000, "<i>Hello</i>"), (2000, 3000, "Bye")];
+        let style = FrameStyle::new(font, 40.0);
+
+        let image = super::frame(&subs, Timestamp::from_miliseconds(500), (320, 180), &style);
+        assert_eq!(image.dimensions(), (320, 180));
+        let drawn: Vec<(u32, u32)> = image.enumerate_pixels()
+            .filter(|&(_, _, pixel)| pixel[3] > 0)
+            .map(|(x, y, _)| (x, y))
+            .collect();
+        assert!(!drawn.is_empty());
+        // Centered at the bottom, above the margin
+        assert!(drawn.iter().all(|&(_, y)| y > 60 && y <= 180 - 54 + 2));
+        let (left, right) = (drawn.iter().map(|p| p.0).min().unwrap(), drawn.iter().map(|p| p.0).max().unwrap());
+        assert!((left as i64 + right as i64 - 320).abs() < 8);
+        assert!(image.pixels().any(|pixel| *pixel == Rgba([255, 255, 255, 255])));
+        assert!(image.pixels().any(|pixel| *pixel == Rgba([0, 0, 0, 255])));
+
+        let empty = super::frame(&subs, Timestamp::from_miliseconds(1000), (320, 180), &style);
+        assert!(empty.pixels().all(|pixel| pixel[3] == 0));
+    }
+}
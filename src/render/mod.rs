@@ -0,0 +1,189 @@
+//! Formatting of lines as text and, with the ```render``` feature, as images.
+
+use std::fmt::Write;
+
+use crate::timestamp::Timestamp;
+use crate::subline::SubLine;
+use crate::subtitles::Subtitles;
+
+#[cfg(feature = "render")]
+mod frame;
+mod terminal;
+
+#[cfg(feature = "render")]
+pub use self::frame::{frame, FrameStyle};
+pub use self::terminal::{terminal, TerminalOptions};
+
+/// Layout used by ```Subtitles::render```.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderStyle {
+    /// SubRip blocks, as written to files.
+    SubRip,
+    /// One line per line, e.g. ```#12 00:01:02,000→00:01:04,000 "text"```, for logs.
+    OneLine,
+}
+
+/// Index lines written by ```RenderStyle::SubRip```.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Numbering {
+    /// Indices of the lines as they are.
+    #[default]
+    Preserve,
+    /// Lines are numbered consecutively from the given number, usually ```0``` or ```1```.
+    From(u32),
+    /// No index lines, blocks start with the timing, as some ingest systems accept.
+    Omit,
+}
+
+/// Options of ```Subtitles::render```, by default output is the same as of ```Display```.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderOptions {
+    pub style: RenderStyle,
+    pub numbering: Numbering,
+    /// Use ```\n``` instead of ```\r\n```.
+    pub unix_newlines: bool,
+    /// Add empty lines after the last block, as ```Display``` does.
+    pub trailing_padding: bool,
+    /// Texts longer than this number of characters are cut and end with ```…```
+    /// in ```RenderStyle::OneLine```.
+    pub max_text_length: usize,
+}
+
+impl Default for RenderOptions {
+    fn default() -> RenderOptions {
+        RenderOptions {
+            style: RenderStyle::SubRip,
+            numbering: Numbering::Preserve,
+            unix_newlines: false,
+            trailing_padding: true,
+            max_text_length: 40,
+        }
+    }
+}
+
+impl RenderOptions {
+    /// SubRip with unix newlines and no trailing padding.
+    pub fn compact() -> RenderOptions {
+        RenderOptions {
+            unix_newlines: true,
+            trailing_padding: false,
+            ..RenderOptions::default()
+        }
+    }
+
+    /// One line per line with unix newlines.
+    pub fn one_line() -> RenderOptions {
+        RenderOptions {
+            style: RenderStyle::OneLine,
+            ..RenderOptions::compact()
+        }
+    }
+}
+
+impl Subtitles {
+    /// Formats ```Subtitles``` with given options.
+    pub fn render(&self, options: &RenderOptions) -> String {
+        let newline = if options.unix_newlines { "\n" } else { "\r\n" };
+        let mut result = String::new();
+
+        for (i, line) in self.inner.iter().enumerate() {
+            match options.style {
+                RenderStyle::SubRip => {
+                    if i > 0 {
+                        result.push_str(newline);
+                    }
+                    match options.numbering {
+                        Numbering::Preserve => write!(result, "{}{}", line.index, newline).unwrap(),
+                        Numbering::From(first) => {
+                            write!(result, "{}{}", first.saturating_add(i as u32), newline).unwrap()
+                        }
+                        Numbering::Omit => {}
+                    }
+                    write!(result,
+                           "{} --> {}{}{}{}",
+                           timestamp(line.start),
+                           timestamp(line.end),
+                           newline,
+                           line.text.replace("\r\n", newline),
+                           newline)
+                        .unwrap();
+                }
+                RenderStyle::OneLine => {
+                    result.push_str(&one_line(line, options.max_text_length));
+                    result.push_str(newline);
+                }
+            }
+        }
+
+        if options.trailing_padding && options.style == RenderStyle::SubRip {
+            for _ in 0..if self.inner.is_empty() { 2 } else { 3 } {
+                result.push_str(newline);
+            }
+        }
+        result
+    }
+}
+
+fn one_line(line: &SubLine, max_text_length: usize) -> String {
+    let text = line.text.lines().collect::<Vec<_>>().join(" | ");
+    let text = if text.chars().count() > max_text_length {
+        let mut cut: String = text.chars().take(max_text_length).collect();
+        cut.push('…');
+        cut
+    } else {
+        text
+    };
+    format!("#{} {}→{} \"{}\"",
+            line.index,
+            timestamp(line.start),
+            timestamp(line.end),
+            text)
+}
+
+fn timestamp(time: Timestamp) -> String {
+    format!("{:02}:{:02}:{:02},{:03}",
+            time.hours,
+            time.minutes,
+            time.seconds,
+            time.miliseconds)
+}
+
+#[cfg(test)]
+mod render_tests {
+    use super::*;
+    use crate::utils;
+
+    #[test]
+    fn render() {
+        let subs = utils::subs_from_texts(&["One", "Two\r\nlines"]);
+        assert_eq!(subs.render(&RenderOptions::default()), subs.to_string());
+        assert_eq!(Subtitles::default().render(&RenderOptions::default()),
+                   Subtitles::default().to_string());
+
+        assert_eq!(subs.render(&RenderOptions::compact()),
+                   "1\n00:00:00,000 --> 00:00:01,000\nOne\n\n\
+                    2\n00:00:02,000 --> 00:00:03,000\nTwo\nlines\n");
+    }
+
+    #[test]
+    fn numbering() {
+        let mut subs = utils::subs_from_texts(&["One", "Two"]);
+        subs.inner[1].index = 7;
+        let render = |numbering| subs.render(&RenderOptions { numbering, ..RenderOptions::compact() });
+        assert_eq!(render(Numbering::Preserve),
+                   "1\n00:00:00,000 --> 00:00:01,000\nOne\n\n7\n00:00:02,000 --> 00:00:03,000\nTwo\n");
+        assert_eq!(render(Numbering::From(0)),
+                   "0\n00:00:00,000 --> 00:00:01,000\nOne\n\n1\n00:00:02,000 --> 00:00:03,000\nTwo\n");
+        assert_eq!(render(Numbering::Omit),
+                   "00:00:00,000 --> 00:00:01,000\nOne\n\n00:00:02,000 --> 00:00:03,000\nTwo\n");
+    }
+
+    #[test]
+    fn render_one_line() {
+        let subs = utils::subs_from_texts(&["One", "Two\r\nlines"]);
+        let options = RenderOptions { max_text_length: 6, ..RenderOptions::one_line() };
+        assert_eq!(subs.render(&options),
+                   "#1 00:00:00,000→00:00:01,000 \"One\"\n\
+                    #2 00:00:02,000→00:00:03,000 \"Two | …\"\n");
+    }
+}
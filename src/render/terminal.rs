@@ -0,0 +1,134 @@
+//! Real time playback of lines in a terminal.
+
+use std::io::{self, Write};
+use std::thread;
+use std::time::Duration;
+
+use crate::timestamp::Timestamp;
+use crate::subtitles::Subtitles;
+use crate::tags::{self, Token};
+
+const CLEAR: &str = "\x1b[2J\x1b[H";
+
+/// Options of ```terminal```.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TerminalOptions {
+    /// Playback starts at this time of the subtitles.
+    pub start: Timestamp,
+    /// Playback speed, e.g. ```2.0``` plays twice as fast. Must be positive.
+    pub speed: f64,
+    /// Show italic and bold tags as ANSI styles, otherwise tags are stripped.
+    pub styles: bool,
+}
+
+impl Default for TerminalOptions {
+    fn default() -> TerminalOptions {
+        TerminalOptions {
+            start: Timestamp::default(),
+            speed: 1.0,
+            styles: true,
+        }
+    }
+}
+
+/// Plays lines to stdout in real time, blocking until the last line ends.
+/// The screen is cleared and lines shown at the moment are drawn at the start
+/// and redrawn whenever a line starts or ends.
+pub fn terminal(subs: &Subtitles, options: &TerminalOptions) -> Result<(), io::Error> {
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    play(subs, options, &mut out, thread::sleep)
+}
+
+/// Plays lines to ```out```, calling ```sleep``` with the time to wait before every redraw.
+fn play<W, S>(subs: &Subtitles, options: &TerminalOptions, out: &mut W, mut sleep: S) -> Result<(), io::Error>
+    where W: Write,
+          S: FnMut(Duration)
+{
+    if !(options.speed > 0.0 && options.speed.is_finite()) {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "Playback speed must be positive"));
+    }
+    let mut events: Vec<Timestamp> = subs.inner
+        .iter()
+        .flat_map(|line| vec![line.start, line.end])
+        .filter(|&time| time >= options.start)
+        .chain(Some(options.start))
+        .collect();
+    events.sort();
+    events.dedup();
+
+    let mut now = options.start;
+    let mut shown: Option<Vec<usize>> = None;
+    for time in events {
+        let wait = (time.total_miliseconds() - now.total_miliseconds()) as f64 / options.speed;
+        if wait > 0.0 {
+            sleep(Duration::from_micros((wait * 1000.0).round() as u64));
+        }
+        now = time;
+
+        let current: Vec<usize> = (0..subs.inner.len())
+            .filter(|&i| subs.inner[i].start <= time && subs.inner[i].end > time)
+            .collect();
+        if shown.as_ref() == Some(&current) {
+            continue;
+        }
+        out.write_all(CLEAR.as_bytes())?;
+        for &i in &current {
+            writeln!(out, "{}", styled(&subs.inner[i].text, options.styles))?;
+        }
+        out.flush()?;
+        shown = Some(current);
+    }
+    Ok(())
+}
+
+/// Text with ```\n``` newlines and tags replaced by ANSI styles or stripped.
+fn styled(text: &str, styles: bool) -> String {
+    let mut result = String::with_capacity(text.len());
+    for token in tags::tokenize(text) {
+        match token {
+            Token::Text(text) => result.push_str(text),
+            Token::Open { ref name, .. } if styles && name == "i" => result.push_str("\x1b[3m"),
+            Token::Close { ref name, .. } if styles && name == "i" => result.push_str("\x1b[23m"),
+            Token::Open { ref name, .. } if styles && name == "b" => result.push_str("\x1b[1m"),
+            Token::Close { ref name, .. } if styles && name == "b" => result.push_str("\x1b[22m"),
+            _ => {}
+        }
+    }
+    if styles {
+        result.push_str("\x1b[0m");
+    }
+    result.replace("\r\n", "\n")
+}
+
+#[cfg(test)]
+mod terminal_tests {
+    use super::*;
+
+    #[test]
+    fn play() {
+        let subs = subtitles![(1000, 2000, "<i>One</i>"), (2000, 3000, "Two\r\n<b>lines</b>"), (5000, 6000, "Three")];
+        let mut out = Vec::new();
+        let mut waits = Vec::new();
+        let options = TerminalOptions { start: Timestamp::from_miliseconds(500), speed: 2.0, ..TerminalOptions::default() };
+        super::play(&subs, &options, &mut out, |wait| waits.push(wait.as_millis())).unwrap();
+
+        assert_eq!(waits, vec![250, 500, 500, 1000, 500]);
+        let screens: Vec<String> = String::from_utf8(out).unwrap().split(CLEAR).skip(1).map(str::to_owned).collect();
+        assert_eq!(screens,
+                   vec!["",
+                        "\x1b[3mOne\x1b[23m\x1b[0m\n",
+                        "Two\n\x1b[1mlines\x1b[22m\x1b[0m\n",
+                        "",
+                        "Three\x1b[0m\n",
+                        ""]);
+
+        let mut out = Vec::new();
+        let options = TerminalOptions { styles: false, start: Timestamp::from_miliseconds(5500), ..TerminalOptions::default() };
+        super::play(&subs, &options, &mut out, |_| {}).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), format!("{}Three\n{}", CLEAR, CLEAR));
+
+        let options = TerminalOptions { speed: 0.0, ..TerminalOptions::default() };
+        assert!(super::play(&subs, &options, &mut Vec::new(), |_| {}).is_err());
+    }
+}
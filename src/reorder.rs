@@ -0,0 +1,187 @@
+use crate::subtitles::Subtitles;
+use crate::subline::SubLine;
+use crate::error::StructureError;
+
+/// How ```Subtitles::move_cue``` and ```Subtitles::swap``` keep the track consistent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReorderMode {
+    /// Lines are moved with their timing and renumbered.
+    /// Use it when timing was edited and lines are out of order,
+    /// or call ```Subtitles::sort_by_time``` afterwards.
+    Renumber,
+    /// Lines are moved with their texts, ids, comments, settings and words,
+    /// but timing stays at the same positions, so lines stay sorted by time.
+    KeepTiming,
+}
+
+impl Subtitles {
+    /// Moves line with index ```from``` so it gets index ```to```.
+    pub fn move_cue(&mut self, from: usize, to: usize, mode: ReorderMode) -> Result<(), StructureError> {
+        let from = self.index_position(from)?;
+        let to = self.index_position(to)?;
+
+        let timings: Vec<_> = self.inner.iter().map(|line| (line.start, line.end)).collect();
+        let line = self.inner.remove(from);
+        self.inner.insert(to, line);
+
+        if mode == ReorderMode::KeepTiming {
+            for (line, (start, end)) in self.inner.iter_mut().zip(timings) {
+                line.start = start;
+                line.end = end;
+            }
+        }
+        self.renumber();
+        Ok(())
+    }
+
+    /// Swaps lines with indices ```a``` and ```b```.
+    pub fn swap(&mut self, a: usize, b: usize, mode: ReorderMode) -> Result<(), StructureError> {
+        let a = self.index_position(a)?;
+        let b = self.index_position(b)?;
+
+        match mode {
+            ReorderMode::Renumber => {
+                self.inner.swap(a, b);
+                self.renumber();
+            }
+            ReorderMode::KeepTiming => {
+                let timing = |line: &SubLine| (line.index, line.start, line.end);
+                let (timing_a, timing_b) = (timing(&self.inner[a]), timing(&self.inner[b]));
+                self.inner.swap(a, b);
+                for (position, (index, start, end)) in [(a, timing_a), (b, timing_b)] {
+                    let line = &mut self.inner[position];
+                    line.index = index;
+                    line.start = start;
+                    line.end = end;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Sorts lines by start (then end) time, keeping the order of lines
+    /// with equal timing, and renumbers them.
+    pub fn sort_by_time(&mut self) {
+        self.inner.sort_by_key(|line| (line.start, line.end));
+        self.renumber();
+    }
+
+    /// Restores the invariants lookups such as ```by_index``` and ```by_time```
+    /// rely on after lines were edited directly, e.g. while iterating over them.
+    ///
+    /// Lines are sorted by start time, lines starting at the same time
+    /// are ordered by end time, then by their old index, and renumbered.
+    pub fn rebuild(&mut self) -> RebuildReport {
+        let mut lines: Vec<_> = self.inner.drain(..).enumerate().collect();
+        lines.sort_by_key(|(_, line)| (line.start, line.end, line.index));
+
+        let mut report = RebuildReport::default();
+        for (position, (old_position, mut line)) in lines.into_iter().enumerate() {
+            let index = position as u32 + 1;
+            if position != old_position {
+                report.moved.push(index);
+            }
+            if line.index != index {
+                report.renumbered.push((line.index, index));
+                line.index = index;
+            }
+            self.inner.push(line);
+        }
+        report
+    }
+}
+
+/// Changes made by ```Subtitles::rebuild```.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RebuildReport {
+    /// New indices of lines which changed their position.
+    pub moved: Vec<u32>,
+    /// Old and new indices of renumbered lines.
+    pub renumbered: Vec<(u32, u32)>,
+}
+
+impl RebuildReport {
+    /// Returns ```true``` if nothing was changed.
+    pub fn is_empty(&self) -> bool {
+        self.moved.is_empty() && self.renumbered.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod reorder_tests {
+    use super::*;
+    use crate::timestamp::Timestamp;
+    use crate::subline::Word;
+    use crate::utils;
+
+    fn texts(subs: &Subtitles) -> Vec<(u32, u32, &str)> {
+        subs.inner.iter().map(|line| (line.index, line.start.seconds, &*line.text)).collect()
+    }
+
+    #[test]
+    fn move_cue() {
+        let mut subs = utils::subs_from_texts(&["One", "Two", "Three"]);
+        subs.move_cue(3, 1, ReorderMode::KeepTiming).unwrap();
+        assert_eq!(texts(&subs), vec![(1, 0, "Three"), (2, 2, "One"), (3, 4, "Two")]);
+
+        subs.move_cue(1, 3, ReorderMode::Renumber).unwrap();
+        assert_eq!(texts(&subs), vec![(1, 2, "One"), (2, 4, "Two"), (3, 0, "Three")]);
+
+        subs.sort_by_time();
+        assert_eq!(texts(&subs), vec![(1, 0, "Three"), (2, 2, "One"), (3, 4, "Two")]);
+
+        assert_eq!(subs.move_cue(1, 4, ReorderMode::Renumber),
+                   Err(StructureError::NotFound { index: 4 }));
+    }
+
+    #[test]
+    fn swap() {
+        let mut subs = utils::subs_from_texts(&["One", "Two", "Three"]);
+        subs.inner[0].id = Some("one".to_owned());
+        subs.inner[0].comment = Some("Check".to_owned());
+        subs.inner[0].words = vec![Word {
+                                       start: Timestamp::new(0, 0, 0, 0),
+                                       end: Timestamp::new(0, 0, 1, 0),
+                                       text: "One".to_owned(),
+                                   }];
+        let mut moved = subs.clone();
+        subs.swap(1, 3, ReorderMode::KeepTiming).unwrap();
+        assert_eq!(texts(&subs), vec![(1, 0, "Three"), (2, 2, "Two"), (3, 4, "One")]);
+        assert_eq!(subs.inner[2].id.as_deref(), Some("one"));
+        assert_eq!(subs.inner[2].comment.as_deref(), Some("Check"));
+        assert_eq!(subs.inner[2].words.len(), 1);
+        assert_eq!((subs.inner[0].id.as_ref(), subs.inner[0].comment.as_ref()), (None, None));
+        assert!(subs.inner[0].words.is_empty());
+
+        // Same as moving the lines one by one
+        moved.move_cue(1, 3, ReorderMode::KeepTiming).unwrap();
+        moved.move_cue(2, 1, ReorderMode::KeepTiming).unwrap();
+        assert_eq!(moved, subs);
+
+        subs.inner[0].start = Timestamp::new(0, 0, 5, 0);
+        subs.inner[0].end = Timestamp::new(0, 0, 6, 0);
+        subs.swap(1, 3, ReorderMode::Renumber).unwrap();
+        assert_eq!(texts(&subs), vec![(1, 4, "One"), (2, 2, "Two"), (3, 5, "Three")]);
+    }
+
+    #[test]
+    fn rebuild() {
+        let mut subs = utils::subs_from_texts(&["One", "Two", "Three", "Four"]);
+        assert!(subs.rebuild().is_empty());
+
+        for line in &mut subs {
+            if line.text == "Four" {
+                line.start = Timestamp::new(0, 0, 2, 0);
+                line.end = Timestamp::new(0, 0, 3, 0);
+            }
+        }
+        subs.inner[1].index = 7;
+        subs.inner.swap(0, 2);
+
+        let report = subs.rebuild();
+        assert_eq!(texts(&subs), vec![(1, 0, "One"), (2, 2, "Four"), (3, 2, "Two"), (4, 4, "Three")]);
+        assert_eq!(report.moved, vec![1, 2, 3, 4]);
+        assert_eq!(report.renumbered, vec![(4, 2), (7, 3), (3, 4)]);
+        assert!(subs.rebuild().is_empty());
+    }
+}
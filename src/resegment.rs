@@ -0,0 +1,177 @@
+use crate::timestamp::Timestamp;
+use crate::subline::SubLine;
+use crate::subtitles::Subtitles;
+
+/// Limits for ```Subtitles::resegment_sentences```.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SegmentLimits {
+    /// Longest text of a line in characters, counting spaces.
+    /// Sentences longer than that are split at clauses or, if there are none, between words.
+    pub max_chars: usize,
+    /// Lines further apart than that are never merged.
+    pub max_gap: Timestamp,
+}
+
+impl Default for SegmentLimits {
+    fn default() -> SegmentLimits {
+        SegmentLimits {
+            max_chars: 84,
+            max_gap: Timestamp::new(0, 0, 1, 0),
+        }
+    }
+}
+
+/// Word of the merged text with its estimated timing in miliseconds.
+struct TimedWord<'a> {
+    text: &'a str,
+    start: u64,
+    end: u64,
+}
+
+impl Subtitles {
+    /// Merges text of close lines and splits it again so that lines end at sentence ends,
+    /// e.g. for speech recognition output cut in the middle of sentences.
+    /// Timing of a line is spread over its words proportionally to their length,
+    /// new lines get the timing of their words. Rows are joined with spaces,
+    /// comments, settings and word timing of merged lines are dropped.
+    /// Lines are renumbered.
+    pub fn resegment_sentences(&mut self, limits: &SegmentLimits) {
+        let max_gap = limits.max_gap.total_miliseconds();
+        let mut lines = Vec::with_capacity(self.inner.len());
+
+        let mut run_start = 0;
+        for i in 1..self.inner.len() + 1 {
+            let split = match self.inner.get(i) {
+                Some(next) => {
+                    let end = self.inner[i - 1].end.total_miliseconds();
+                    next.start.total_miliseconds() > end + max_gap
+                }
+                None => true,
+            };
+            if split {
+                segment(&self.inner[run_start..i], limits.max_chars, &mut lines);
+                run_start = i;
+            }
+        }
+
+        self.inner = lines;
+        self.renumber();
+    }
+}
+
+/// Splits lines of a run into sentences, pushing them to ```lines```.
+fn segment(run: &[SubLine], max_chars: usize, lines: &mut Vec<SubLine>) {
+    let words = timed_words(run);
+    lines.extend(split(&words, max_chars).iter().map(|words| line(words)));
+}
+
+/// Splits text into sentences no longer than ```max_chars```, see ```Subtitles::resegment_sentences```.
+pub(crate) fn sentences(text: &str, max_chars: usize) -> Vec<String> {
+    let words: Vec<TimedWord> = text.split_whitespace()
+        .map(|text| {
+            TimedWord {
+                text,
+                start: 0,
+                end: 0,
+            }
+        })
+        .collect();
+    split(&words, max_chars).iter().map(|words| line(words).text).collect()
+}
+
+fn split<'a, 'b>(words: &'b [TimedWord<'a>], max_chars: usize) -> Vec<Vec<&'b TimedWord<'a>>> {
+    let mut result = Vec::new();
+    let mut current: Vec<&TimedWord> = Vec::new();
+
+    for word in words {
+        while !current.is_empty() && length(&current) + 1 + word.text.chars().count() > max_chars {
+            // Keep the text up to the last clause, otherwise everything
+            let cut = current.iter()
+                .rposition(|word| ends_clause(word.text))
+                .map(|position| position + 1)
+                .unwrap_or(current.len());
+            let rest = current.split_off(cut);
+            result.push(current);
+            current = rest;
+        }
+        current.push(word);
+        if ends_sentence(word.text) {
+            result.push(::std::mem::take(&mut current));
+        }
+    }
+    if !current.is_empty() {
+        result.push(current);
+    }
+    result
+}
+
+fn timed_words(run: &[SubLine]) -> Vec<TimedWord<'_>> {
+    let mut words = Vec::new();
+    for line in run {
+        let texts: Vec<&str> = line.text.split_whitespace().collect();
+        // Characters of the text with single spaces between words
+        let total = texts.iter().map(|text| text.chars().count() + 1).sum::<usize>().saturating_sub(1).max(1);
+        let time = |position: usize| Timestamp::lerp(line.start, line.end, position as f64 / total as f64);
+
+        let mut offset = 0;
+        for text in texts {
+            let length = text.chars().count();
+            words.push(TimedWord {
+                text,
+                start: time(offset).total_miliseconds(),
+                end: time(offset + length).total_miliseconds(),
+            });
+            offset += length + 1;
+        }
+    }
+    words
+}
+
+fn length(words: &[&TimedWord]) -> usize {
+    words.iter().map(|word| word.text.chars().count()).sum::<usize>() + words.len().saturating_sub(1)
+}
+
+fn line(words: &[&TimedWord]) -> SubLine {
+    let text = words.iter().map(|word| word.text).collect::<Vec<_>>().join(" ");
+    let start = words.first().map(|word| word.start).unwrap_or(0);
+    let end = words.last().map(|word| word.end).unwrap_or(0).max(start);
+    SubLine::new(0, text, Timestamp::from_miliseconds(start), Timestamp::from_miliseconds(end))
+}
+
+fn ends_sentence(word: &str) -> bool {
+    word.trim_end_matches(['"', '\'', ')', '»', '”']).ends_with(['.', '?', '!', '…'])
+}
+
+fn ends_clause(word: &str) -> bool {
+    ends_sentence(word) || word.ends_with([',', ';', ':', '—', '–'])
+}
+
+#[cfg(test)]
+mod resegment_tests {
+    use super::*;
+
+    fn texts(subs: &Subtitles) -> Vec<(u32, &str, u64, u64)> {
+        subs.inner
+            .iter()
+            .map(|line| (line.index, &*line.text, line.start.total_miliseconds(), line.end.total_miliseconds()))
+            .collect()
+    }
+
+    #[test]
+    fn resegment_sentences() {
+        let mut subs = subtitles![(0, 1500, "So we went"),
+                                  (1500, 2500, "home. Then"),
+                                  (2500, 4000, "we slept."),
+                                  (10000, 11000, "Far away")];
+        subs.resegment_sentences(&SegmentLimits::default());
+        assert_eq!(texts(&subs),
+                   vec![(1, "So we went home.", 0, 2000),
+                        (2, "Then we slept.", 2100, 4000),
+                        (3, "Far away", 10000, 11000)]);
+
+        let mut subs = subtitles![(0, 4000, "One, two, three four five six")];
+        subs.resegment_sentences(&SegmentLimits { max_chars: 16, ..SegmentLimits::default() });
+        let split: Vec<_> = texts(&subs).into_iter().map(|(_, text, _, _)| text).collect();
+        assert_eq!(split, vec!["One, two,", "three four five", "six"]);
+    }
+}
@@ -0,0 +1,122 @@
+use crate::timestamp::Timestamp;
+use crate::subline::SubLine;
+use crate::subtitles::Subtitles;
+
+struct Row {
+    text: String,
+    start: Timestamp,
+    end: Timestamp,
+}
+
+impl Subtitles {
+    /// Converts roll-up captions (every line repeats the rows of the previous one
+    /// and adds a new or grown row, as CEA-608 captions imported from SCC)
+    /// into pop-on lines, one line per caption row.
+    ///
+    /// Every row is shown from the moment its first part appeared until the next row
+    /// appears (or until it scrolled off the screen, if that happened earlier).
+    /// Rows which would end before they start, e.g. from unsorted lines, end at their start.
+    pub fn rollup_to_popon(&mut self) {
+        let mut active: Vec<Row> = Vec::new();
+        let mut rows: Vec<Row> = Vec::new();
+
+        for line in &self.inner {
+            let mut matched = vec![false; active.len()];
+
+            for text in line.text.lines().map(|row| row.trim()).filter(|row| !row.is_empty()) {
+                let continued = active.iter()
+                    .enumerate()
+                    .position(|(i, row)| !matched[i] && text.starts_with(&*row.text));
+                match continued {
+                    Some(i) => {
+                        matched[i] = true;
+                        active[i].text = text.to_owned();
+                        active[i].end = line.end;
+                    }
+                    None => {
+                        matched.push(true);
+                        active.push(Row {
+                            text: text.to_owned(),
+                            start: line.start,
+                            end: line.end,
+                        });
+                    }
+                }
+            }
+
+            // Rows that are not shown anymore have scrolled off
+            let mut still_active = Vec::with_capacity(active.len());
+            for (row, matched) in active.into_iter().zip(matched) {
+                if matched {
+                    still_active.push(row);
+                } else {
+                    rows.push(row);
+                }
+            }
+            active = still_active;
+        }
+        rows.extend(active);
+        rows.sort_by_key(|row| row.start);
+
+        let mut result = Vec::with_capacity(rows.len());
+        for (i, row) in rows.iter().enumerate() {
+            let mut end = row.end.max(row.start);
+            if let Some(next) = rows.get(i + 1) {
+                if next.start > row.start && next.start < end {
+                    end = next.start;
+                }
+            }
+            result.push(SubLine::new(i as u32 + 1, row.text.clone(), row.start, end));
+        }
+        self.inner = result;
+    }
+}
+
+#[cfg(test)]
+mod rollup_tests {
+    use crate::timestamp::Timestamp;
+    use crate::subline::SubLine;
+    use crate::subtitles::Subtitles;
+
+    #[test]
+    fn rollup_to_popon() {
+        let captions = [(0, 1, "THE TRIAL"),
+                        (1, 2, "THE TRIAL WILL BE"),
+                        (2, 4, "THE TRIAL WILL BE\r\nGETTING UNDER"),
+                        (4, 6, "THE TRIAL WILL BE\r\nGETTING UNDER WAY SOON."),
+                        (6, 9, "GETTING UNDER WAY SOON.\r\nYOU GOT MY MONEY?")];
+        let mut subs = Subtitles::default();
+        for (i, &(start, end, text)) in captions.iter().enumerate() {
+            subs.try_push(SubLine::new(i as u32 + 1,
+                                       text.to_owned(),
+                                       Timestamp::new(0, 0, start, 0),
+                                       Timestamp::new(0, 0, end, 0)))
+                .unwrap();
+        }
+
+        subs.rollup_to_popon();
+
+        let lines: Vec<(u32, u32, u32, &str)> = subs.inner
+            .iter()
+            .map(|line| (line.index, line.start.seconds, line.end.seconds, &*line.text))
+            .collect();
+        assert_eq!(lines,
+                   vec![(1, 0, 2, "THE TRIAL WILL BE"),
+                        (2, 2, 6, "GETTING UNDER WAY SOON."),
+                        (3, 6, 9, "YOU GOT MY MONEY?")]);
+    }
+
+    #[test]
+    fn unsorted() {
+        let line = |index, text: &str, start, end| {
+            SubLine::new(index, text.to_owned(), Timestamp::new(0, 0, start, 0), Timestamp::new(0, 0, end, 0))
+        };
+        // The second line continues the first one, but ends before it started
+        let mut subs = Subtitles::from(vec![line(1, "ONE", 5, 6), line(2, "ONE TWO", 1, 2), line(3, "THREE", 3, 4)]);
+        subs.rollup_to_popon();
+        assert!(subs.inner.iter().all(|line| line.start <= line.end));
+        assert_eq!(subs.inner[0].text, "THREE");
+        assert_eq!(subs.inner[1].text, "ONE TWO");
+        assert_eq!(subs.inner[1].start, subs.inner[1].end);
+    }
+}
@@ -0,0 +1,161 @@
+use std::io::Error;
+
+use crate::subtitles::Subtitles;
+use crate::parse::ParseOptions;
+
+/// Kind of a ```Difference```, from the least to the most significant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DifferenceKind {
+    /// Only the newline style (```\n``` or ```\r\n```) differs.
+    Newline,
+    /// Spaces inside the line or empty lines differ.
+    Whitespace,
+    /// Index is written differently, e.g. ```007``` as ```7```.
+    Index,
+    /// Anything else, e.g. a byte order mark or changed text.
+    Other,
+}
+
+/// Differing line of the content and of its serialization.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Difference {
+    pub kind: DifferenceKind,
+    /// Line of the original content, for added lines the one they were added before.
+    pub line_number: usize,
+    /// Original line with its newline, empty if the line was added.
+    pub original: String,
+    /// Serialized line with its newline, empty if the line was removed.
+    pub serialized: String,
+}
+
+/// Result of ```Subtitles::roundtrip_check```.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RoundTripReport {
+    /// Content as written by ```Display```.
+    pub serialized: String,
+    /// Differences in the order of lines.
+    pub differences: Vec<Difference>,
+}
+
+impl RoundTripReport {
+    /// Returns ```true``` if the serialization is byte for byte the same as the content.
+    pub fn is_lossless(&self) -> bool {
+        self.differences.is_empty()
+    }
+
+    /// Number of differences of given kind.
+    pub fn count(&self, kind: DifferenceKind) -> usize {
+        self.differences.iter().filter(|difference| difference.kind == kind).count()
+    }
+}
+
+impl Subtitles {
+    /// Parses ```content``` strictly, writes it back with ```Display``` and compares
+    /// the result with ```content``` line by line, e.g. to prove that a corpus survives
+    /// processing without losses. Errors are the errors of ```Subtitles::parse```.
+    pub fn roundtrip_check(content: &str) -> Result<RoundTripReport, Error> {
+        let parsed = Subtitles::parse(content, &ParseOptions::default())?;
+        let serialized = parsed.subtitles.to_string();
+        let differences = compare(content, &serialized);
+        Ok(RoundTripReport {
+            serialized,
+            differences,
+        })
+    }
+}
+
+fn compare(content: &str, serialized: &str) -> Vec<Difference> {
+    let original: Vec<&str> = content.split_inclusive('\n').collect();
+    let written: Vec<&str> = serialized.split_inclusive('\n').collect();
+    let mut differences = Vec::new();
+    let (mut i, mut j) = (0, 0);
+
+    while i < original.len() || j < written.len() {
+        let (before, after) = (original.get(i).cloned(), written.get(j).cloned());
+        let difference = |kind, original: Option<&str>, serialized: Option<&str>| {
+            Difference {
+                kind,
+                line_number: i + 1,
+                original: original.unwrap_or("").to_owned(),
+                serialized: serialized.unwrap_or("").to_owned(),
+            }
+        };
+        match (before, after) {
+            (Some(before), Some(after)) if key(before) == key(after) => {
+                if before != after {
+                    differences.push(difference(kind(before, after), Some(before), Some(after)));
+                }
+                i += 1;
+                j += 1;
+            }
+            // Empty lines which were dropped or added, e.g. padding between blocks
+            (Some(before), _) if key(before).is_empty() => {
+                differences.push(difference(DifferenceKind::Whitespace, Some(before), None));
+                i += 1;
+            }
+            (_, Some(after)) if key(after).is_empty() => {
+                differences.push(difference(DifferenceKind::Whitespace, None, Some(after)));
+                j += 1;
+            }
+            _ => {
+                differences.push(difference(DifferenceKind::Other, before, after));
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    differences
+}
+
+/// Line without the newline and with single spaces, indices are equal whatever their digits.
+fn key(line: &str) -> String {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    if words.len() == 1 && words[0].parse::<u32>().is_ok() {
+        return "#".to_owned();
+    }
+    words.join(" ")
+}
+
+/// Kind of the difference between lines with the same ```key```.
+fn kind(before: &str, after: &str) -> DifferenceKind {
+    let (before, after) = (before.trim_end_matches(['\r', '\n']), after.trim_end_matches(['\r', '\n']));
+    if before == after {
+        DifferenceKind::Newline
+    } else if before.trim() != after.trim() && before.trim().parse::<u32>().is_ok() {
+        DifferenceKind::Index
+    } else {
+        DifferenceKind::Whitespace
+    }
+}
+
+#[cfg(test)]
+mod roundtrip_tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip_check() {
+        let lossless = "1\r\n00:00:01,000 --> 00:00:02,000\r\nOne\r\n\r\n\
+                        2\r\n00:00:03,000 --> 00:00:04,000\r\nTwo\r\n\r\n\r\n\r\n";
+        let report = Subtitles::roundtrip_check(lossless).unwrap();
+        assert!(report.is_lossless());
+        assert_eq!(report.serialized, lossless);
+
+        let content = "01\n00:00:01,000  -->  00:00:02,000\nOne\n\n\n\
+                       2\n00:00:03,000 --> 00:00:04,000\nTwo\n";
+        let report = Subtitles::roundtrip_check(content).unwrap();
+        assert!(!report.is_lossless());
+        let first: Vec<_> = report.differences[..3].iter().map(|difference| difference.kind).collect();
+        assert_eq!(first, vec![DifferenceKind::Index, DifferenceKind::Whitespace, DifferenceKind::Newline]);
+        assert_eq!(report.differences[0].original, "01\n");
+        assert_eq!(report.differences[0].serialized, "1\r\n");
+        assert_eq!(report.count(DifferenceKind::Index), 1);
+        // Spaced arrow, one of two empty lines is dropped, padding is added
+        assert_eq!(report.count(DifferenceKind::Whitespace), 1 + 1 + 3);
+        assert_eq!(report.count(DifferenceKind::Newline), 5);
+        assert_eq!(report.count(DifferenceKind::Other), 0);
+        let dropped = report.differences.iter().find(|difference| difference.serialized.is_empty()).unwrap();
+        assert_eq!(dropped.line_number, 5);
+
+        assert!(Subtitles::roundtrip_check("broken").is_err());
+    }
+}
@@ -0,0 +1,172 @@
+//! JSON Schema of ```Subtitles``` serialized to JSON with the ```serde``` feature.
+
+/// Returns JSON Schema (draft 2020-12) describing ```Subtitles``` serialized
+/// to JSON, so consumers can validate payloads. ```$id``` contains the crate version.
+///
+/// Optional fields are serialized as ```null```, except ```SubLine```'s
+/// ```id```, ```comment```, ```settings``` and ```words```, which are omitted when empty.
+pub fn json_schema() -> &'static str {
+    SCHEMA
+}
+
+const SCHEMA: &str = concat!(r##"{
+  "$schema": "https://json-schema.org/draft/2020-12/schema",
+  "$id": "https://crates.io/crates/srt/"##,
+                             env!("CARGO_PKG_VERSION"),
+                             r##"/subtitles.schema.json",
+  "title": "Subtitles",
+  "type": "object",
+  "required": ["inner", "metadata"],
+  "additionalProperties": false,
+  "properties": {
+    "inner": { "type": "array", "items": { "$ref": "#/$defs/SubLine" } },
+    "metadata": { "$ref": "#/$defs/Metadata" }
+  },
+  "$defs": {
+    "Timestamp": {
+      "type": "object",
+      "required": ["hours", "minutes", "seconds", "miliseconds"],
+      "additionalProperties": false,
+      "properties": {
+        "hours": { "type": "integer", "minimum": 0, "maximum": 4294967295 },
+        "minutes": { "type": "integer", "minimum": 0, "maximum": 59 },
+        "seconds": { "type": "integer", "minimum": 0, "maximum": 59 },
+        "miliseconds": { "type": "integer", "minimum": 0, "maximum": 999 }
+      }
+    },
+    "SubLine": {
+      "type": "object",
+      "required": ["index", "start", "end", "text"],
+      "additionalProperties": false,
+      "properties": {
+        "index": { "type": "integer", "minimum": 0, "maximum": 4294967295 },
+        "id": { "type": ["string", "null"] },
+        "start": { "$ref": "#/$defs/Timestamp" },
+        "end": { "$ref": "#/$defs/Timestamp" },
+        "text": { "type": "string" },
+        "comment": { "type": ["string", "null"] },
+        "settings": { "$ref": "#/$defs/CueSettings" },
+        "words": { "type": "array", "items": { "$ref": "#/$defs/Word" } }
+      }
+    },
+    "Word": {
+      "type": "object",
+      "required": ["start", "end", "text"],
+      "additionalProperties": false,
+      "properties": {
+        "start": { "$ref": "#/$defs/Timestamp" },
+        "end": { "$ref": "#/$defs/Timestamp" },
+        "text": { "type": "string" }
+      }
+    },
+    "Percent": {
+      "description": "Hundredths of percent.",
+      "type": "integer",
+      "minimum": 0,
+      "maximum": 10000
+    },
+    "BoxAlign": { "enum": ["Start", "Center", "End", null] },
+    "CueSettings": {
+      "type": "object",
+      "required": ["vertical", "line", "position", "size", "align"],
+      "additionalProperties": false,
+      "properties": {
+        "vertical": { "enum": ["RightToLeft", "LeftToRight", null] },
+        "line": {
+          "oneOf": [
+            { "type": "null" },
+            {
+              "type": "array",
+              "prefixItems": [
+                {
+                  "oneOf": [
+                    {
+                      "type": "object",
+                      "required": ["Number"],
+                      "additionalProperties": false,
+                      "properties": { "Number": { "type": "integer" } }
+                    },
+                    {
+                      "type": "object",
+                      "required": ["Percent"],
+                      "additionalProperties": false,
+                      "properties": { "Percent": { "$ref": "#/$defs/Percent" } }
+                    }
+                  ]
+                },
+                { "$ref": "#/$defs/BoxAlign" }
+              ],
+              "minItems": 2,
+              "maxItems": 2
+            }
+          ]
+        },
+        "position": {
+          "oneOf": [
+            { "type": "null" },
+            {
+              "type": "array",
+              "prefixItems": [{ "$ref": "#/$defs/Percent" }, { "$ref": "#/$defs/BoxAlign" }],
+              "minItems": 2,
+              "maxItems": 2
+            }
+          ]
+        },
+        "size": { "oneOf": [{ "type": "null" }, { "$ref": "#/$defs/Percent" }] },
+        "align": { "enum": ["Start", "Center", "End", "Left", "Right", null] }
+      }
+    },
+    "FrameRate": {
+      "type": "object",
+      "required": ["numerator", "denominator"],
+      "additionalProperties": false,
+      "properties": {
+        "numerator": { "type": "integer", "minimum": 0, "maximum": 4294967295 },
+        "denominator": { "type": "integer", "minimum": 1, "maximum": 4294967295 }
+      }
+    },
+    "Metadata": {
+      "type": "object",
+      "required": ["language", "title", "credits", "source_format", "fps"],
+      "additionalProperties": false,
+      "properties": {
+        "language": {
+          "description": "Lowercase ISO 639 code.",
+          "oneOf": [{ "type": "null" }, { "type": "string", "pattern": "^[a-z]{2,3}$" }]
+        },
+        "title": { "type": ["string", "null"] },
+        "credits": { "type": ["string", "null"] },
+        "source_format": { "type": ["string", "null"] },
+        "fps": { "oneOf": [{ "type": "null" }, { "$ref": "#/$defs/FrameRate" }] }
+      }
+    }
+  }
+}
+"##);
+
+#[cfg(test)]
+mod schema_tests {
+    use super::*;
+
+    #[test]
+    fn _json_schema() {
+        let schema = json_schema();
+        assert!(schema.contains(concat!("/srt/", env!("CARGO_PKG_VERSION"), "/")));
+
+        let mut depth = 0;
+        for c in schema.chars() {
+            match c {
+                '{' | '[' => depth += 1,
+                '}' | ']' => depth -= 1,
+                _ => {}
+            }
+            assert!(depth >= 0);
+        }
+        assert_eq!(depth, 0);
+
+        for reference in schema.split("\"#/$defs/").skip(1) {
+            let name = &reference[..reference.find('"').unwrap()];
+            assert!(schema.contains(&format!("\n    \"{}\": {{", name)), "{}", name);
+        }
+    }
+}
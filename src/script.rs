@@ -0,0 +1,89 @@
+use crate::timestamp::Timestamp;
+use crate::subline::SubLine;
+use crate::subtitles::Subtitles;
+use crate::resegment;
+
+/// Options for ```Subtitles::from_script```.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScriptOptions {
+    /// Longest text of a line, longer script lines are split at sentences and clauses.
+    pub max_chars: usize,
+    /// Reading speed in characters per second at which lines are shown if there is enough time.
+    /// Lines take their whole share of time if it isn't positive.
+    pub target_cps: f64,
+    /// Lines are shown at least that long if there is enough time.
+    pub min_duration: Timestamp,
+    /// Time left between lines.
+    pub gap: Timestamp,
+}
+
+impl Default for ScriptOptions {
+    fn default() -> ScriptOptions {
+        ScriptOptions {
+            max_chars: 84,
+            target_cps: 17.0,
+            min_duration: Timestamp::new(0, 0, 1, 0),
+            gap: Timestamp::new(0, 0, 0, 100),
+        }
+    }
+}
+
+impl Subtitles {
+    /// Makes rough subtitles from a plain script lasting ```total```, e.g. lyrics or scratch captions.
+    /// Every non-empty script line starts a new subtitle line. Lines get a share of ```total```
+    /// proportional to their length and start at the beginning of it,
+    /// lasting as long as reading at ```ScriptOptions::target_cps``` takes.
+    pub fn from_script(script: &str, total: Timestamp, options: &ScriptOptions) -> Subtitles {
+        let texts: Vec<String> = script.lines()
+            .flat_map(|line| resegment::sentences(line, options.max_chars))
+            .collect();
+        let lengths: Vec<usize> = texts.iter().map(|text| text.chars().count()).collect();
+        let sum = lengths.iter().sum::<usize>().max(1) as f64;
+
+        let mut subs = Subtitles::default();
+        let mut offset = 0;
+        for (text, length) in texts.into_iter().zip(lengths) {
+            let start = Timestamp::lerp(Timestamp::default(), total, offset as f64 / sum);
+            offset += length;
+            let slot_end = Timestamp::lerp(Timestamp::default(), total, offset as f64 / sum);
+
+            let mut end = slot_end.total_miliseconds().saturating_sub(options.gap.total_miliseconds());
+            if options.target_cps > 0.0 && options.target_cps.is_finite() {
+                let reading = (length as f64 / options.target_cps * 1000.0).round() as u64;
+                let duration = reading.max(options.min_duration.total_miliseconds());
+                end = end.min(start.total_miliseconds() + duration);
+            }
+            let end = Timestamp::from_miliseconds(end).max(start);
+            let index = subs.len() as u32 + 1;
+            subs.inner.push(SubLine::new(index, text, start, end));
+        }
+        subs
+    }
+}
+
+#[cfg(test)]
+mod script_tests {
+    use super::*;
+
+    #[test]
+    fn from_script() {
+        let script = "Twinkle, twinkle, little star.\n\nHow I wonder\nwhat you are!";
+        let subs = Subtitles::from_script(script, Timestamp::new(0, 0, 10, 0), &ScriptOptions::default());
+        let timing: Vec<_> = subs.inner
+            .iter()
+            .map(|line| (line.index, &*line.text, line.start.total_miliseconds(), line.end.total_miliseconds()))
+            .collect();
+        // 30, 12 and 13 characters, 30 characters take 1765 ms at 17 cps
+        assert_eq!(timing,
+                   vec![(1, "Twinkle, twinkle, little star.", 0, 1765),
+                        (2, "How I wonder", 5455, 6455),
+                        (3, "what you are!", 7636, 8636)]);
+
+        let options = ScriptOptions { max_chars: 20, target_cps: 0.0, ..ScriptOptions::default() };
+        let subs = Subtitles::from_script(script, Timestamp::new(0, 0, 10, 0), &options);
+        assert_eq!(subs.inner[0].text, "Twinkle, twinkle,");
+        assert_eq!(subs.inner[1].text, "little star.");
+        assert_eq!(subs.inner[0].end.total_miliseconds() + 100, subs.inner[1].start.total_miliseconds());
+        assert!(Subtitles::from_script("", Timestamp::new(0, 0, 10, 0), &options).inner.is_empty());
+    }
+}
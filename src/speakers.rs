@@ -0,0 +1,111 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::timestamp::Timestamp;
+use crate::subtitles::Subtitles;
+use crate::utils;
+
+/// Statistics of a speaker, see ```Subtitles::speaker_stats```.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpeakerStats {
+    /// Label as written, ```None``` for text without a label.
+    pub speaker: Option<String>,
+    /// Lines in which the speaker talks.
+    pub lines: usize,
+    /// Time of the speaker's lines, a line of several speakers is split proportionally to their words.
+    pub speaking_time: Timestamp,
+    pub words: usize,
+}
+
+impl Subtitles {
+    /// Statistics of every speaker, in the order of their first line, e.g. for dubbing budgets.
+    /// Speakers are recognized by uppercase labels at the start of a row (```JOHN: Hi.```,
+    /// ```- MARY: Hello.```) and WebVTT voice tags (```<v John>Hi.```). Rows without a label
+    /// continue the previous label of the line.
+    pub fn speaker_stats(&self) -> Vec<SpeakerStats> {
+        let mut stats: Vec<SpeakerStats> = Vec::new();
+        for line in &self.inner {
+            let parts = speakers(&line.text);
+            let total_words = parts.iter().map(|&(_, words)| words).sum::<usize>();
+            let duration = line.end.total_miliseconds().saturating_sub(line.start.total_miliseconds());
+
+            for (speaker, words) in parts {
+                let position = match stats.iter().position(|stat| stat.speaker == speaker) {
+                    Some(position) => position,
+                    None => {
+                        stats.push(SpeakerStats {
+                            speaker,
+                            lines: 0,
+                            speaking_time: Timestamp::default(),
+                            words: 0,
+                        });
+                        stats.len() - 1
+                    }
+                };
+                let share = if total_words == 0 { 1.0 } else { words as f64 / total_words as f64 };
+                let stat = &mut stats[position];
+                stat.lines += 1;
+                stat.words += words;
+                stat.speaking_time += Timestamp::from_miliseconds((duration as f64 * share).round() as u64);
+            }
+        }
+        stats
+    }
+}
+
+/// Speakers of the text with their word counts, each speaker once.
+fn speakers(text: &str) -> Vec<(Option<String>, usize)> {
+    let mut result: Vec<(Option<String>, usize)> = Vec::new();
+    let mut speaker = None;
+    for row in text.lines() {
+        let mut rest = row.to_owned();
+        if let Some(cap) = VOICE.captures(row) {
+            speaker = cap.get(1).map(|name| name.as_str().trim().to_owned());
+            rest = row[cap.get(0).unwrap().end()..].to_owned();
+        }
+        let rest = utils::strip_tags(&rest);
+        let rest = match LABEL.captures(&rest) {
+            Some(cap) => {
+                speaker = cap.get(1).map(|name| name.as_str().trim().to_owned());
+                rest[cap.get(0).unwrap().end()..].to_owned()
+            }
+            None => rest,
+        };
+
+        let words = rest.split_whitespace().filter(|word| word.chars().any(char::is_alphanumeric)).count();
+        match result.iter_mut().find(|entry| entry.0 == speaker) {
+            Some(entry) => entry.1 += words,
+            None => result.push((speaker.clone(), words)),
+        }
+    }
+    result
+}
+
+static VOICE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\s*<v(?:\.[^\s>]*)?\s+([^>]+)>").unwrap());
+static LABEL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*(?:-\s*)?([A-Z][A-Z0-9 .'-]*[A-Z0-9.]|[A-Z])\s*:\s").unwrap());
+
+#[cfg(test)]
+mod speakers_tests {
+    use super::*;
+
+    #[test]
+    fn speaker_stats() {
+        let subs = subtitles![(0, 2000, "JOHN: Where are you going?"),
+                              (3000, 6000, "- MARY: Home.\r\n- JOHN: Wait for me, please."),
+                              (7000, 8000, "<v Mary>Fine."),
+                              (9000, 10000, "[door slams]")];
+        let stats: Vec<_> = subs.speaker_stats()
+            .into_iter()
+            .map(|stat| (stat.speaker, stat.lines, stat.speaking_time.total_miliseconds(), stat.words))
+            .collect();
+        assert_eq!(stats,
+                   vec![(Some("JOHN".to_owned()), 2, 2000 + 2400, 4 + 4),
+                        (Some("MARY".to_owned()), 1, 600, 1),
+                        (Some("Mary".to_owned()), 1, 1000, 1),
+                        (None, 1, 1000, 2)]);
+        assert!(Subtitles::default().speaker_stats().is_empty());
+        assert_eq!(speakers("At 12:30: lunch")[0].0, None);
+    }
+}
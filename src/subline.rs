@@ -1,10 +1,36 @@
+use std::borrow::Cow;
 use std::fmt::{self, Display, Formatter};
 
-use timestamp::Timestamp;
+use crate::timestamp::Timestamp;
+use crate::cue_settings::CueSettings;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct SubLine {
     pub index: u32,
+    /// Identifier which, unlike ```index```, is kept when lines are renumbered,
+    /// e.g. WebVTT cue identifier or one given by ```Subtitles::assign_ids```.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub id: Option<String>,
+    pub start: Timestamp,
+    pub end: Timestamp,
+    pub text: String,
+    /// Note for translators or editors, not shown on screen.
+    /// Written only by formats which support comments, such as WebVTT.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub comment: Option<String>,
+    /// Position and direction of the line, written only by WebVTT.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "CueSettings::is_empty"))]
+    pub settings: CueSettings,
+    /// Timing of separate words, e.g. from speech recognition, empty if unknown.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Vec::is_empty"))]
+    pub words: Vec<Word>,
+}
+
+/// Word of a line with its own timing.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Word {
     pub start: Timestamp,
     pub end: Timestamp,
     pub text: String,
@@ -48,29 +74,82 @@ impl SubLine {
         }
 
         SubLine {
-            index: index,
-            text: text,
-            start: start,
-            end: end,
+            index,
+            id: None,
+            text,
+            start,
+            end,
+            comment: None,
+            settings: CueSettings::default(),
+            words: Vec::new(),
         }
     }
+
+    /// Copy of the line with the text cut to at most ```max_chars``` characters,
+    /// including the ```…``` added at the end of cut texts. Texts are cut after the last
+    /// word which fits, a single too long word is cut in the middle.
+    /// Tags and newlines count as characters, so tags may be cut off.
+    pub fn truncated(&self, max_chars: usize) -> SubLine {
+        SubLine { text: truncate(&self.text, max_chars).into_owned(), ..self.clone() }
+    }
+}
+
+/// Text cut as described in ```SubLine::truncated```, borrowed if it fits.
+pub(crate) fn truncate(text: &str, max_chars: usize) -> Cow<'_, str> {
+    if text.chars().count() <= max_chars {
+        return Cow::Borrowed(text);
+    }
+    if max_chars == 0 {
+        return Cow::Owned(String::new());
+    }
+    // Byte positions of the character after the kept text and of the one after it
+    let mut positions = text.char_indices().map(|(position, _)| position).skip(max_chars - 1);
+    let cut = positions.next().unwrap_or(text.len());
+    let kept = &text[..cut];
+    let at_boundary = text[cut..].starts_with(char::is_whitespace);
+    let kept = match kept.rfind(char::is_whitespace) {
+        Some(space) if !at_boundary => &kept[..space],
+        _ => kept,
+    };
+    let mut result = kept.trim_end_matches(|c: char| c.is_whitespace() || c == ',' || c == ';' || c == ':').to_owned();
+    result.push('…');
+    Cow::Owned(result)
 }
 
 #[cfg(test)]
 mod subline_tests {
     use super::*;
-    use timestamp::Timestamp;
+    use crate::timestamp::Timestamp;
     #[test]
     fn display() {
         let subline = SubLine {
             text: "Some text lalala".to_owned(),
             index: 1,
+            id: None,
             start: Timestamp::new(0, 55, 9, 8),
             end: Timestamp::new(1, 1, 1, 1),
+            comment: None,
+            settings: CueSettings::default(),
+            words: Vec::new(),
         };
 
         let in_text = "1\r\n00:55:09,008 --> 01:01:01,001\r\nSome text lalala\r\n\r\n".to_owned();
 
         assert_eq!(format!("{}", subline), in_text);
     }
+
+    #[test]
+    fn truncated() {
+        let line = SubLine::new(1, "Hello there, my friend".to_owned(), Timestamp::default(), Timestamp::default());
+        let truncate = |max_chars| line.truncated(max_chars).text;
+        assert_eq!(truncate(100), "Hello there, my friend");
+        assert_eq!(truncate(22), "Hello there, my friend");
+        assert_eq!(truncate(21), "Hello there, my…");
+        assert_eq!(truncate(14), "Hello there…");
+        assert_eq!(truncate(16), "Hello there, my…");
+        assert_eq!(truncate(4), "Hel…");
+        assert_eq!(truncate(1), "…");
+        assert_eq!(truncate(0), "");
+        assert!(truncate(13).chars().count() <= 13);
+    }
 }
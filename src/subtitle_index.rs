@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+
+use crate::timestamp::Timestamp;
+use crate::subtitles::Subtitles;
+use crate::subline::SubLine;
+use crate::compare;
+
+/// Searchable collection of many tracks, e.g. all episodes of a season:
+/// lines containing words or phrases and lines shown at a time of a track.
+#[derive(Debug, Clone, Default)]
+pub struct SubtitleIndex {
+    tracks: Vec<IndexedTrack>,
+    /// Normalized word to positions of lines containing it as ```(track, position)```,
+    /// in the order of tracks and lines.
+    words: HashMap<String, Vec<(usize, usize)>>,
+}
+
+#[derive(Debug, Clone)]
+struct IndexedTrack {
+    name: String,
+    subtitles: Subtitles,
+    /// Positions of lines sorted by start.
+    by_start: Vec<usize>,
+    /// Latest end of lines in ```by_start``` up to and including every one.
+    max_end: Vec<Timestamp>,
+}
+
+/// Line found in a ```SubtitleIndex```.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hit<'a> {
+    /// Track number, as returned by ```SubtitleIndex::add_track```.
+    pub track: usize,
+    pub track_name: &'a str,
+    pub line: &'a SubLine,
+}
+
+impl SubtitleIndex {
+    /// Constructs an empty ```SubtitleIndex```.
+    pub fn new() -> SubtitleIndex {
+        SubtitleIndex::default()
+    }
+
+    /// Adds a track with given ```name``` (e.g. ```"S01E05"```) and indexes its lines.
+    /// Returns the number of the track, tracks are numbered from 0 in the order of adding.
+    pub fn add_track<S: Into<String>>(&mut self, name: S, subtitles: Subtitles) -> usize {
+        let track = self.tracks.len();
+        for (position, line) in subtitles.inner.iter().enumerate() {
+            let mut words = compare::words(&line.text);
+            words.sort();
+            words.dedup();
+            for word in words {
+                self.words.entry(word).or_default().push((track, position));
+            }
+        }
+
+        let mut by_start: Vec<usize> = (0..subtitles.inner.len()).collect();
+        by_start.sort_by_key(|&position| subtitles.inner[position].start);
+        let max_end = by_start.iter()
+            .scan(Timestamp::default(), |max_end, &position| {
+                *max_end = (*max_end).max(subtitles.inner[position].end);
+                Some(*max_end)
+            })
+            .collect();
+
+        self.tracks.push(IndexedTrack {
+            name: name.into(),
+            subtitles,
+            by_start,
+            max_end,
+        });
+        track
+    }
+
+    /// Returns the number of tracks.
+    pub fn len(&self) -> usize {
+        self.tracks.len()
+    }
+
+    /// Returns ```true``` if there are no tracks.
+    pub fn is_empty(&self) -> bool {
+        self.tracks.is_empty()
+    }
+
+    /// Get the track with given number.
+    pub fn track(&self, track: usize) -> Option<&Subtitles> {
+        self.tracks.get(track).map(|indexed| &indexed.subtitles)
+    }
+
+    /// Get the number of the first track with given ```name```.
+    pub fn track_by_name(&self, name: &str) -> Option<usize> {
+        self.tracks.iter().position(|indexed| indexed.name == name)
+    }
+
+    /// Lines containing ```word```, compared without case and punctuation,
+    /// in the order of tracks and lines.
+    pub fn search_word(&self, word: &str) -> Vec<Hit<'_>> {
+        match compare::words(word).first().and_then(|word| self.words.get(word)) {
+            Some(positions) => positions.iter().map(|&(track, position)| self.hit(track, position)).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Lines containing all words of ```phrase``` in a row, compared without case, tags
+    /// and punctuation, in the order of tracks and lines. Phrases continuing
+    /// in the next line aren't found.
+    pub fn search_phrase(&self, phrase: &str) -> Vec<Hit<'_>> {
+        let phrase = compare::words(phrase);
+        let rarest = phrase.iter()
+            .map(|word| self.words.get(word))
+            .min_by_key(|positions| positions.map_or(0, Vec::len));
+        let candidates = match rarest {
+            Some(Some(positions)) => positions,
+            _ => return Vec::new(),
+        };
+        candidates.iter()
+            .filter(|&&(track, position)| {
+                let words = compare::words(&self.tracks[track].subtitles.inner[position].text);
+                words.windows(phrase.len()).any(|window| window == &phrase[..])
+            })
+            .map(|&(track, position)| self.hit(track, position))
+            .collect()
+    }
+
+    /// Lines of ```track``` shown at given ```time``` (```start...end```, inclusive)
+    /// in the order of their start. Tracks don't need to be sorted.
+    pub fn at(&self, track: usize, time: Timestamp) -> Vec<Hit<'_>> {
+        let indexed = match self.tracks.get(track) {
+            Some(indexed) => indexed,
+            None => return Vec::new(),
+        };
+        let lines = &indexed.subtitles.inner;
+        let started = indexed.by_start.partition_point(|&position| lines[position].start <= time);
+        // Lines before the last one ending at or after time can't be shown
+        let first = indexed.max_end[..started].partition_point(|&end| end < time);
+        indexed.by_start[first..started]
+            .iter()
+            .filter(|&&position| lines[position].end >= time)
+            .map(|&position| self.hit(track, position))
+            .collect()
+    }
+
+    fn hit(&self, track: usize, position: usize) -> Hit<'_> {
+        let indexed = &self.tracks[track];
+        Hit {
+            track,
+            track_name: &indexed.name,
+            line: &indexed.subtitles.inner[position],
+        }
+    }
+}
+
+#[cfg(test)]
+mod subtitle_index_tests {
+    use super::*;
+    use crate::utils;
+
+    fn index() -> SubtitleIndex {
+        let mut index = SubtitleIndex::new();
+        let first = utils::subs_from_texts(&["Winter is coming.", "<i>Winter</i>, they said.", "Hold the door!"]);
+        index.add_track("S01E01", first);
+        let mut second = utils::subs_from_texts(&["Hold the door.", "The door!", "Is winter coming?"]);
+        // Long line shown over the next one, out of order
+        second.inner[0].end = Timestamp::new(0, 0, 5, 0);
+        second.inner.swap(0, 1);
+        index.add_track("S01E02", second);
+        index
+    }
+
+    fn found<'a>(hits: &[Hit<'a>]) -> Vec<(&'a str, u32)> {
+        hits.iter().map(|hit| (hit.track_name, hit.line.index)).collect()
+    }
+
+    #[test]
+    fn search() {
+        let index = index();
+        assert_eq!(found(&index.search_word("WINTER")), vec![("S01E01", 1), ("S01E01", 2), ("S01E02", 3)]);
+        assert_eq!(found(&index.search_phrase("hold the door")), vec![("S01E01", 3), ("S01E02", 1)]);
+        assert_eq!(found(&index.search_phrase("winter coming")), vec![("S01E02", 3)]);
+        assert!(index.search_word("summer").is_empty());
+        assert!(index.search_phrase("").is_empty());
+    }
+
+    #[test]
+    fn at() {
+        let index = index();
+        let track = index.track_by_name("S01E02").unwrap();
+        assert_eq!(found(&index.at(track, Timestamp::new(0, 0, 2, 500))), vec![("S01E02", 1), ("S01E02", 2)]);
+        assert_eq!(found(&index.at(track, Timestamp::new(0, 0, 4, 0))), vec![("S01E02", 1), ("S01E02", 3)]);
+        assert!(index.at(track, Timestamp::new(0, 0, 5, 500)).is_empty());
+        assert!(index.at(5, Timestamp::default()).is_empty());
+        assert_eq!(index.len(), 2);
+        assert_eq!(index.track(0).unwrap().len(), 3);
+    }
+}
@@ -0,0 +1,256 @@
+use crate::timestamp::Timestamp;
+use crate::subtitles::Subtitles;
+use crate::subline::SubLine;
+use crate::language::LanguageTag;
+
+/// Several tracks of the same video, e.g. translations into different languages.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SubtitleSet {
+    pub tracks: Vec<Subtitles>,
+}
+
+/// Options for ```SubtitleSet::check_consistency```.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConsistencyOptions {
+    /// Maximal allowed difference between start (or end) times of aligned lines.
+    pub max_divergence: Timestamp,
+    /// Minimal silence (in all tracks) that separates two scenes.
+    pub scene_gap: Timestamp,
+    /// Maximal allowed difference between the numbers of lines in a scene.
+    pub max_count_difference: usize,
+}
+
+impl Default for ConsistencyOptions {
+    fn default() -> ConsistencyOptions {
+        ConsistencyOptions {
+            max_divergence: Timestamp::new(0, 0, 1, 0),
+            scene_gap: Timestamp::new(0, 0, 5, 0),
+            max_count_difference: 2,
+        }
+    }
+}
+
+/// Problem found by ```SubtitleSet::check_consistency```.
+/// Tracks are referred by their position in ```SubtitleSet::tracks```,
+/// lines by their indices.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConsistencyIssue {
+    /// Line has no time-overlapping line in the other track.
+    MissingLine {
+        track: usize,
+        index: u32,
+        missing_in: usize,
+    },
+    /// Aligned lines' timings differ by more than allowed,
+    /// differences are ```other - line``` in miliseconds.
+    TimingDivergence {
+        track: usize,
+        index: u32,
+        other_track: usize,
+        other_index: u32,
+        start_difference: i64,
+        end_difference: i64,
+    },
+    /// Tracks have different number of lines in the scene, ```counts``` are per track.
+    SceneCountMismatch {
+        start: Timestamp,
+        end: Timestamp,
+        counts: Vec<usize>,
+    },
+}
+
+impl SubtitleSet {
+    /// Constructs a new ```SubtitleSet``` from given tracks.
+    pub fn new(tracks: Vec<Subtitles>) -> SubtitleSet {
+        SubtitleSet { tracks }
+    }
+
+    /// Returns the number of tracks.
+    pub fn len(&self) -> usize {
+        self.tracks.len()
+    }
+
+    /// Returns ```true``` if there are no tracks.
+    pub fn is_empty(&self) -> bool {
+        self.tracks.is_empty()
+    }
+
+    /// Get the first track with given language in its metadata.
+    pub fn by_language(&self, language: &LanguageTag) -> Option<&Subtitles> {
+        self.tracks.iter().find(|track| track.metadata.language.as_ref() == Some(language))
+    }
+
+    /// Compares every pair of tracks, reporting lines missing from the other track
+    /// (no time-overlapping line), aligned lines with diverging timing
+    /// and scenes with mismatched numbers of lines.
+    pub fn check_consistency(&self, options: &ConsistencyOptions) -> Vec<ConsistencyIssue> {
+        let mut issues = Vec::new();
+        let max_divergence = options.max_divergence.total_miliseconds() as i64;
+
+        for (track, subs) in self.tracks.iter().enumerate() {
+            for (other_track, other_subs) in self.tracks.iter().enumerate() {
+                if track == other_track {
+                    continue;
+                }
+
+                for (line, aligned) in subs.inner.iter().zip(align(subs, other_subs)) {
+                    let other = match aligned {
+                        Some(other) => other,
+                        None => {
+                            issues.push(ConsistencyIssue::MissingLine {
+                                track,
+                                index: line.index,
+                                missing_in: other_track,
+                            });
+                            continue;
+                        }
+                    };
+
+                    // Divergence is symmetric, report it once per pair
+                    if track > other_track {
+                        continue;
+                    }
+                    let start_difference = difference(line.start, other.start);
+                    let end_difference = difference(line.end, other.end);
+                    if start_difference.abs() > max_divergence || end_difference.abs() > max_divergence {
+                        issues.push(ConsistencyIssue::TimingDivergence {
+                            track,
+                            index: line.index,
+                            other_track,
+                            other_index: other.index,
+                            start_difference,
+                            end_difference,
+                        });
+                    }
+                }
+            }
+        }
+
+        for (start, end) in self.scenes(options.scene_gap) {
+            let counts: Vec<usize> = self.tracks
+                .iter()
+                .map(|subs| subs.inner.iter().filter(|line| line.start >= start && line.end <= end).count())
+                .collect();
+            let min = counts.iter().cloned().min().unwrap_or(0);
+            let max = counts.iter().cloned().max().unwrap_or(0);
+            if max - min > options.max_count_difference {
+                issues.push(ConsistencyIssue::SceneCountMismatch {
+                    start,
+                    end,
+                    counts,
+                });
+            }
+        }
+
+        issues
+    }
+
+    /// Time ranges during which at least one track has a line,
+    /// separated by silence of at least ```gap```.
+    fn scenes(&self, gap: Timestamp) -> Vec<(Timestamp, Timestamp)> {
+        let mut intervals: Vec<(Timestamp, Timestamp)> = self.tracks
+            .iter()
+            .flat_map(|subs| subs.inner.iter().map(|line| (line.start, line.end)))
+            .collect();
+        intervals.sort();
+
+        let mut scenes: Vec<(Timestamp, Timestamp)> = Vec::new();
+        for (start, end) in intervals {
+            if let Some(scene) = scenes.last_mut() {
+                if start < scene.1 || start - scene.1 < gap {
+                    if end > scene.1 {
+                        scene.1 = end;
+                    }
+                    continue;
+                }
+            }
+            scenes.push((start, end));
+        }
+        scenes
+    }
+}
+
+/// For every line of ```subs``` finds the line of ```other``` which overlaps it the most.
+fn align<'a>(subs: &Subtitles, other: &'a Subtitles) -> Vec<Option<&'a SubLine>> {
+    let mut result = Vec::with_capacity(subs.len());
+    let mut first = 0;
+
+    for line in &subs.inner {
+        while first < other.inner.len() && other.inner[first].end <= line.start {
+            first += 1;
+        }
+
+        let mut best: Option<(&SubLine, Timestamp)> = None;
+        for candidate in other.inner[first..].iter().take_while(|candidate| candidate.start < line.end) {
+            let start = if candidate.start > line.start { candidate.start } else { line.start };
+            let end = if candidate.end < line.end { candidate.end } else { line.end };
+            if end <= start {
+                continue;
+            }
+            let overlap = end - start;
+            if best.map(|(_, best_overlap)| overlap > best_overlap).unwrap_or(true) {
+                best = Some((candidate, overlap));
+            }
+        }
+        result.push(best.map(|(candidate, _)| candidate));
+    }
+    result
+}
+
+fn difference(from: Timestamp, to: Timestamp) -> i64 {
+    to.total_miliseconds() as i64 - from.total_miliseconds() as i64
+}
+
+#[cfg(test)]
+mod subtitle_set_tests {
+    use super::*;
+    use crate::timestamp::Timestamp;
+    use crate::utils;
+
+    #[test]
+    fn check_consistency() {
+        let english = utils::subs_from_texts(&["One", "Two", "Three", "Four"]);
+        let mut french = utils::subs_from_texts(&["Un", "Deux", "Trois"]);
+        // "Deux" is late by a half of a second
+        french.inner[1].start += Timestamp::new(0, 0, 0, 500);
+        french.inner[1].end += Timestamp::new(0, 0, 0, 500);
+
+        let set = SubtitleSet::new(vec![english, french]);
+        let options = ConsistencyOptions { max_divergence: Timestamp::new(0, 0, 0, 300), ..Default::default() };
+        let issues = set.check_consistency(&options);
+
+        assert_eq!(issues,
+                   vec![ConsistencyIssue::TimingDivergence {
+                            track: 0,
+                            index: 2,
+                            other_track: 1,
+                            other_index: 2,
+                            start_difference: 500,
+                            end_difference: 500,
+                        },
+                        ConsistencyIssue::MissingLine {
+                            track: 0,
+                            index: 4,
+                            missing_in: 1,
+                        }]);
+    }
+
+    #[test]
+    fn scene_count_mismatch() {
+        let english = utils::subs_from_texts(&["One", "Two", "Three", "Four"]);
+        let mut french = utils::subs_from_texts(&["Un, deux, trois et quatre"]);
+        french.inner[0].end = Timestamp::new(0, 0, 7, 0);
+
+        let set = SubtitleSet::new(vec![english, french]);
+        let options = ConsistencyOptions { max_count_difference: 1, ..Default::default() };
+        let issues = set.check_consistency(&options);
+
+        assert_eq!(issues.last().unwrap(),
+                   &ConsistencyIssue::SceneCountMismatch {
+                       start: Timestamp::new(0, 0, 0, 0),
+                       end: Timestamp::new(0, 0, 7, 0),
+                       counts: vec![4, 1],
+                   });
+        assert!(!issues.iter().any(|issue| matches!(*issue, ConsistencyIssue::MissingLine { .. })));
+    }
+}
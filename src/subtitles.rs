@@ -1,17 +1,36 @@
 use std::str::FromStr;
-use std::ops::Index;
+use std::ops::{Index, Range};
+use std::iter::FromIterator;
 use std::fs::File;
 use std::io::{Error, Write, ErrorKind};
 use std::path::Path;
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
 use std::fmt::{self, Display, Formatter};
 
-use timestamp::Timestamp;
-use subline::SubLine;
-use utils;
+use crate::timestamp::Timestamp;
+use crate::subline::SubLine;
+use crate::metadata::Metadata;
+use crate::cue_settings::CueSettings;
+use crate::error::{StructureError, EditError};
+use crate::utils;
+
+/// Whether a line is shown at its end time, see ```Subtitles::by_time_at```.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Boundary {
+    /// Line is shown in ```start...end```, as ```Subtitles::by_time``` does.
+    #[default]
+    EndInclusive,
+    /// Line is shown in ```start..end```, as players do, so a line ending
+    /// exactly when the next one starts isn't found at that time.
+    EndExclusive,
+}
 
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Subtitles {
     pub inner: Vec<SubLine>,
+    pub metadata: Metadata,
 }
 
 impl Subtitles {
@@ -20,166 +39,219 @@ impl Subtitles {
         self.inner.len()
     }
 
+    /// Returns ```true``` if there are no lines.
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Iterates over lines in order.
+    pub fn iter(&self) -> ::std::slice::Iter<'_, SubLine> {
+        self.inner.iter()
+    }
+
+    /// Iterates over lines in order, allowing to modify them.
+    pub fn iter_mut(&mut self) -> ::std::slice::IterMut<'_, SubLine> {
+        self.inner.iter_mut()
+    }
+
+    /// Lines in order. Prefer this to the ```inner``` field, which will become private.
+    pub fn lines(&self) -> &[SubLine] {
+        &self.inner
+    }
+
+    /// Lines in order, allowing to modify them but not to add or remove them.
+    pub fn lines_mut(&mut self) -> &mut [SubLine] {
+        &mut self.inner
+    }
+
+    /// Consumes ```Subtitles```, returning their lines.
+    pub fn into_lines(self) -> Vec<SubLine> {
+        self.inner
+    }
+
+    /// Metadata of the file. Prefer this to the ```metadata``` field, which will become private.
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    /// Mutable metadata of the file.
+    pub fn metadata_mut(&mut self) -> &mut Metadata {
+        &mut self.metadata
+    }
+
+    /// Get ```&SubLine``` at given ```position``` of the inner vector (starting at 0),
+    /// or ```None``` if it is out of bounds. Unlike indexing ```Subtitles```, never panics.
+    pub fn get(&self, position: usize) -> Option<&SubLine> {
+        self.inner.get(position)
+    }
+
+    /// Get ```&mut SubLine``` at given ```position``` of the inner vector (starting at 0).
+    pub fn get_mut(&mut self, position: usize) -> Option<&mut SubLine> {
+        self.inner.get_mut(position)
+    }
+
     /// Construct ```Subtitles``` from given file path.
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Subtitles, Error> {
-        let mut content = try!(utils::read_file(&path));
+        let mut content = utils::read_file(&path)?;
         content = utils::prepare(&content);
 
         if !utils::check(&content) {
             return Err(Error::new(ErrorKind::InvalidData,
                                   "Given file does not match with srt format specification"));
         }
-        Ok(try!(Subtitles::from_str(&content)))
+        Subtitles::from_str(&content)
     }
 
     /// Saves ```Subtitles``` into given file path according srt subtitles format.
     pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
-        let mut file = try!(File::create(&path));
+        let mut file = File::create(&path)?;
         for line in &self.inner {
-            try!(write!(&mut file, "{}", line));
+            write!(&mut file, "{}", line)?;
         }
-        try!(write!(&mut file, "\r\n\r\n"));
+        write!(&mut file, "\r\n\r\n")?;
         Ok(())
     }
 
     /// Get ```&SubLine``` by it's index.
     ///
-    /// # Panics
-    /// Panics if inner structure is broken.
+    /// Returns an error if there is no such line or inner structure is broken.
     /// E.g. inner vector is not sorted or SubLine's indices is not consistent.
-    pub fn by_index(&self, index: usize) -> Option<&SubLine> {
-        let indexed_subline = self.inner.get(index - 1);
-
-        indexed_subline.as_ref().map(|elem| {
-            if elem.index != index as u32 {
-                panic!("Subtitles's inner structure is broken, inner vector of Subtitles must always be \
-            sorted and also unterlying SubLine's indices must be correct");
-            }
-        });
-
-        indexed_subline
+    pub fn by_index(&self, index: usize) -> Result<&SubLine, StructureError> {
+        let position = self.index_position(index)?;
+        Ok(&self.inner[position])
     }
 
     /// Get ```&mut SubLine``` by it's index.
     ///
-    /// # Panics
-    /// Panics if inner structure is broken.
+    /// Returns an error if there is no such line or inner structure is broken.
     /// E.g. inner vector is not sorted or SubLine's indices is not consistent.
-    pub fn by_index_mut(&mut self, index: usize) -> Option<&mut SubLine> {
-        let indexed_subline = self.inner.get_mut(index - 1);
+    pub fn by_index_mut(&mut self, index: usize) -> Result<&mut SubLine, StructureError> {
+        let position = self.index_position(index)?;
+        Ok(&mut self.inner[position])
+    }
 
-        indexed_subline.as_ref().map(|elem| {
-            if elem.index != index as u32 {
-                panic!("Subtitles's inner structure is broken, inner vector of Subtitles must always be \
-            sorted and also unterlying SubLine's indices must be correct");
-            }
-        });
-        indexed_subline
+    /// Get ```&SubLine``` by its ```id```.
+    pub fn by_id(&self, id: &str) -> Option<&SubLine> {
+        self.inner.iter().find(|line| line.id.as_deref() == Some(id))
+    }
+
+    /// Get ```&mut SubLine``` by its ```id```.
+    pub fn by_id_mut(&mut self, id: &str) -> Option<&mut SubLine> {
+        self.inner.iter_mut().find(|line| line.id.as_deref() == Some(id))
     }
 
     /// Get ```&SubLine``` for which given ```time```
     /// lies in the range ```start...end``` (inclusive).
     pub fn by_time(&self, time: Timestamp) -> Option<&SubLine> {
-        let mut min = 0;
-        let mut max = self.inner.len() - 1;
-        let mut guess_index;
-
-        while max >= min {
-            guess_index = (max + min) / 2;
-            let guess = self.inner.get(guess_index).unwrap();
-
-            if (guess.start <= time) && (time <= guess.end) {
-                return Some(&guess);
-            } else if time > guess.end {
-                min = guess_index + 1;
-            } else {
-                max = guess_index - 1;
-            }
-        }
-        None
+        self.by_time_at(time, Boundary::EndInclusive)
     }
 
     /// Get ```&mut SubLine``` for which given ```time```
     /// lies in the range ```start...end``` (inclusive).
     pub fn by_time_mut(&mut self, time: Timestamp) -> Option<&mut SubLine> {
-        let mut min = 0;
-        let mut max = self.inner.len() - 1;
-        let mut guess_index;
-        let mut result: usize = 0;
-        let mut found = false;
-
-        while max >= min {
-            guess_index = (max + min) / 2;
-
-            let guess = self.inner.get(guess_index).unwrap();
-
-            if (guess.start <= time) && (time <= guess.end) {
-                result = guess_index;
-                found = true;
-                break;
-            } else if time > guess.end {
-                min = guess_index + 1;
-            } else {
-                max = guess_index - 1;
-            }
-        }
-        if found {
-            self.inner.get_mut(result)
-        } else {
-            None
+        self.by_time_at_mut(time, Boundary::EndInclusive)
+    }
+
+    /// Get ```&SubLine``` shown at given ```time```, treating its end according to ```boundary```.
+    /// If several lines are shown, the first of them in the inner vector is returned.
+    pub fn by_time_at(&self, time: Timestamp, boundary: Boundary) -> Option<&SubLine> {
+        self.time_position(time, boundary).ok().map(|position| &self.inner[position])
+    }
+
+    /// Get ```&mut SubLine``` shown at given ```time```, treating its end according to ```boundary```.
+    pub fn by_time_at_mut(&mut self, time: Timestamp, boundary: Boundary) -> Option<&mut SubLine> {
+        match self.time_position(time, boundary) {
+            Ok(position) => self.inner.get_mut(position),
+            Err(_) => None,
         }
     }
 
     /// Get ```&SubLine``` for which given ```time```
     /// lies in the range ```start..start_of_the_next_line``` (exclusive).
     pub fn nearest_by_time(&self, time: Timestamp) -> Option<&SubLine> {
-        let mut min = 0;
-        let mut max = self.inner.len() - 1;
-        let mut guess_index;
-
-        while max >= min {
-            guess_index = (max + min) / 2;
-            let guess = self.inner.get(guess_index).unwrap();
-
-            if (guess.start <= time) && (time <= guess.end) {
-                return Some(&guess);
-            } else if time > guess.end {
-                min = guess_index + 1;
-            } else {
-                max = guess_index - 1;
-            }
-        }
-        self.inner.get(max)
+        self.nearest_position(time).map(|position| &self.inner[position])
     }
 
     /// Get ```&mut SubLine``` for which given ```time```
     /// lies in the range ```start..start_of_the_next_line``` (exclusive).
     pub fn nearest_by_time_mut(&mut self, time: Timestamp) -> Option<&mut SubLine> {
-        let mut min = 0;
-        let mut max = self.inner.len() - 1;
-        let mut guess_index;
-        let mut result: usize = 0;
-        let mut found_exact = false;
-
-        while max >= min {
-            guess_index = (max + min) / 2;
-
-            let guess = self.inner.get(guess_index).unwrap();
-
-            if (guess.start <= time) && (time <= guess.end) {
-                result = guess_index;
-                found_exact = true;
-                break;
-            } else if time > guess.end {
-                min = guess_index + 1;
-            } else {
-                max = guess_index - 1;
-            }
+        match self.nearest_position(time) {
+            Some(position) => self.inner.get_mut(position),
+            None => None,
+        }
+    }
+
+    /// Lines starting exactly at given ```time```, e.g. a sign and a dialogue line,
+    /// in the order of ```sort_by_time```: by end time, then by their previous order.
+    ///
+    /// Lines must be sorted by start time.
+    pub fn all_starting_at(&self, time: Timestamp) -> &[SubLine] {
+        let first = self.inner.partition_point(|line| line.start < time);
+        &self.inner[first..self.position_by_start(time)]
+    }
+
+    /// Get the first ```&SubLine``` which starts strictly after given ```time```.
+    pub fn first_starting_after(&self, time: Timestamp) -> Option<&SubLine> {
+        self.inner.get(self.position_by_start(time))
+    }
+
+    /// Get the last ```&SubLine``` which starts strictly before given ```time```.
+    pub fn last_starting_before(&self, time: Timestamp) -> Option<&SubLine> {
+        let position = self.inner.partition_point(|line| line.start < time);
+        position.checked_sub(1).map(|position| &self.inner[position])
+    }
+
+    /// Iterates lines starting from the one shown at given ```time``` (inclusive end)
+    /// or, if there is none, the next one. The start is found by a single binary search,
+    /// so sequential rendering can walk forward from it instead of looking up every frame.
+    pub fn iter_from(&self, time: Timestamp) -> ::std::slice::Iter<'_, SubLine> {
+        let position = match self.time_position(time, Boundary::EndInclusive) {
+            Ok(position) | Err(position) => position,
+        };
+        self.inner[position..].iter()
+    }
+
+    /// Inner vector position at which a line starting at given ```time``` should be inserted,
+    /// i.e. after all lines starting at or before ```time```.
+    ///
+    /// Lines must be sorted by start time.
+    pub fn position_by_start(&self, time: Timestamp) -> usize {
+        self.inner.partition_point(|line| line.start <= time)
+    }
+
+    /// Inner vector position of the line with given ```index```.
+    pub(crate) fn index_position(&self, index: usize) -> Result<usize, StructureError> {
+        let position = match index.checked_sub(1) {
+            Some(position) if position < self.inner.len() => position,
+            _ => return Err(StructureError::NotFound { index }),
+        };
+        let found = self.inner[position].index;
+        if found as usize != index {
+            return Err(StructureError::IndexMismatch {
+                index,
+                found,
+            });
         }
-        if found_exact {
-            self.inner.get_mut(result)
-        } else {
-            self.inner.get_mut(max)
+        Ok(position)
+    }
+
+    /// Binary search of the line containing ```time```, if there is no such line
+    /// returns position of the first line after ```time```.
+    fn time_position(&self, time: Timestamp, boundary: Boundary) -> Result<usize, usize> {
+        let position = match boundary {
+            Boundary::EndInclusive => self.inner.partition_point(|line| line.end < time),
+            Boundary::EndExclusive => self.inner.partition_point(|line| line.end <= time),
+        };
+        match self.inner.get(position) {
+            Some(line) if line.start <= time => Ok(position),
+            _ => Err(position),
+        }
+    }
+
+    fn nearest_position(&self, time: Timestamp) -> Option<usize> {
+        match self.time_position(time, Boundary::EndInclusive) {
+            Ok(position) => Some(position),
+            Err(next) => next.checked_sub(1),
         }
     }
 
@@ -188,12 +260,24 @@ impl Subtitles {
     /// # Panics
     /// Panics if given ```line```'s index is not ```latest_sub_index + 1```.
     /// E.g. pushed line is going to break consistency.
+    #[deprecated(since = "0.2.0", note = "use `try_push`, which returns an error instead of panicking")]
     pub fn push(&mut self, line: SubLine) {
-        let last_index: u32 = self.inner.last().map(|line| line.index).unwrap_or(0);
-        if line.index != last_index + 1 {
-            panic!("Pushed line is going to break consistency, invalid index");
+        if let Err(error) = self.try_push(line) {
+            panic!("Pushed line is going to break consistency: {}", error);
+        }
+    }
+
+    /// Pushes given line in the end.
+    ///
+    /// Returns an error and leaves ```Subtitles``` unchanged
+    /// if given ```line```'s index is not ```latest_sub_index + 1```.
+    pub fn try_push(&mut self, line: SubLine) -> Result<(), EditError> {
+        let expected = self.inner.last().map(|line| line.index).unwrap_or(0) + 1;
+        if line.index != expected {
+            return Err(EditError::InvalidIndex { index: line.index, expected });
         }
         self.inner.push(line);
+        Ok(())
     }
 
     /// Inserts given ```element``` into ```Subtitles```,
@@ -201,19 +285,122 @@ impl Subtitles {
     ///
     /// # Panics
     /// Panics if ```element``'s index is greater than the ```Subtitles``` length.
+    #[deprecated(since = "0.2.0", note = "use `try_insert`, which returns an error instead of panicking")]
     pub fn insert(&mut self, element: SubLine) {
-        let index = (element.index - 1) as usize;
+        if let Err(error) = self.try_insert(element) {
+            panic!("Inserted line is going to break consistency: {}", error);
+        }
+    }
+
+    /// Inserts given ```element``` into ```Subtitles```,
+    /// shifting all its right element's indices.
+    ///
+    /// Returns an error and leaves ```Subtitles``` unchanged if ```element```'s index
+    /// is zero or greater than the ```Subtitles``` length plus one.
+    pub fn try_insert(&mut self, element: SubLine) -> Result<(), EditError> {
+        let len = self.inner.len();
+        let index = match (element.index as usize).checked_sub(1) {
+            Some(index) if index <= len => index,
+            _ => return Err(EditError::IndexOutOfRange { index: element.index, len }),
+        };
         for line in &mut self.inner[index..] {
             line.index += 1;
         }
-        
+
         self.inner.insert(index, element);
+        Ok(())
     }
-    
+
+    /// Inserts a new line at the position given by its ```start``` time
+    /// (after lines starting at the same time), shifting indices of the following lines.
+    /// Returns the inserted line.
+    ///
+    /// Returns an error and leaves ```Subtitles``` unchanged if ```start``` is greater than ```end```.
+    pub fn try_insert_at(&mut self, start: Timestamp, end: Timestamp, text: String) -> Result<&mut SubLine, EditError> {
+        if start > end {
+            return Err(EditError::InvalidTiming { start, end });
+        }
+        Ok(self.insert_by_start(start, end, text))
+    }
+
+    /// Same as ```try_insert_at```, but also returns an error if the new line overlaps its neighbours.
+    pub fn insert_at_checked(&mut self,
+                             start: Timestamp,
+                             end: Timestamp,
+                             text: String)
+                             -> Result<&mut SubLine, EditError> {
+        if start > end {
+            return Err(EditError::InvalidTiming { start, end });
+        }
+        let position = self.position_by_start(start);
+        if let Some(previous) = position.checked_sub(1).map(|previous| &self.inner[previous]) {
+            if previous.end > start {
+                return Err(EditError::Overlap { index: previous.index });
+            }
+        }
+        if let Some(next) = self.inner.get(position).filter(|next| next.start < end) {
+            return Err(EditError::Overlap { index: next.index });
+        }
+        Ok(self.insert_by_start(start, end, text))
+    }
+
+    /// Inserts a line with ```start``` not greater than ```end``` by its start.
+    pub(crate) fn insert_by_start(&mut self, start: Timestamp, end: Timestamp, text: String) -> &mut SubLine {
+        let position = self.position_by_start(start);
+        for line in &mut self.inner[position..] {
+            line.index += 1;
+        }
+        self.inner.insert(position, SubLine::new(position as u32 + 1, text, start, end));
+        &mut self.inner[position]
+    }
+
     /// Removes the last element from a Subtitles and returns it, or None if it is empty.
     pub fn pop(&mut self) -> Option<SubLine> {
         self.inner.pop()
     }
+
+    /// Splits lines into groups (e.g. scenes) separated by the silence
+    /// of at least ```threshold```. Returns ranges of the inner vector positions.
+    pub fn group_by_gap(&self, threshold: Timestamp) -> Vec<Range<usize>> {
+        let mut groups = Vec::new();
+        let mut group_start = 0;
+        let mut group_end = match self.inner.first() {
+            Some(line) => line.end,
+            None => return groups,
+        };
+
+        for (i, line) in self.inner.iter().enumerate().skip(1) {
+            if line.start > group_end && line.start - group_end >= threshold {
+                groups.push(group_start..i);
+                group_start = i;
+            }
+            if line.end > group_end {
+                group_end = line.end;
+            }
+        }
+        groups.push(group_start..self.inner.len());
+        groups
+    }
+
+    /// Gives every line without ```id``` a random UUID.
+    /// Returns the number of lines which got one.
+    pub fn assign_ids(&mut self) -> usize {
+        let state = RandomState::new();
+        let mut assigned = 0;
+        for (i, line) in self.inner.iter_mut().enumerate().filter(|(_, line)| line.id.is_none()) {
+            let hash = |seed: u8| state.hash_one((seed, i));
+            line.id = Some(utils::uuid(hash(0), hash(1)));
+            assigned += 1;
+        }
+        assigned
+    }
+
+    /// Renumbers all lines' indices consecutively starting from 1.
+    pub fn renumber(&mut self) {
+        for (i, line) in self.inner.iter_mut().enumerate() {
+            line.index = i as u32 + 1;
+        }
+    }
 }
 
 
@@ -224,32 +411,42 @@ impl FromStr for Subtitles {
     /// Given str must be properly formated:
     /// Newlne styles must be windows like (\r\n).
     /// And in the end of str must be exacly 4 newlines.
+    ///
+    /// Never panics: any input either parses or returns an error,
+    /// so it's safe to use with untrusted content.
     fn from_str(content: &str) -> Result<Subtitles, Error> {
         let mut result = Vec::with_capacity(400);
 
-        for cap in utils::SUBS.captures_iter(&content) {
+        for block in utils::blocks(content) {
+            let number = |s: &str| -> Result<u32, Error> {
+                s.parse().map_err(|_| Error::new(ErrorKind::InvalidData, format!("Invalid number {:?}", s)))
+            };
 
-            let index: u32 = cap.at(1).unwrap().parse().unwrap();
+            let index = number(block.index)?;
 
-            let start_timestamp: [u32; 4] = [cap.at(2).unwrap().parse().unwrap(),
-                                             cap.at(3).unwrap().parse().unwrap(),
-                                             cap.at(4).unwrap().parse().unwrap(),
-                                             cap.at(5).unwrap().parse().unwrap()];
-            let end_timestamp: [u32; 4] = [cap.at(6).unwrap().parse().unwrap(),
-                                           cap.at(7).unwrap().parse().unwrap(),
-                                           cap.at(8).unwrap().parse().unwrap(),
-                                           cap.at(9).unwrap().parse().unwrap()];
+            let start_timestamp: [u32; 4] =
+                [number(block.start[0])?, number(block.start[1])?, number(block.start[2])?, number(block.start[3])?];
+            let end_timestamp: [u32; 4] =
+                [number(block.end[0])?, number(block.end[1])?, number(block.end[2])?, number(block.end[3])?];
 
+            // Minutes are at most 99, so normalizing them adds an hour at most
+            if start_timestamp[0] == u32::MAX || end_timestamp[0] == u32::MAX {
+                return Err(Error::new(ErrorKind::InvalidData, "Timestamp is too big"));
+            }
             let start = Timestamp::from(&start_timestamp);
             let end = Timestamp::from(&end_timestamp);
 
-            let text = cap.at(10).unwrap().to_owned();
+            let text = block.text.to_owned();
 
             let line = SubLine {
-                index: index,
-                text: text,
-                start: start,
-                end: end,
+                index,
+                id: None,
+                text,
+                start,
+                end,
+                comment: None,
+                settings: CueSettings::default(),
+                words: Vec::new(),
             };
             result.push(line);
         }
@@ -260,7 +457,16 @@ impl FromStr for Subtitles {
 
 impl From<Vec<SubLine>> for Subtitles {
     fn from(vec: Vec<SubLine>) -> Subtitles {
-        Subtitles { inner: vec }
+        Subtitles {
+            inner: vec,
+            metadata: Metadata::default(),
+        }
+    }
+}
+
+impl FromIterator<SubLine> for Subtitles {
+    fn from_iter<I: IntoIterator<Item = SubLine>>(iter: I) -> Subtitles {
+        Subtitles::from(iter.into_iter().collect::<Vec<_>>())
     }
 }
 
@@ -295,7 +501,7 @@ impl Display for Subtitles {
     /// Formats Subtitles according srt subtitles format.
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         for line in self {
-            try!(write!(f, "{}", line));
+            write!(f, "{}", line)?;
         }
         write!(f, "\r\n\r\n")
     }
@@ -304,24 +510,25 @@ impl Display for Subtitles {
 impl Index<usize> for Subtitles {
     type Output = SubLine;
     /// Indexes inner vector. That means that indexing start at 0.
-    fn index<'a>(&'a self, index: usize) -> &'a Self::Output {
+    /// Panics if ```index``` is out of bounds, see ```Subtitles::get```.
+    fn index(&self, index: usize) -> &Self::Output {
         &self.inner[index]
     }
 }
 
 #[cfg(test)]
 mod subtitles_tests {
+    use std::sync::LazyLock;
+
     use super::*;
-    use subline::SubLine;
-    use timestamp::Timestamp;
-    use utils;
+    use crate::subline::SubLine;
+    use crate::timestamp::Timestamp;
+    use crate::utils;
     use std::str::FromStr;
 
-    static PATH: &'static str = "example.srt";
+    static PATH: &str = "example.srt";
 
-    lazy_static! {
-        static ref SUBS: Subtitles = Subtitles::from_file(PATH).unwrap();
-    }
+    static SUBS: LazyLock<Subtitles> = LazyLock::new(|| Subtitles::from_file(PATH).unwrap());
 
     #[test]
     fn from_file() {
@@ -331,6 +538,7 @@ mod subtitles_tests {
         let latest_sub = SubLine {
             text: "Last".to_owned(),
             index: 619,
+            id: None,
             start: Timestamp {
                 hours: 1,
                 minutes: 6,
@@ -343,6 +551,9 @@ mod subtitles_tests {
                 seconds: 50,
                 miliseconds: 792,
             },
+            comment: None,
+            settings: CueSettings::default(),
+            words: Vec::new(),
         };
         assert_eq!(&latest_sub, subs.by_index(619).unwrap());
     }
@@ -358,6 +569,19 @@ mod subtitles_tests {
         assert_eq!(subs, same_subs);
     }
 
+    #[test]
+    fn iter() {
+        let mut subs = utils::subs_from_texts(&["One", "Two"]);
+        assert!(!subs.is_empty());
+        assert!(Subtitles::default().is_empty());
+        for line in subs.iter_mut() {
+            line.text.push('!');
+        }
+        let texts: Vec<&str> = subs.iter().map(|line| line.text.as_str()).collect();
+        assert_eq!(texts, vec!["One!", "Two!"]);
+        assert_eq!((&subs).into_iter().count(), 2);
+    }
+
     #[test]
     fn to_string() {
         let mut sub_str = utils::read_file(PATH).unwrap();
@@ -366,7 +590,7 @@ mod subtitles_tests {
         let subs = Subtitles::from_str(&sub_str).unwrap();
         let same_subs = Subtitles::from_file(PATH).unwrap();
 
-        sub_str += &"\r\n\r\n";
+        sub_str += "\r\n\r\n";
         assert_eq!(sub_str, subs.to_string());
         assert_eq!(sub_str, same_subs.to_string());
     }
@@ -395,7 +619,7 @@ mod subtitles_tests {
         let mut new_subs_vec = Vec::with_capacity(subs.len());
 
         for mut line in subs {
-            line.text = line.text.trim_right_matches(" lol").to_owned();
+            line.text = line.text.trim_end_matches(" lol").to_owned();
             let offset = Timestamp::new(1, 1, 30, 500);
             line.end -= offset;
             line.start -= offset;
@@ -407,6 +631,7 @@ mod subtitles_tests {
     }
 
     #[test]
+    #[allow(deprecated)]
     fn insert() {
         let mut subs = SUBS.clone();
 
@@ -419,7 +644,7 @@ mod subtitles_tests {
 
         let line = SubLine::new(15, "foo bar foo".to_owned(), start, end);
         subs.insert(line.clone());
-        
+
         let newline = subs.by_index(15).unwrap();
         assert_eq!(newline, &line);
 
@@ -428,4 +653,270 @@ mod subtitles_tests {
         let new_sub16 = subs.by_index(16).unwrap();
         assert_eq!(new_sub16, &sub15);
     }
+
+    #[test]
+    fn try_insert() {
+        let mut subs = SUBS.clone();
+
+        let sub14 = subs.by_index(14).unwrap().clone();
+        let mut sub15 = subs.by_index(15).unwrap().clone();
+
+        let one_ms = Timestamp::new(0, 0, 0, 1);
+        let start = sub14.end + one_ms;
+        let end = sub15.start - one_ms;
+
+        let line = SubLine::new(15, "foo bar foo".to_owned(), start, end);
+        assert_eq!(subs.try_insert(line.clone()), Ok(()));
+        assert_eq!(subs.by_index(15).unwrap(), &line);
+        sub15.index += 1;
+        assert_eq!(subs.by_index(16).unwrap(), &sub15);
+
+        let len = subs.len();
+        let out_of_range = SubLine::new(len as u32 + 2, String::new(), start, end);
+        assert_eq!(subs.try_insert(out_of_range),
+                   Err(EditError::IndexOutOfRange { index: len as u32 + 2, len }));
+        let zero = SubLine::new(0, String::new(), start, end);
+        assert_eq!(subs.try_insert(zero), Err(EditError::IndexOutOfRange { index: 0, len }));
+        assert_eq!(subs.len(), len);
+    }
+
+    #[test]
+    fn try_push() {
+        let mut subs = utils::subs_from_texts(&["One"]);
+        let line = |index| SubLine::new(index, "Two".to_owned(), Timestamp::new(0, 0, 3, 0), Timestamp::new(0, 0, 4, 0));
+
+        assert_eq!(subs.try_push(line(3)), Err(EditError::InvalidIndex { index: 3, expected: 2 }));
+        assert_eq!(subs.len(), 1);
+        assert_eq!(subs.try_push(line(2)), Ok(()));
+        assert_eq!(subs.get(1).unwrap().text, "Two");
+        assert!(subs.get(2).is_none());
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    #[should_panic]
+    fn deprecated_push() {
+        let mut subs = utils::subs_from_texts(&["One"]);
+        subs.push(SubLine::new(3, String::new(), Default::default(), Default::default()));
+    }
+
+    #[test]
+    fn accessors() {
+        let mut subs = utils::subs_from_texts(&["One", "Two"]);
+        subs.lines_mut()[1].text.push('!');
+        subs.metadata_mut().title = Some("Title".to_owned());
+
+        assert_eq!(subs.lines()[1].text, "Two!");
+        assert_eq!(subs.metadata().title.as_deref(), Some("Title"));
+        assert_eq!(subs.into_lines().len(), 2);
+    }
+
+    #[test]
+    fn group_by_gap() {
+        let mut subs = utils::subs_from_texts(&["One", "Two", "Three", "Four", "Five"]);
+        subs.inner[2].end = Timestamp::new(0, 0, 5, 500);
+        subs.inner[4].start = Timestamp::new(0, 0, 10, 0);
+        subs.inner[4].end = Timestamp::new(0, 0, 11, 0);
+
+        assert_eq!(subs.group_by_gap(Timestamp::new(0, 0, 1, 0)), vec![0..1, 1..2, 2..4, 4..5]);
+        assert_eq!(subs.group_by_gap(Timestamp::new(0, 0, 3, 0)), vec![0..4, 4..5]);
+        assert_eq!(subs.group_by_gap(Timestamp::new(0, 0, 3, 1)), vec![0..5]);
+        assert!(Subtitles::default().group_by_gap(Timestamp::new(0, 0, 1, 0)).is_empty());
+    }
+
+    #[test]
+    fn ids() {
+        let mut subs = utils::subs_from_texts(&["One", "Two", "Three"]);
+        subs.inner[1].id = Some("intro".to_owned());
+        assert_eq!(subs.assign_ids(), 2);
+        assert_eq!(subs.assign_ids(), 0);
+        assert_eq!(subs.inner[1].id.as_ref().unwrap(), "intro");
+        let id = subs.inner[2].id.clone().unwrap();
+        assert_eq!(id.len(), 36);
+        assert_ne!(subs.inner[0].id.as_ref(), Some(&id));
+
+        subs.inner.remove(0);
+        subs.renumber();
+        assert_eq!(subs.by_id(&id).unwrap().index, 2);
+        subs.by_id_mut("intro").unwrap().text = "Zero".to_owned();
+        assert_eq!(subs.inner[0].text, "Zero");
+        assert!(subs.by_id("missing").is_none());
+    }
+
+    #[test]
+    fn iter_from() {
+        let subs = utils::subs_from_texts(&["One", "Two", "Three"]);
+        let texts = |ms| subs.iter_from(Timestamp::from_miliseconds(ms)).map(|line| &*line.text).collect::<Vec<_>>();
+        assert_eq!(texts(0), vec!["One", "Two", "Three"]);
+        assert_eq!(texts(1000), vec!["One", "Two", "Three"]);
+        assert_eq!(texts(1500), vec!["Two", "Three"]);
+        assert_eq!(texts(4500), vec!["Three"]);
+        assert!(texts(5500).is_empty());
+    }
+
+    #[test]
+    fn ties() {
+        let line = |start, end, text: &str| {
+            SubLine::new(0, text.to_owned(), Timestamp::from_miliseconds(start), Timestamp::from_miliseconds(end))
+        };
+        let mut subs = Subtitles::from(vec![line(0, 1000, "Before"),
+                                            line(2000, 4000, "Sign"),
+                                            line(3000, 3500, "Late"),
+                                            line(2000, 3000, "Dialogue"),
+                                            line(2000, 4000, "Caption")]);
+        subs.sort_by_time();
+
+        let texts = |lines: &[SubLine]| lines.iter().map(|line| line.text.clone()).collect::<Vec<_>>();
+        assert_eq!(texts(subs.all_starting_at(Timestamp::new(0, 0, 2, 0))), vec!["Dialogue", "Sign", "Caption"]);
+        assert!(subs.all_starting_at(Timestamp::new(0, 0, 1, 0)).is_empty());
+        assert_eq!(subs.by_time(Timestamp::new(0, 0, 2, 500)).unwrap().text, "Dialogue");
+        assert_eq!(subs.by_time(Timestamp::new(0, 0, 3, 200)).unwrap().text, "Sign");
+    }
+
+    #[test]
+    fn boundary() {
+        let subs = subtitles![(0, 1000, "One"), (1000, 2000, "Two"), (3000, 3000, "Empty")];
+        let at = |ms, boundary| subs.by_time_at(Timestamp::from_miliseconds(ms), boundary).map(|line| line.index);
+        assert_eq!(at(1000, Boundary::EndInclusive), Some(1));
+        assert_eq!(at(1000, Boundary::EndExclusive), Some(2));
+        assert_eq!(at(2000, Boundary::EndInclusive), Some(2));
+        assert_eq!(at(2000, Boundary::EndExclusive), None);
+        assert_eq!(at(3000, Boundary::EndInclusive), Some(3));
+        assert_eq!(at(3000, Boundary::EndExclusive), None);
+        assert_eq!(subs.by_time(Timestamp::from_miliseconds(1000)).unwrap().index, 1);
+    }
+
+    #[test]
+    fn lookup() {
+        let empty = Subtitles::default();
+        assert_eq!(empty.by_index(1), Err(StructureError::NotFound { index: 1 }));
+        assert_eq!(empty.by_index(0), Err(StructureError::NotFound { index: 0 }));
+        assert!(empty.by_time(Timestamp::new(0, 0, 1, 0)).is_none());
+        assert!(empty.nearest_by_time(Timestamp::new(0, 0, 1, 0)).is_none());
+
+        let mut subs = utils::subs_from_texts(&["One", "Two", "Three"]);
+        assert_eq!(subs.by_time(Timestamp::new(0, 0, 2, 500)).unwrap().text, "Two");
+        assert!(subs.by_time(Timestamp::new(0, 0, 1, 500)).is_none());
+        assert_eq!(subs.nearest_by_time(Timestamp::new(0, 0, 1, 500)).unwrap().text, "One");
+        assert_eq!(subs.nearest_by_time(Timestamp::new(0, 0, 9, 0)).unwrap().text, "Three");
+        assert!(subs.nearest_by_time(Timestamp::new(0, 0, 0, 0)).is_some());
+
+        subs.inner[0].start = Timestamp::new(0, 0, 0, 500);
+        assert!(subs.nearest_by_time_mut(Timestamp::new(0, 0, 0, 0)).is_none());
+        subs.by_time_mut(Timestamp::new(0, 0, 4, 0)).unwrap().text = "3".to_owned();
+        assert_eq!(subs.inner[2].text, "3");
+
+        subs.inner.swap(0, 1);
+        assert_eq!(subs.by_index(1), Err(StructureError::IndexMismatch { index: 1, found: 2 }));
+        assert_eq!(subs.by_index(4), Err(StructureError::NotFound { index: 4 }));
+    }
+
+    #[test]
+    fn by_start() {
+        let subs = utils::subs_from_texts(&["One", "Two", "Three"]);
+        let two = Timestamp::new(0, 0, 2, 0);
+
+        assert_eq!(subs.first_starting_after(two).unwrap().text, "Three");
+        assert_eq!(subs.last_starting_before(two).unwrap().text, "One");
+        assert_eq!(subs.position_by_start(two), 2);
+        assert_eq!(subs.position_by_start(Timestamp::new(0, 0, 1, 0)), 1);
+        assert!(subs.last_starting_before(Timestamp::new(0, 0, 0, 0)).is_none());
+        assert!(subs.first_starting_after(Timestamp::new(0, 0, 4, 0)).is_none());
+        assert_eq!(Subtitles::default().position_by_start(two), 0);
+    }
+
+    #[test]
+    fn insert_at() {
+        let mut subs = utils::subs_from_texts(&["One", "Two", "Three"]);
+
+        subs.try_insert_at(Timestamp::new(0, 0, 1, 200), Timestamp::new(0, 0, 1, 800), "Between".to_owned())
+            .unwrap()
+            .text += "!";
+        assert_eq!(subs.by_index(2).unwrap().text, "Between!");
+        assert_eq!(subs.by_index(4).unwrap().text, "Three");
+
+        let line = subs.try_insert_at(Timestamp::new(0, 0, 10, 0), Timestamp::new(0, 0, 11, 0), "Last".to_owned())
+            .unwrap();
+        assert_eq!(line.index, 5);
+
+        assert!(subs.insert_at_checked(Timestamp::new(0, 0, 1, 500), Timestamp::new(0, 0, 2, 500), "".to_owned())
+            .is_err());
+        assert!(subs.insert_at_checked(Timestamp::new(0, 0, 6, 0), Timestamp::new(0, 0, 5, 0), "".to_owned())
+            .is_err());
+        assert_eq!(subs.len(), 5);
+        let line = subs.insert_at_checked(Timestamp::new(0, 0, 5, 0), Timestamp::new(0, 0, 6, 0), "".to_owned())
+            .unwrap();
+        assert_eq!(line.index, 5);
+    }
+
+    #[test]
+    fn try_insert_at() {
+        let mut subs = utils::subs_from_texts(&["One", "Two", "Three"]);
+        let (one, two) = (Timestamp::new(0, 0, 1, 0), Timestamp::new(0, 0, 2, 0));
+
+        assert_eq!(subs.try_insert_at(two, one, "".to_owned()).unwrap_err(),
+                   EditError::InvalidTiming { start: two, end: one });
+        let line = subs.try_insert_at(Timestamp::new(0, 0, 1, 500), Timestamp::new(0, 0, 2, 500), "Overlapping".to_owned())
+            .unwrap();
+        assert_eq!(line.index, 2);
+        assert_eq!(subs.len(), 4);
+
+        let mut subs = utils::subs_from_texts(&["One", "Two", "Three"]);
+        assert_eq!(subs.insert_at_checked(Timestamp::new(0, 0, 0, 500), Timestamp::new(0, 0, 1, 500), "".to_owned())
+                       .unwrap_err(),
+                   EditError::Overlap { index: 1 });
+        assert_eq!(subs.insert_at_checked(Timestamp::new(0, 0, 1, 500), Timestamp::new(0, 0, 2, 500), "".to_owned())
+                       .unwrap_err(),
+                   EditError::Overlap { index: 2 });
+        assert_eq!(subs.insert_at_checked(two, one, "".to_owned()).unwrap_err(),
+                   EditError::InvalidTiming { start: two, end: one });
+        assert_eq!(subs.len(), 3);
+    }
+
+    #[test]
+    fn from_str_never_panics() {
+        let content = utils::prepare(&utils::read_file(PATH).unwrap());
+        let bytes = content.as_bytes();
+        let noise = b"0123456789:,-> \r\n\t\x0b\xc2\xa0\xe2\x80\xa8";
+
+        assert!(Subtitles::from_str("1\r\n00:00:01,000 --> 00:00:02,000\r\nText\r\n\r\n").is_ok());
+        assert!(Subtitles::from_str("99999999999999999999\r\n00:00:01,000 --> 00:00:02,000\r\nText\r\n\r\n")
+            .is_err());
+        assert!(Subtitles::from_str("1\r\n4294967295:00:01,000 --> 4294967295:00:02,000\r\nText\r\n\r\n")
+            .is_err());
+        assert!(Subtitles::from_str(&" \r\n\t".repeat(10000)).unwrap().inner.is_empty());
+
+        // Simple deterministic fuzzing: mutate random bytes of a valid file
+        let mut seed: u64 = 0x5eed;
+        let mut random = |bound: usize| {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (seed >> 33) as usize % bound
+        };
+        for _ in 0..200 {
+            let start = random(bytes.len());
+            let mut mutated = bytes[start..(start + 2000).min(bytes.len())].to_vec();
+            for _ in 0..random(20) {
+                let position = random(mutated.len() + 1);
+                match random(3) {
+                    0 => mutated.insert(position, noise[random(noise.len())]),
+                    1 if position < mutated.len() => mutated[position] = noise[random(noise.len())],
+                    _ => mutated.truncate(position),
+                }
+            }
+            let mutated = String::from_utf8_lossy(&mutated);
+            let _ = Subtitles::from_str(&mutated);
+            let _ = Subtitles::from_str(&utils::prepare(&mutated));
+        }
+    }
+
+    #[test]
+    fn hundred_hours() {
+        let content = "1\r\n99:59:59,000 --> 100:00:01,500\r\nText\r\n\r\n\
+                       2\r\n1234:00:00,000 --> 1234:00:01,000\r\nMore\r\n\r\n";
+        let subs = Subtitles::from_str(content).unwrap();
+        assert_eq!(subs.len(), 2);
+        assert_eq!(subs.inner[0].end, Timestamp::new(100, 0, 1, 500));
+        assert_eq!(subs.inner[1].start.total_miliseconds(), 1234 * 3_600_000);
+        assert_eq!(subs.to_string(), format!("{}\r\n\r\n", content));
+    }
 }
@@ -0,0 +1,364 @@
+use std::collections::HashMap;
+
+use crate::timestamp::Timestamp;
+use crate::subtitles::Subtitles;
+
+/// Maximal offset searched by ```Subtitles::estimate_sync```, in miliseconds.
+const MAX_OFFSET: i64 = 10 * 60 * 1000;
+/// Offsets are voted in bins of this size, in miliseconds.
+const BIN: i64 = 100;
+/// Lines are paired for the drift estimation if their starts
+/// are that close after the offset correction, in miliseconds.
+const PAIR_TOLERANCE: i64 = 1000;
+
+/// Linear timing correction: ```corrected = time * scale + offset```.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SyncCorrection {
+    /// Offset in miliseconds.
+    pub offset: i64,
+    /// Speed ratio, ```1.0``` if there is no drift.
+    pub scale: f64,
+}
+
+impl SyncCorrection {
+    /// Corrects given ```time```, negative results are clamped to zero.
+    pub fn apply(&self, time: Timestamp) -> Timestamp {
+        let corrected = (time.total_miliseconds() as f64 * self.scale).round() as i64 + self.offset;
+        if corrected < 0 {
+            Timestamp::new(0, 0, 0, 0)
+        } else {
+            Timestamp::from_miliseconds(corrected as u64)
+        }
+    }
+}
+
+/// Options for ```Subtitles::align_to_segments```.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlignOptions {
+    /// Lines' boundaries are snapped to segments' boundaries not farther than that.
+    pub max_shift: Timestamp,
+    /// Boundaries are not moved if the line becomes shorter than that.
+    pub min_duration: Timestamp,
+    /// Before snapping shift the whole track by the median distance
+    /// between lines' starts and the nearest segments' starts,
+    /// to fix systematically early or late timing.
+    pub correct_offset: bool,
+}
+
+impl Default for AlignOptions {
+    fn default() -> AlignOptions {
+        AlignOptions {
+            max_shift: Timestamp::new(0, 0, 0, 500),
+            min_duration: Timestamp::new(0, 0, 0, 500),
+            correct_offset: true,
+        }
+    }
+}
+
+impl Subtitles {
+    /// Estimates correction that moves lines' starts onto the starts of correctly
+    /// timed ```reference``` track (e.g. the original language track of the same video).
+    /// Tracks don't have to contain the same lines, only similar timing patterns.
+    ///
+    /// Offset is found by voting of all start differences up to 10 minutes.
+    /// If ```drift``` is set, voting is repeated for the common frame rate
+    /// conversions (e.g. 25 to 23.976 fps) and the best speed ratio
+    /// is then refined by the least squares over the paired lines.
+    ///
+    /// Returns ```None``` if either track is empty.
+    pub fn estimate_sync(&self, reference: &Subtitles, drift: bool) -> Option<SyncCorrection> {
+        let reference_starts = starts(reference);
+        let starts = starts(self);
+        if starts.is_empty() || reference_starts.is_empty() {
+            return None;
+        }
+
+        let scales: &[f64] = if drift { SCALES } else { &[1.0] };
+        let mut best: Option<(usize, SyncCorrection)> = None;
+        for &scale in scales {
+            let scaled: Vec<i64> = starts.iter().map(|&start| (start as f64 * scale).round() as i64).collect();
+            if let Some((score, offset)) = vote(&scaled, &reference_starts) {
+                if best.map(|(best_score, _)| score > best_score).unwrap_or(true) {
+                    best = Some((score,
+                                 SyncCorrection {
+                                     offset,
+                                     scale,
+                                 }));
+                }
+            }
+        }
+
+        best.map(|(_, correction)| {
+            if drift {
+                fit_drift(&starts, &reference_starts, &correction).unwrap_or(correction)
+            } else {
+                correction
+            }
+        })
+    }
+
+    /// Applies given correction to all lines.
+    pub fn apply_sync(&mut self, correction: &SyncCorrection) {
+        for line in &mut self.inner {
+            line.start = correction.apply(line.start);
+            line.end = correction.apply(line.end);
+        }
+    }
+
+    /// Estimates correction against the ```reference``` track (see ```estimate_sync```)
+    /// and applies it. Returns the applied correction.
+    pub fn sync_to_reference(&mut self, reference: &Subtitles, drift: bool) -> Option<SyncCorrection> {
+        let correction = self.estimate_sync(reference, drift);
+        if let Some(ref correction) = correction {
+            self.apply_sync(correction);
+        }
+        correction
+    }
+}
+
+impl Subtitles {
+    /// Nudges lines' starts and ends onto the nearest boundaries of
+    /// given speech segments, e.g. produced by a voice activity detector.
+    /// Lines are never moved over their neighbours.
+    ///
+    /// Returns the number of changed lines.
+    pub fn align_to_segments(&mut self, speech_segments: &[(Timestamp, Timestamp)], options: &AlignOptions) -> usize {
+        let mut segment_starts: Vec<i64> = speech_segments.iter().map(|s| s.0.total_miliseconds() as i64).collect();
+        let mut segment_ends: Vec<i64> = speech_segments.iter().map(|s| s.1.total_miliseconds() as i64).collect();
+        segment_starts.sort();
+        segment_ends.sort();
+        if segment_starts.is_empty() || self.inner.is_empty() {
+            return 0;
+        }
+
+        let max_shift = options.max_shift.total_miliseconds() as i64;
+        let min_duration = options.min_duration.total_miliseconds() as i64;
+        let mut bounds: Vec<(i64, i64)> = self.inner
+            .iter()
+            .map(|line| (line.start.total_miliseconds() as i64, line.end.total_miliseconds() as i64))
+            .collect();
+
+        if options.correct_offset {
+            let mut distances: Vec<i64> = bounds.iter()
+                .filter_map(|&(start, _)| nearest(&segment_starts, start, max_shift).map(|nearest| nearest - start))
+                .collect();
+            distances.sort();
+            if let Some(&offset) = distances.get(distances.len() / 2) {
+                for bound in &mut bounds {
+                    // Keep duration of lines that would start before zero
+                    let offset = if bound.0 + offset < 0 { -bound.0 } else { offset };
+                    bound.0 += offset;
+                    bound.1 += offset;
+                }
+            }
+        }
+
+        for i in 0..bounds.len() {
+            let (start, end) = bounds[i];
+            let previous_end = if i > 0 { bounds[i - 1].1 } else { 0 };
+            let next_start = bounds.get(i + 1).map(|next| next.0).unwrap_or(i64::MAX);
+
+            let new_start = nearest(&segment_starts, start, max_shift)
+                .map(|nearest| if nearest < previous_end { previous_end } else { nearest })
+                .unwrap_or(start);
+            let new_end = nearest(&segment_ends, end, max_shift)
+                .map(|nearest| if nearest > next_start { next_start } else { nearest })
+                .unwrap_or(end);
+
+            if new_end - new_start >= min_duration {
+                bounds[i] = (new_start, new_end);
+            }
+        }
+
+        let mut changed = 0;
+        for (line, &(start, end)) in self.inner.iter_mut().zip(&bounds) {
+            let start = Timestamp::from_miliseconds(start as u64);
+            let end = Timestamp::from_miliseconds(end as u64);
+            if line.start != start || line.end != end {
+                line.start = start;
+                line.end = end;
+                changed += 1;
+            }
+        }
+        changed
+    }
+}
+
+/// Returns the value from sorted ```values``` nearest to ```target```, if not farther than ```max_distance```.
+fn nearest(values: &[i64], target: i64, max_distance: i64) -> Option<i64> {
+    let position = match values.binary_search(&target) {
+        Ok(position) => return Some(values[position]),
+        Err(position) => position,
+    };
+
+    let before = if position > 0 { Some(values[position - 1]) } else { None };
+    let after = values.get(position).cloned();
+    let nearest = match (before, after) {
+        (Some(before), Some(after)) => if target - before <= after - target { before } else { after },
+        (Some(value), None) | (None, Some(value)) => value,
+        (None, None) => return None,
+    };
+
+    if (nearest - target).abs() <= max_distance {
+        Some(nearest)
+    } else {
+        None
+    }
+}
+
+/// Speed ratios of the common frame rate conversions.
+static SCALES: &[f64] = &[1.0,
+                          25.0 / 23.976,
+                          23.976 / 25.0,
+                          25.0 / 24.0,
+                          24.0 / 25.0,
+                          24.0 / 23.976,
+                          23.976 / 24.0,
+                          30.0 / 29.97,
+                          29.97 / 30.0];
+
+fn starts(subs: &Subtitles) -> Vec<i64> {
+    let mut starts: Vec<i64> = subs.inner.iter().map(|line| line.start.total_miliseconds() as i64).collect();
+    starts.sort();
+    starts
+}
+
+/// Returns the most voted offset and the number of its votes.
+fn vote(starts: &[i64], reference_starts: &[i64]) -> Option<(usize, i64)> {
+    let mut votes: HashMap<i64, Vec<i64>> = HashMap::new();
+    let mut first = 0;
+    for &start in starts {
+        while first < reference_starts.len() && reference_starts[first] < start - MAX_OFFSET {
+            first += 1;
+        }
+        for &reference_start in reference_starts[first..].iter().take_while(|&&r| r <= start + MAX_OFFSET) {
+            let difference = reference_start - start;
+            votes.entry(difference.div_euclid(BIN)).or_default().push(difference);
+        }
+    }
+
+    // Bins are smoothed with their neighbours, ties are resolved towards smaller offset
+    let count = |bin: i64| votes.get(&bin).map(|differences| differences.len()).unwrap_or(0);
+    let best = votes.keys()
+        .map(|&bin| (count(bin - 1) + count(bin) + count(bin + 1), -bin.abs(), bin))
+        .max();
+
+    best.map(|(score, _, bin)| {
+        let mut differences: Vec<i64> = (bin - 1..bin + 2)
+            .filter_map(|b| votes.get(&b))
+            .flat_map(|differences| differences.iter().cloned())
+            .collect();
+        differences.sort();
+        (score, differences[differences.len() / 2])
+    })
+}
+
+/// Fits ```reference = start * scale + offset``` over the starts paired by ```estimate```.
+fn fit_drift(starts: &[i64], reference_starts: &[i64], estimate: &SyncCorrection) -> Option<SyncCorrection> {
+    let mut pairs = Vec::new();
+    let mut first = 0;
+    for &start in starts {
+        let shifted = (start as f64 * estimate.scale).round() as i64 + estimate.offset;
+        while first < reference_starts.len() && reference_starts[first] < shifted - PAIR_TOLERANCE {
+            first += 1;
+        }
+        let nearest = reference_starts[first..]
+            .iter()
+            .take_while(|&&r| r <= shifted + PAIR_TOLERANCE)
+            .min_by_key(|&&r| (r - shifted).abs());
+        if let Some(&reference_start) = nearest {
+            pairs.push((start as f64, reference_start as f64));
+        }
+    }
+
+    if pairs.len() < 2 {
+        return None;
+    }
+
+    let n = pairs.len() as f64;
+    let mean_x = pairs.iter().map(|&(x, _)| x).sum::<f64>() / n;
+    let mean_y = pairs.iter().map(|&(_, y)| y).sum::<f64>() / n;
+    let covariance: f64 = pairs.iter().map(|&(x, y)| (x - mean_x) * (y - mean_y)).sum();
+    let variance: f64 = pairs.iter().map(|&(x, _)| (x - mean_x) * (x - mean_x)).sum();
+    if variance == 0.0 {
+        return None;
+    }
+
+    let scale = covariance / variance;
+    Some(SyncCorrection {
+        offset: (mean_y - scale * mean_x).round() as i64,
+        scale,
+    })
+}
+
+#[cfg(test)]
+mod sync_tests {
+    use std::sync::LazyLock;
+
+    use super::*;
+    use crate::subtitles::Subtitles;
+    use crate::timestamp::Timestamp;
+    use crate::utils;
+
+    static PATH: &str = "example.srt";
+
+    static SUBS: LazyLock<Subtitles> = LazyLock::new(|| Subtitles::from_file(PATH).unwrap());
+
+    #[test]
+    fn sync_to_reference() {
+        let reference = SUBS.clone();
+        let mut subs = SUBS.clone();
+        // Translation is 3.2 seconds late and lacks every fifth line
+        subs.inner.retain(|line| line.index % 5 != 0);
+        subs.renumber();
+        let offset = Timestamp::new(0, 0, 3, 200);
+        for line in &mut subs {
+            line.start += offset;
+            line.end += offset;
+        }
+
+        let correction = subs.sync_to_reference(&reference, false).unwrap();
+        assert_eq!(correction, SyncCorrection { offset: -3200, scale: 1.0 });
+        assert_eq!(subs[0], reference[0]);
+        assert_eq!((subs[4].start, subs[4].end), (reference[5].start, reference[5].end));
+    }
+
+    #[test]
+    fn drift() {
+        let reference = SUBS.clone();
+        let mut subs = SUBS.clone();
+        // Translation was timed for 25 fps video, while reference is 23.976 fps
+        let slowdown = SyncCorrection { offset: 1500, scale: 25.0 / 23.976 };
+        subs.apply_sync(&slowdown);
+
+        let correction = subs.sync_to_reference(&reference, true).unwrap();
+        assert!((correction.scale - 23.976 / 25.0).abs() < 0.0001);
+        for (line, reference_line) in subs.into_iter().zip(&reference) {
+            let difference = line.start.total_miliseconds() as i64 - reference_line.start.total_miliseconds() as i64;
+            assert!(difference.abs() <= 2);
+        }
+    }
+
+    #[test]
+    fn align_to_segments() {
+        let mut subs = utils::subs_from_texts(&["One", "Two", "Three", "Four"]);
+        // Every line is timed 200 ms early, speech of "Three" actually ends later
+        let segments: Vec<(Timestamp, Timestamp)> = vec![(200, 1150), (2200, 3200), (4200, 5500), (6250, 7200)]
+            .into_iter()
+            .map(|(start, end)| (Timestamp::from_miliseconds(start), Timestamp::from_miliseconds(end)))
+            .collect();
+
+        assert_eq!(subs.align_to_segments(&segments, &AlignOptions::default()), 4);
+
+        let bounds: Vec<(u64, u64)> = subs.into_iter()
+            .map(|line| (line.start.total_miliseconds(), line.end.total_miliseconds()))
+            .collect();
+        assert_eq!(bounds, vec![(200, 1150), (2200, 3200), (4200, 5500), (6250, 7200)]);
+    }
+
+    #[test]
+    fn empty() {
+        let mut subs = SUBS.clone();
+        assert_eq!(subs.sync_to_reference(&Subtitles::default(), true), None);
+    }
+}
@@ -0,0 +1,505 @@
+use std::ops::Range;
+use std::str::FromStr;
+
+use crate::subline::SubLine;
+use crate::subtitles::Subtitles;
+use crate::color::Color;
+
+/// Html-like formatting tag of SubRip text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Tag {
+    /// ```<i>```
+    Italic,
+    /// ```<b>```
+    Bold,
+    /// ```<u>```
+    Underline,
+    /// ```<s>```
+    Strikethrough,
+    /// ```<font color="...">```
+    Font,
+}
+
+impl Tag {
+    /// Lowercase tag name, e.g. ```"i"```.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Tag::Italic => "i",
+            Tag::Bold => "b",
+            Tag::Underline => "u",
+            Tag::Strikethrough => "s",
+            Tag::Font => "font",
+        }
+    }
+
+    /// Tag by its case-insensitive name.
+    pub fn from_name(name: &str) -> Option<Tag> {
+        match &*name.to_lowercase() {
+            "i" => Some(Tag::Italic),
+            "b" => Some(Tag::Bold),
+            "u" => Some(Tag::Underline),
+            "s" => Some(Tag::Strikethrough),
+            "font" => Some(Tag::Font),
+            _ => None,
+        }
+    }
+}
+
+/// Styles applied to the whole text of a line, see ```SubLine::styles```.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CueStyles {
+    pub italic: bool,
+    pub bold: bool,
+    pub underline: bool,
+    /// Color of ```<font color="...">```.
+    pub color: Option<Color>,
+}
+
+/// Piece of the text: plain text or a tag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Token<'a> {
+    Text(&'a str),
+    Open {
+        /// Lowercase tag name.
+        name: String,
+        attributes: &'a str,
+        raw: &'a str,
+    },
+    Close { name: String, raw: &'a str },
+}
+
+impl<'a> Token<'a> {
+    pub fn raw(&self) -> &'a str {
+        match *self {
+            Token::Text(raw) |
+            Token::Open { raw, .. } |
+            Token::Close { raw, .. } => raw,
+        }
+    }
+}
+
+/// Names of tags recognized by ```tokenize```: tags of SubRip and WebVTT.
+static KNOWN: &[&str] = &["i", "b", "u", "s", "font", "c", "v", "lang", "ruby", "rt"];
+
+/// Splits text into plain text and html-like tags. Only known tags of SubRip and WebVTT
+/// (```<i>```, ```<font ...>```, ```<v Name>```, ...) with the name right after ```<```
+/// or ```</``` are tags, so text such as ```a < b && c > d``` stays text.
+pub fn tokenize(text: &str) -> Vec<Token<'_>> {
+    tokens(text, true)
+}
+
+/// Same as ```tokenize```, but tags with any name are tags,
+/// e.g. for markup formats such as RealText.
+#[cfg(any(feature = "formats-realtext", test))]
+pub(crate) fn tokenize_markup(text: &str) -> Vec<Token<'_>> {
+    tokens(text, false)
+}
+
+fn tokens(text: &str, known_only: bool) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut last = 0;
+    let mut position = 0;
+    while let Some(found) = text[position..].find('<') {
+        let start = position + found;
+        position = start + 1;
+        let (closing, name, attributes, end) = match tag(&text[start..]) {
+            Some((closing, name, attributes, len)) => (closing, name, attributes, start + len),
+            None => continue,
+        };
+        let name = name.to_lowercase();
+        if known_only && !KNOWN.contains(&&*name) {
+            continue;
+        }
+        if start > last {
+            tokens.push(Token::Text(&text[last..start]));
+        }
+        let raw = &text[start..end];
+        tokens.push(if !closing {
+            Token::Open {
+                name,
+                attributes,
+                raw,
+            }
+        } else {
+            Token::Close {
+                name,
+                raw,
+            }
+        });
+        last = end;
+        position = end;
+    }
+    if last < text.len() {
+        tokens.push(Token::Text(&text[last..]));
+    }
+    tokens
+}
+
+/// Parses a tag at the start of ```text``` (```<i>```, ```</font>```, ```<font color="red">```),
+/// returns whether it's closing, its name, attributes and length.
+fn tag(text: &str) -> Option<(bool, &str, &str, usize)> {
+    let rest = text.strip_prefix('<')?;
+    let (closing, rest) = match rest.strip_prefix('/') {
+        Some(rest) => (true, rest),
+        None => (false, rest),
+    };
+    let name_len = rest.find(|c: char| !c.is_ascii_alphabetic()).unwrap_or(rest.len());
+    if name_len == 0 {
+        return None;
+    }
+    let attributes = &rest[name_len..];
+    // Another '<' before the end means it's text, e.g. a comparison
+    let end = attributes.find(['<', '>']).filter(|&end| attributes[end..].starts_with('>'))?;
+    Some((closing, &rest[..name_len], &attributes[..end], text.len() - attributes.len() + end + 1))
+}
+
+/// Value of the ```color``` attribute.
+pub fn color_attribute(attributes: &str) -> Option<String> {
+    color(attributes, 0).map(|(_, value, _)| attributes[value].to_owned())
+}
+
+/// Finds the ```color``` attribute (case insensitive, with optional quotes) after ```from```.
+/// Returns the range of the attribute with the whitespace before it, the range of its value
+/// and the position from which the next attribute is searched.
+fn color(attributes: &str, from: usize) -> Option<(Range<usize>, Range<usize>, usize)> {
+    let lowercase = attributes.to_ascii_lowercase();
+    let mut position = from;
+    while let Some(found) = lowercase[position..].find("color") {
+        let name = position + found;
+        position = name + 1;
+
+        let rest = attributes[name + "color".len()..].trim_start();
+        let rest = match rest.strip_prefix('=') {
+            Some(rest) => rest.trim_start(),
+            None => continue,
+        };
+        let rest = rest.strip_prefix(['"', '\'']).unwrap_or(rest);
+        let value_len = rest.find(|c: char| c == '"' || c == '\'' || c == '>' || c.is_whitespace()).unwrap_or(rest.len());
+        if value_len == 0 {
+            continue;
+        }
+        let value_start = attributes.len() - rest.len();
+        let value = value_start..value_start + value_len;
+        let after = &rest[value_len..];
+        let end = value.end + if after.starts_with(['"', '\'']) { 1 } else { 0 };
+        let start = attributes[from..name].trim_end().len() + from;
+        return Some((start..end, value, end));
+    }
+    None
+}
+
+/// Removes all ```color``` attributes.
+fn remove_colors(attributes: &str) -> String {
+    let mut result = String::with_capacity(attributes.len());
+    let mut last = 0;
+    while let Some((attribute, _, next)) = color(attributes, last) {
+        result.push_str(&attributes[last..attribute.start]);
+        last = next;
+    }
+    result.push_str(&attributes[last..]);
+    result
+}
+
+/// Removes tags enclosing the whole ```text```, returns them
+/// (as names and attributes) and the text inside of them.
+fn peel(text: &str) -> (Vec<(String, String)>, String) {
+    let mut wrappers = Vec::new();
+    let mut text = text.trim().to_owned();
+
+    loop {
+        let peeled = {
+            let tokens = tokenize(&text);
+            match (tokens.first(), tokens.last()) {
+                (Some(&Token::Open { ref name, attributes, raw }), Some(&Token::Close { name: ref last_name, raw: last_raw }))
+                    if name == last_name && tokens.len() > 1 => {
+                    // The opening tag must be closed by the last one, not earlier
+                    let mut depth = 0;
+                    let mut closed_at_end = true;
+                    for (i, token) in tokens.iter().enumerate() {
+                        match *token {
+                            Token::Open { name: ref other, .. } if other == name => depth += 1,
+                            Token::Close { name: ref other, .. } if other == name => {
+                                depth -= 1;
+                                if depth == 0 && i != tokens.len() - 1 {
+                                    closed_at_end = false;
+                                    break;
+                                }
+                            }
+                            _ => {}
+                        }
+                    }
+                    if closed_at_end {
+                        let inner = text[raw.len()..text.len() - last_raw.len()].trim().to_owned();
+                        Some(((name.clone(), attributes.to_owned()), inner))
+                    } else {
+                        None
+                    }
+                }
+                _ => None,
+            }
+        };
+        match peeled {
+            Some((wrapper, inner)) => {
+                wrappers.push(wrapper);
+                text = inner;
+            }
+            None => return (wrappers, text),
+        }
+    }
+}
+
+fn apply(styles: &mut CueStyles, wrappers: &[(String, String)]) {
+    for (name, attributes) in wrappers {
+        match Tag::from_name(name) {
+            Some(Tag::Italic) => styles.italic = true,
+            Some(Tag::Bold) => styles.bold = true,
+            Some(Tag::Underline) => styles.underline = true,
+            Some(Tag::Font) => {
+                if let Some(color) = color_attribute(attributes).and_then(|color| Color::from_str(&color).ok()) {
+                    styles.color = Some(color);
+                }
+            }
+            Some(Tag::Strikethrough) | None => {}
+        }
+    }
+}
+
+impl SubLine {
+    /// Styles applied to the whole text, either by tags enclosing all of it
+    /// or by the same tags enclosing every row of it.
+    pub fn styles(&self) -> CueStyles {
+        let (wrappers, inner) = peel(&self.text);
+        let mut styles = CueStyles::default();
+        apply(&mut styles, &wrappers);
+
+        let mut rows = inner.lines().filter(|row| !row.trim().is_empty()).map(|row| {
+            let mut row_styles = CueStyles::default();
+            apply(&mut row_styles, &peel(row).0);
+            row_styles
+        });
+        if let Some(first) = rows.next() {
+            let common = rows.fold(first, |common, row| {
+                CueStyles {
+                    italic: common.italic && row.italic,
+                    bold: common.bold && row.bold,
+                    underline: common.underline && row.underline,
+                    color: if common.color == row.color { common.color } else { None },
+                }
+            });
+            styles.italic |= common.italic;
+            styles.bold |= common.bold;
+            styles.underline |= common.underline;
+            if styles.color.is_none() {
+                styles.color = common.color;
+            }
+        }
+        styles
+    }
+
+    /// Makes the whole text italic or not, removing italic spans inside of it.
+    pub fn set_italic(&mut self, italic: bool) {
+        self.set_tag(Tag::Italic, italic);
+    }
+
+    /// Makes the whole text bold or not, removing bold spans inside of it.
+    pub fn set_bold(&mut self, bold: bool) {
+        self.set_tag(Tag::Bold, bold);
+    }
+
+    /// Makes the whole text underlined or not, removing underlined spans inside of it.
+    pub fn set_underline(&mut self, underline: bool) {
+        self.set_tag(Tag::Underline, underline);
+    }
+
+    /// Sets color of the whole text or removes all colors, other ```<font>```
+    /// attributes are kept.
+    pub fn set_color(&mut self, color: Option<Color>) {
+        let mut text = String::with_capacity(self.text.len());
+        // Whether every open font tag is kept, to drop the matching closing tags
+        let mut kept = Vec::new();
+
+        for token in tokenize(&self.text) {
+            match token {
+                Token::Open { ref name, attributes, raw } if name == "font" => {
+                    if color_attribute(attributes).is_none() {
+                        kept.push(true);
+                        text.push_str(raw);
+                        continue;
+                    }
+                    let attributes = remove_colors(attributes);
+                    let keep = !attributes.trim().is_empty();
+                    if keep {
+                        text.push_str(&format!("<font {}>", attributes.trim()));
+                    }
+                    kept.push(keep);
+                }
+                Token::Close { ref name, raw } if name == "font" => {
+                    if kept.pop().unwrap_or(true) {
+                        text.push_str(raw);
+                    }
+                }
+                token => text.push_str(token.raw()),
+            }
+        }
+
+        self.text = match color {
+            Some(color) => format!("<font color=\"{}\">{}</font>", color, text),
+            None => text,
+        };
+    }
+
+    fn set_tag(&mut self, tag: Tag, enabled: bool) {
+        let mut text: String = tokenize(&self.text)
+            .into_iter()
+            .filter(|token| match *token {
+                Token::Open { ref name, .. } |
+                Token::Close { ref name, .. } => name != tag.name(),
+                Token::Text(_) => true,
+            })
+            .map(|token| token.raw())
+            .collect();
+        if enabled {
+            text = format!("<{0}>{1}</{0}>", tag.name(), text);
+        }
+        self.text = text;
+    }
+}
+
+impl Subtitles {
+    /// Fixes tags of all lines: removes unknown and not ```allowed``` tags
+    /// and closing tags without opening ones, closes unclosed tags,
+    /// fixes wrong nesting and makes tag names lowercase.
+    /// Text which isn't a tag, such as ```a < b && c > d```, is kept.
+    ///
+    /// Returns the number of changed lines.
+    pub fn sanitize_tags(&mut self, allowed: &[Tag]) -> usize {
+        let mut changed = 0;
+        for line in &mut self.inner {
+            let sanitized = sanitize(&line.text, allowed);
+            if sanitized != line.text {
+                line.text = sanitized;
+                changed += 1;
+            }
+        }
+        changed
+    }
+}
+
+fn sanitize(text: &str, allowed: &[Tag]) -> String {
+    let mut result = String::with_capacity(text.len());
+    // Open tags as names and normalized opening tags
+    let mut open: Vec<(&'static str, String)> = Vec::new();
+
+    for token in tokens(text, false) {
+        match token {
+            Token::Text(text) => result.push_str(text),
+            Token::Open { ref name, attributes, .. } => {
+                if let Some(tag) = Tag::from_name(name).filter(|tag| allowed.contains(tag)) {
+                    let opening = format!("<{}{}>", tag.name(), attributes.trim_end());
+                    result.push_str(&opening);
+                    open.push((tag.name(), opening));
+                }
+            }
+            Token::Close { ref name, .. } => {
+                if let Some(position) = open.iter().rposition(|&(open_name, _)| open_name == name) {
+                    // Close inner tags as well and reopen them after
+                    let reopened = open.split_off(position + 1);
+                    for &(inner, _) in reopened.iter().rev() {
+                        result.push_str(&format!("</{}>", inner));
+                    }
+                    result.push_str(&format!("</{}>", open.pop().unwrap().0));
+                    for (inner, opening) in reopened {
+                        result.push_str(&opening);
+                        open.push((inner, opening));
+                    }
+                }
+            }
+        }
+    }
+    for &(name, _) in open.iter().rev() {
+        result.push_str(&format!("</{}>", name));
+    }
+    result
+}
+
+#[cfg(test)]
+mod tags_tests {
+    use super::*;
+    use crate::timestamp::Timestamp;
+    use crate::utils;
+
+    fn line(text: &str) -> SubLine {
+        SubLine::new(1, text.to_owned(), Timestamp::new(0, 0, 0, 0), Timestamp::new(0, 0, 1, 0))
+    }
+
+    #[test]
+    fn styles() {
+        assert_eq!(line("Plain text").styles(), CueStyles::default());
+        let styles = line("<b><font color=\"#ff0000\">Red</font></b>").styles();
+        assert!(styles.bold && !styles.italic);
+        assert_eq!(styles.color, Some(Color::rgb(255, 0, 0)));
+
+        assert!(line("<i>One</i>\r\n<i>Two</i>").styles().italic);
+        assert!(line("<I>One\r\nTwo</I>").styles().italic);
+        assert!(!line("<i>One</i> and <i>two</i>").styles().italic);
+        assert!(!line("<i>One</i>\r\nTwo").styles().italic);
+    }
+
+    #[test]
+    fn set_styles() {
+        let mut subline = line("Some <i>partly</i> italic");
+        subline.set_italic(true);
+        assert_eq!(subline.text, "<i>Some partly italic</i>");
+        assert!(subline.styles().italic);
+        subline.set_italic(false);
+        assert_eq!(subline.text, "Some partly italic");
+
+        subline.set_bold(true);
+        subline.set_underline(true);
+        assert_eq!(subline.text, "<u><b>Some partly italic</b></u>");
+
+        let mut subline = line("<font color=\"red\" face=\"Arial\">A</font> <font color=red>B</font>");
+        subline.set_color(None);
+        assert_eq!(subline.text, "<font face=\"Arial\">A</font> B");
+        subline.set_color(Some(Color::rgb(0, 255, 0)));
+        assert_eq!(subline.text, "<font color=\"#00ff00\"><font face=\"Arial\">A</font> B</font>");
+        assert_eq!(subline.styles().color, Some(Color::rgb(0, 255, 0)));
+    }
+
+    #[test]
+    fn _tokenize() {
+        assert_eq!(tokenize("a < b && c > d <x>"), vec![Token::Text("a < b && c > d <x>")]);
+        assert_eq!(tokenize("<I>A</ i>"),
+                   vec![Token::Open { name: "i".to_owned(), attributes: "", raw: "<I>" }, Token::Text("A</ i>")]);
+        assert_eq!(tokenize_markup("<clear/>A").len(), 2);
+    }
+
+    #[test]
+    fn sanitize_tags() {
+        let allowed = [Tag::Italic, Tag::Bold, Tag::Font];
+        let mut subs = utils::subs_from_texts(&["<I>Unclosed",
+                                                "Stray</i> <u>tag</u>",
+                                                "<i><b>Wrong</i> nesting</b>",
+                                                "<font color=\"red\">Fine</font>"]);
+
+        assert_eq!(subs.sanitize_tags(&allowed), 3);
+        let texts: Vec<&str> = subs.inner.iter().map(|line| &*line.text).collect();
+        assert_eq!(texts,
+                   vec!["<i>Unclosed</i>",
+                        "Stray tag",
+                        "<i><b>Wrong</b></i><b> nesting</b>",
+                        "<font color=\"red\">Fine</font>"]);
+        assert_eq!(subs.sanitize_tags(&allowed), 0);
+
+        // Comparisons aren't tags
+        let mut subs = utils::subs_from_texts(&["a < b && c > d", "<i>x <y</i> z>"]);
+        assert_eq!(subs.sanitize_tags(&[Tag::Italic]), 0);
+        assert_eq!(subs.inner[0].text, "a < b && c > d");
+        assert_eq!(subs.inner[1].text, "<i>x <y</i> z>");
+
+        let mut subs = utils::subs_from_texts(&["<blink>x</blink>", "<span class=\"a\">y</span> <i>z</i>", "1 < 2 <BLINK>"]);
+        assert_eq!(subs.sanitize_tags(&[Tag::Italic]), 3);
+        let texts: Vec<&str> = subs.inner.iter().map(|line| &*line.text).collect();
+        assert_eq!(texts, vec!["x", "y <i>z</i>", "1 < 2 "]);
+    }
+}
@@ -0,0 +1,617 @@
+use std::io::{Error, ErrorKind};
+
+use crate::timestamp::Timestamp;
+use crate::subtitles::Subtitles;
+use crate::subline::SubLine;
+use crate::utils;
+
+/// Largest number of intervals ```Subtitles::density``` and ```Subtitles::character_density``` return.
+pub const MAX_BUCKETS: u64 = 1_000_000;
+
+/// What ```Subtitles::clamp_to``` does with lines which start before the end
+/// of the media, but end after it. Lines starting after the end are always dropped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClampPolicy {
+    /// Line's end is moved to the end of the media.
+    Truncate,
+    /// Line is dropped.
+    Drop,
+}
+
+/// Changes made by ```Subtitles::clamp_to```, lines' indices are the ones before the clamping.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ClampReport {
+    pub truncated: Vec<u32>,
+    pub dropped: Vec<u32>,
+}
+
+impl ClampReport {
+    /// Returns ```true``` if nothing was changed.
+    pub fn is_empty(&self) -> bool {
+        self.truncated.is_empty() && self.dropped.is_empty()
+    }
+}
+
+/// Result of ```Subtitles::check_against_duration```.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CoverageReport {
+    /// Start of the first line, ```None``` if there are no lines.
+    pub first_start: Option<Timestamp>,
+    /// Latest end of lines, ```None``` if there are no lines.
+    pub last_end: Option<Timestamp>,
+    /// Part of the media during which a line is shown, see ```Subtitles::coverage```.
+    pub coverage: f32,
+    /// The first line starts after the first quarter of the media.
+    pub late_start: bool,
+    /// All lines end before the last quarter of the media.
+    pub early_end: bool,
+    /// Lines ending after the end of the media.
+    pub past_end: Vec<u32>,
+}
+
+impl CoverageReport {
+    /// Returns ```true``` if the subtitles probably belong to another video:
+    /// there are no lines, they start late, end early or go past the end of the media.
+    pub fn is_suspicious(&self) -> bool {
+        self.last_end.is_none() || self.late_start || self.early_end || !self.past_end.is_empty()
+    }
+}
+
+/// Changes made by ```Subtitles::shift```, lines' indices are the ones before the shift.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ShiftReport {
+    /// Lines which would end at or before zero.
+    pub dropped: Vec<u32>,
+    /// Lines which would start before zero, they start at zero instead.
+    pub clamped: Vec<u32>,
+    /// Offset in miliseconds by which the rest of lines were moved.
+    pub applied_offset: i64,
+}
+
+impl Subtitles {
+    /// Moves all lines and their words by ```offset``` miliseconds (negative offset moves them earlier).
+    /// Lines which would end at or before zero are dropped and the rest renumbered,
+    /// lines which would start before zero start at zero.
+    pub fn shift(&mut self, offset: i64) -> ShiftReport {
+        let mut report = ShiftReport { applied_offset: offset, ..ShiftReport::default() };
+
+        self.inner.retain_mut(|line| {
+            if (line.end.total_miliseconds() as i64).saturating_add(offset) <= 0 {
+                report.dropped.push(line.index);
+                return false;
+            }
+            if (line.start.total_miliseconds() as i64).saturating_add(offset) < 0 {
+                report.clamped.push(line.index);
+            }
+            shift_line(line, offset);
+            true
+        });
+
+        if !report.dropped.is_empty() {
+            self.renumber();
+        }
+        report
+    }
+
+    /// Makes all lines end within the media of given ```duration```.
+    /// Lines are renumbered if some of them were dropped.
+    pub fn clamp_to(&mut self, duration: Timestamp, policy: ClampPolicy) -> ClampReport {
+        let mut report = ClampReport::default();
+
+        self.inner.retain_mut(|line| {
+            if line.end <= duration {
+                true
+            } else if line.start < duration && policy == ClampPolicy::Truncate {
+                line.end = duration;
+                line.words.retain(|word| word.start < duration);
+                for word in &mut line.words {
+                    word.end = word.end.min(duration);
+                }
+                report.truncated.push(line.index);
+                true
+            } else {
+                report.dropped.push(line.index);
+                false
+            }
+        });
+
+        if !report.dropped.is_empty() {
+            self.renumber();
+        }
+        report
+    }
+
+    /// Moves lines starting at or after ```time``` and their words by ```offset``` miliseconds
+    /// (negative offset moves them earlier), e.g. when a scene was cut from the video.
+    /// Earlier lines are not changed, shifted timing is clamped at zero.
+    /// If shifted lines get ahead of earlier ones, lines are sorted and renumbered.
+    ///
+    /// Returns the number of shifted lines.
+    pub fn shift_after(&mut self, time: Timestamp, offset: i64) -> usize {
+        let first = self.inner.partition_point(|line| line.start < time);
+        for line in &mut self.inner[first..] {
+            shift_line(line, offset);
+        }
+
+        let unordered = match (first.checked_sub(1), self.inner.get(first)) {
+            (Some(previous), Some(line)) => line.start < self.inner[previous].start,
+            _ => false,
+        };
+        if unordered {
+            self.sort_by_time();
+        }
+        self.inner.len() - first
+    }
+
+    /// Cuts ```start..end``` interval out of the timeline, as if it was cut from the video:
+    /// lines inside of it are removed, lines crossing its bounds are trimmed
+    /// and later lines are moved earlier by the interval's length.
+    /// Lines are renumbered after that.
+    ///
+    /// Returns the number of removed lines.
+    pub fn delete_interval(&mut self, start: Timestamp, end: Timestamp) -> Result<usize, Error> {
+        if start > end {
+            return Err(Error::new(ErrorKind::InvalidInput, "Interval's start is bigger than its end"));
+        }
+        if start == end {
+            return Ok(0);
+        }
+        let length = end - start;
+        let cut = |time: Timestamp| if time <= start {
+            time
+        } else if time >= end {
+            time - length
+        } else {
+            start
+        };
+
+        let count = self.inner.len();
+        self.inner.retain(|line| !(line.start >= start && line.end <= end));
+        for line in &mut self.inner {
+            line.start = cut(line.start);
+            line.end = cut(line.end);
+            line.words.retain(|word| !(word.start >= start && word.end <= end));
+            map_words(line, cut);
+        }
+
+        let removed = count - self.inner.len();
+        if removed > 0 {
+            self.renumber();
+        }
+        Ok(removed)
+    }
+
+    /// Inserts ```length``` of time at ```at```, as if something was inserted into the video:
+    /// lines starting at or after ```at``` are moved later by ```length```.
+    /// A line crossing ```at``` is either split in two lines with the same text,
+    /// showing around the inserted part, or just lasts ```length``` longer.
+    /// The second part of a split line has no id, so ids stay unique.
+    /// Lines are renumbered if some line was split.
+    pub fn insert_gap(&mut self, at: Timestamp, length: Timestamp, split: bool) {
+        let mut split_lines = Vec::new();
+        let moved = |time: Timestamp| if time >= at { time + length } else { time };
+
+        for (position, line) in self.inner.iter_mut().enumerate() {
+            if line.start >= at {
+                line.start += length;
+                line.end += length;
+                map_words(line, moved);
+            } else if line.end > at {
+                if split {
+                    let mut second = line.clone();
+                    second.id = None;
+                    second.start = at + length;
+                    second.end += length;
+                    second.words.retain(|word| word.start >= at);
+                    map_words(&mut second, moved);
+                    line.end = at;
+                    line.words.retain(|word| word.start < at);
+                    map_words(line, |time| time.min(at));
+                    split_lines.push((position + 1, second));
+                } else {
+                    line.end += length;
+                    map_words(line, moved);
+                }
+            }
+        }
+
+        if !split_lines.is_empty() {
+            for (shift, (position, line)) in split_lines.into_iter().enumerate() {
+                self.inner.insert(position + shift, line);
+            }
+            self.renumber();
+        }
+    }
+}
+
+impl Subtitles {
+    /// Time during which at least one line is shown, overlapping lines are counted once.
+    pub fn total_speech_time(&self) -> Timestamp {
+        Timestamp::from_miliseconds(self.shown_within(u64::MAX))
+    }
+
+    /// Part of the media of given ```duration``` during which at least one line is shown,
+    /// from 0 to 1. Lines after the end of the media aren't counted.
+    pub fn coverage(&self, duration: Timestamp) -> f32 {
+        let duration = duration.total_miliseconds();
+        if duration == 0 {
+            return 0.0;
+        }
+        (self.shown_within(duration) as f64 / duration as f64) as f32
+    }
+
+    /// Compares timing of lines with the media of given ```duration```, e.g. to catch
+    /// subtitles made for another cut or episode. See ```CoverageReport``` for the checks.
+    pub fn check_against_duration(&self, duration: Timestamp) -> CoverageReport {
+        let media = duration.total_miliseconds();
+        let first_start = self.inner.iter().map(|line| line.start).min();
+        let last_end = self.inner.iter().map(|line| line.end).max();
+        CoverageReport {
+            first_start,
+            last_end,
+            coverage: self.coverage(duration),
+            late_start: first_start.is_some_and(|start| start.total_miliseconds() > media / 4),
+            early_end: last_end.is_some_and(|end| end.total_miliseconds() < media - media / 4),
+            past_end: self.inner.iter().filter(|line| line.end > duration).map(|line| line.index).collect(),
+        }
+    }
+
+    /// Number of lines shown during every ```bucket``` long interval from zero to the end
+    /// of the last line, paired with the interval's start. Intervals with no lines
+    /// are dead zones which may lack captions.
+    ///
+    /// Zero ```bucket``` or more than ```MAX_BUCKETS``` intervals are an ```InvalidInput``` error.
+    pub fn density(&self, bucket: Timestamp) -> Result<Vec<(Timestamp, usize)>, Error> {
+        Ok(self.histogram(bucket, |_, _| 1.0)?
+            .into_iter()
+            .map(|(start, count)| (start, count as usize))
+            .collect())
+    }
+
+    /// Characters of lines shown during every ```bucket``` long interval, like ```density```.
+    /// Characters of a line, without tags, are spread over the intervals proportionally to
+    /// the part of it shown in each of them.
+    ///
+    /// Zero ```bucket``` or more than ```MAX_BUCKETS``` intervals are an ```InvalidInput``` error.
+    pub fn character_density(&self, bucket: Timestamp) -> Result<Vec<(Timestamp, usize)>, Error> {
+        Ok(self.histogram(bucket, |line, shown| {
+                let characters = utils::strip_tags(&line.text).chars().filter(|c| !c.is_whitespace()).count() as f64;
+                let duration = line.end.total_miliseconds().saturating_sub(line.start.total_miliseconds()) as f64;
+                if duration > 0.0 { characters * shown as f64 / duration } else { characters }
+            })?
+            .into_iter()
+            .map(|(start, characters)| (start, characters.round() as usize))
+            .collect())
+    }
+
+    /// Sums ```weight``` of lines shown during every bucket, ```weight``` gets the line
+    /// and how long it's shown in the bucket.
+    fn histogram<F: Fn(&SubLine, u64) -> f64>(&self, bucket: Timestamp, weight: F) -> Result<Vec<(Timestamp, f64)>, Error> {
+        let bucket = bucket.total_miliseconds();
+        if bucket == 0 {
+            return Err(Error::new(ErrorKind::InvalidInput, "Bucket must be positive"));
+        }
+        let end = self.inner.iter().map(|line| line.end.total_miliseconds()).max().unwrap_or(0);
+        if end.div_ceil(bucket) > MAX_BUCKETS {
+            return Err(Error::new(ErrorKind::InvalidInput,
+                                  format!("Bucket is too small, there would be more than {} of them", MAX_BUCKETS)));
+        }
+        let mut result: Vec<(Timestamp, f64)> = (0..end.div_ceil(bucket))
+            .map(|i| (Timestamp::from_miliseconds(i * bucket), 0.0))
+            .collect();
+
+        for line in &self.inner {
+            let (start, end) = (line.start.total_miliseconds(), line.end.total_miliseconds());
+            if start >= end {
+                // Lines lasting no time count in the bucket they are shown at
+                if let Some(entry) = result.get_mut((start / bucket) as usize) {
+                    entry.1 += weight(line, 0);
+                }
+                continue;
+            }
+            for i in start / bucket..end.div_ceil(bucket) {
+                let shown = end.min((i + 1) * bucket) - start.max(i * bucket);
+                result[i as usize].1 += weight(line, shown);
+            }
+        }
+        Ok(result)
+    }
+
+    /// Length of the union of lines' intervals before ```limit```.
+    fn shown_within(&self, limit: u64) -> u64 {
+        let mut intervals: Vec<(u64, u64)> = self.inner
+            .iter()
+            .map(|line| (line.start.total_miliseconds().min(limit), line.end.total_miliseconds().min(limit)))
+            .filter(|&(start, end)| start < end)
+            .collect();
+        intervals.sort_unstable();
+
+        let mut total = 0;
+        let mut covered = 0;
+        for (start, end) in intervals {
+            let start = start.max(covered);
+            if end > start {
+                total += end - start;
+                covered = end;
+            }
+        }
+        total
+    }
+}
+
+impl Subtitles {
+    /// Snaps lines' starts and ends to the nearest point of a musical grid, e.g. for lyrics:
+    /// ```subdivision``` points per beat of ```bpm``` tempo, starting at ```offset```.
+    /// Lines which would become empty end a grid step after their start.
+    pub fn quantize_to_grid(&mut self, bpm: f64, offset: Timestamp, subdivision: u32) -> Result<(), Error> {
+        if !bpm.is_finite() || bpm <= 0.0 || subdivision == 0 {
+            return Err(Error::new(ErrorKind::InvalidInput, "Tempo and subdivision must be positive"));
+        }
+        let step = 60_000.0 / (bpm * subdivision as f64);
+        let offset = offset.total_miliseconds() as f64;
+        let snap = |time: Timestamp| {
+            let steps = ((time.total_miliseconds() as f64 - offset) / step).round();
+            Timestamp::from_miliseconds((offset + steps * step).round().max(0.0) as u64)
+        };
+
+        for line in &mut self.inner {
+            line.start = snap(line.start);
+            line.end = snap(line.end);
+            map_words(line, snap);
+            if line.end <= line.start {
+                line.end = Timestamp::from_miliseconds((line.start.total_miliseconds() as f64 + step).round() as u64);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Moves start, end and words of ```line``` by ```offset``` miliseconds, clamping at zero.
+pub(crate) fn shift_line(line: &mut SubLine, offset: i64) {
+    line.start = shift(line.start, offset);
+    line.end = shift(line.end, offset);
+    map_words(line, |time| shift(time, offset));
+}
+
+/// Changes start and end of every word of ```line``` with ```f```.
+fn map_words<F: Fn(Timestamp) -> Timestamp>(line: &mut SubLine, f: F) {
+    for word in &mut line.words {
+        word.start = f(word.start);
+        word.end = f(word.end);
+    }
+}
+
+/// Moves ```time``` by ```offset``` miliseconds, clamping at zero.
+pub fn shift(time: Timestamp, offset: i64) -> Timestamp {
+    let shifted = (time.total_miliseconds() as i64).saturating_add(offset);
+    Timestamp::from_miliseconds(if shifted < 0 { 0 } else { shifted as u64 })
+}
+
+#[cfg(test)]
+mod timeline_tests {
+    use super::*;
+    use crate::subline::Word;
+    use crate::adapters::SubLineIterator;
+    use crate::utils;
+
+    fn timing(subs: &Subtitles) -> Vec<(u32, u64, u64)> {
+        subs.inner
+            .iter()
+            .map(|line| (line.index, line.start.total_miliseconds(), line.end.total_miliseconds()))
+            .collect()
+    }
+
+    #[test]
+    fn density() {
+        let line = |index, text: &str, start, end| {
+            SubLine::new(index, text.to_owned(), Timestamp::from_miliseconds(start), Timestamp::from_miliseconds(end))
+        };
+        // The first two lines overlap
+        let subs = Subtitles::from(vec![line(1, "<i>Ab</i>", 500, 1500), line(2, "Cdef", 1000, 2000), line(3, "G", 4000, 4200)]);
+        let second = Timestamp::new(0, 0, 1, 0);
+        let counts: Vec<usize> = subs.density(second).unwrap().into_iter().map(|(_, count)| count).collect();
+        assert_eq!(counts, vec![1, 2, 0, 0, 1]);
+        assert_eq!(subs.density(second).unwrap()[2].0, Timestamp::new(0, 0, 2, 0));
+        let characters: Vec<usize> = subs.character_density(second).unwrap().into_iter().map(|(_, count)| count).collect();
+        assert_eq!(characters, vec![1, 5, 0, 0, 1]);
+        assert!(Subtitles::default().density(second).unwrap().is_empty());
+
+        assert!(subs.density(Timestamp::default()).is_err());
+        let long = Subtitles::from(vec![line(1, "A", 0, Timestamp::new(99, 59, 59, 0).total_miliseconds())]);
+        assert_eq!(long.density(Timestamp::from_miliseconds(1)).unwrap_err().kind(), ErrorKind::InvalidInput);
+        assert_eq!(long.density(Timestamp::new(0, 1, 0, 0)).unwrap().len(), 6000);
+
+        // Reversed lines count in the bucket of their start
+        let mut reversed = Subtitles::from(vec![line(1, "Ab", 500, 1500), line(2, "Cd", 0, 1000)]);
+        reversed.inner[1].start = Timestamp::from_miliseconds(1200);
+        let characters: Vec<usize> = reversed.character_density(second).unwrap().into_iter().map(|(_, count)| count).collect();
+        assert_eq!(characters, vec![1, 3]);
+    }
+
+    fn with_words(start: u64, end: u64, words: &[(u64, u64)]) -> Subtitles {
+        let mut line = SubLine::new(1, "Word".to_owned(), Timestamp::from_miliseconds(start), Timestamp::from_miliseconds(end));
+        line.words = words.iter()
+            .map(|&(start, end)| {
+                Word {
+                    start: Timestamp::from_miliseconds(start),
+                    end: Timestamp::from_miliseconds(end),
+                    text: "Word".to_owned(),
+                }
+            })
+            .collect();
+        Subtitles::from(vec![line])
+    }
+
+    fn word_timing(subs: &Subtitles) -> Vec<(u64, u64)> {
+        subs.inner
+            .iter()
+            .flat_map(|line| &line.words)
+            .map(|word| (word.start.total_miliseconds(), word.end.total_miliseconds()))
+            .collect()
+    }
+
+    #[test]
+    fn shift_words() {
+        let mut subs = with_words(1000, 3000, &[(1000, 2000), (2000, 3000)]);
+        subs.shift(-1500);
+        assert_eq!(word_timing(&subs), vec![(0, 500), (500, 1500)]);
+        subs.shift_after(Timestamp::default(), 1000);
+        assert_eq!(word_timing(&subs), vec![(1000, 1500), (1500, 2500)]);
+        assert_eq!(super::shift(Timestamp::new(0, 0, 1, 0), i64::MIN), Timestamp::default());
+        if !cfg!(feature = "strict-math") {
+            // Saturates instead of overflowing, the timestamp itself wraps
+            super::shift(Timestamp::new(0, 0, 1, 0), i64::MAX);
+        }
+        assert_eq!(subs.clone().into_iter().shifted(-1000).flat_map(|line| line.words).count(), 2);
+    }
+
+    #[test]
+    fn edit_words() {
+        let words = [(1000, 2000), (2000, 3000), (3000, 4000)];
+
+        let mut subs = with_words(1000, 4000, &words);
+        subs.delete_interval(Timestamp::new(0, 0, 1, 500), Timestamp::new(0, 0, 3, 0)).unwrap();
+        assert_eq!(word_timing(&subs), vec![(1000, 1500), (1500, 2500)]);
+
+        let mut subs = with_words(1000, 4000, &words);
+        subs.insert_gap(Timestamp::new(0, 0, 2, 0), Timestamp::new(0, 0, 10, 0), true);
+        assert_eq!(subs.inner[0].words.len(), 1);
+        assert_eq!(word_timing(&subs), vec![(1000, 2000), (12000, 13000), (13000, 14000)]);
+
+        let mut subs = with_words(1000, 4000, &words);
+        subs.clamp_to(Timestamp::new(0, 0, 2, 500), ClampPolicy::Truncate);
+        assert_eq!(word_timing(&subs), vec![(1000, 2000), (2000, 2500)]);
+
+        let mut subs = with_words(1000, 4000, &words);
+        subs.quantize_to_grid(60.0, Timestamp::default(), 1).unwrap();
+        assert_eq!(word_timing(&subs), vec![(1000, 2000), (2000, 3000), (3000, 4000)]);
+        subs.quantize_to_grid(30.0, Timestamp::default(), 1).unwrap();
+        assert_eq!(word_timing(&subs), vec![(2000, 2000), (2000, 4000), (4000, 4000)]);
+    }
+
+    #[test]
+    fn shift() {
+        let mut subs = subtitles![(0, 1000, "Dropped"), (1500, 2500, "Clamped"), (3000, 4000, "Moved")];
+        let report = subs.shift(-2000);
+        assert_eq!(report,
+                   ShiftReport {
+                       dropped: vec![1],
+                       clamped: vec![2],
+                       applied_offset: -2000,
+                   });
+        assert_eq!(timing(&subs), vec![(1, 0, 500), (2, 1000, 2000)]);
+
+        let report = subs.shift(500);
+        assert!(report.dropped.is_empty() && report.clamped.is_empty());
+        assert_eq!(timing(&subs), vec![(1, 500, 1000), (2, 1500, 2500)]);
+    }
+
+    #[test]
+    fn quantize_to_grid() {
+        // 120 bpm in eighths is a 250 ms grid, shifted by 100 ms
+        let mut subs = subtitles![(0, 1120, "One"), (1240, 1300, "Two"), (2000, 2100, "Three")];
+        subs.quantize_to_grid(120.0, Timestamp::new(0, 0, 0, 100), 2).unwrap();
+        assert_eq!(timing(&subs), vec![(1, 100, 1100), (2, 1350, 1600), (3, 2100, 2350)]);
+
+        assert!(subs.quantize_to_grid(0.0, Timestamp::default(), 2).is_err());
+        assert!(subs.quantize_to_grid(120.0, Timestamp::default(), 0).is_err());
+    }
+
+    #[test]
+    fn speech_time() {
+        let mut subs = utils::subs_from_texts(&["One", "Two", "Three", "Four"]);
+        // Two overlaps One and contains Three
+        subs.inner[1].start = Timestamp::from_miliseconds(500);
+        subs.inner[1].end = Timestamp::from_miliseconds(5000);
+        assert_eq!(subs.total_speech_time(), Timestamp::from_miliseconds(6000));
+        assert_eq!(subs.coverage(Timestamp::new(0, 0, 10, 0)), 0.6);
+        assert_eq!(subs.coverage(Timestamp::new(0, 0, 2, 0)), 1.0);
+        assert_eq!(subs.coverage(Timestamp::default()), 0.0);
+        assert_eq!(Subtitles::default().total_speech_time(), Timestamp::default());
+    }
+
+    #[test]
+    fn clamp_to() {
+        let subs = utils::subs_from_texts(&["One", "Two", "Three", "Four"]);
+        let duration = Timestamp::new(0, 0, 4, 500);
+
+        let mut truncated = subs.clone();
+        let report = truncated.clamp_to(duration, ClampPolicy::Truncate);
+        assert_eq!(report.truncated, vec![3]);
+        assert_eq!(report.dropped, vec![4]);
+        assert_eq!(timing(&truncated), vec![(1, 0, 1000), (2, 2000, 3000), (3, 4000, 4500)]);
+
+        let mut dropped = subs.clone();
+        let report = dropped.clamp_to(duration, ClampPolicy::Drop);
+        assert_eq!(report.dropped, vec![3, 4]);
+        assert_eq!(dropped.len(), 2);
+
+        assert!(dropped.clamp_to(duration, ClampPolicy::Drop).is_empty());
+    }
+
+    #[test]
+    fn check_against_duration() {
+        let subs = utils::subs_from_texts(&["One", "Two", "Three", "Four"]);
+
+        let report = subs.check_against_duration(Timestamp::new(0, 0, 8, 0));
+        assert_eq!(report.first_start, Some(Timestamp::default()));
+        assert_eq!(report.last_end, Some(Timestamp::new(0, 0, 7, 0)));
+        assert_eq!(report.coverage, 0.5);
+        assert!(!report.is_suspicious());
+
+        let longer = subs.check_against_duration(Timestamp::new(0, 0, 30, 0));
+        assert!(longer.early_end && !longer.late_start && longer.is_suspicious());
+
+        let shorter = subs.check_against_duration(Timestamp::new(0, 0, 5, 500));
+        assert_eq!(shorter.past_end, vec![4]);
+        assert!(shorter.is_suspicious());
+
+        let mut late = subs.clone();
+        late.shift(60_000);
+        let report = late.check_against_duration(Timestamp::new(0, 1, 8, 0));
+        assert!(report.late_start && !report.early_end);
+
+        assert!(Subtitles::default().check_against_duration(Timestamp::new(0, 0, 8, 0)).is_suspicious());
+    }
+
+    #[test]
+    fn shift_after() {
+        let mut subs = utils::subs_from_texts(&["One", "Two", "Three"]);
+        assert_eq!(subs.shift_after(Timestamp::new(0, 0, 2, 0), 1500), 2);
+        assert_eq!(timing(&subs), vec![(1, 0, 1000), (2, 3500, 4500), (3, 5500, 6500)]);
+
+        assert_eq!(subs.shift_after(Timestamp::new(0, 0, 4, 0), -5000), 1);
+        assert_eq!(timing(&subs), vec![(1, 0, 1000), (2, 500, 1500), (3, 3500, 4500)]);
+        assert_eq!(subs.inner[1].text, "Three");
+    }
+
+    #[test]
+    fn delete_interval() {
+        let mut subs = utils::subs_from_texts(&["One", "Two", "Three", "Four"]);
+        assert_eq!(subs.delete_interval(Timestamp::new(0, 0, 0, 500), Timestamp::new(0, 0, 4, 500)).unwrap(), 1);
+        assert_eq!(timing(&subs), vec![(1, 0, 500), (2, 500, 1000), (3, 2000, 3000)]);
+        assert_eq!(subs.inner[1].text, "Three");
+
+        assert!(subs.delete_interval(Timestamp::new(0, 0, 1, 0), Timestamp::new(0, 0, 0, 0)).is_err());
+    }
+
+    #[test]
+    fn insert_gap() {
+        let mut subs = utils::subs_from_texts(&["One", "Two", "Three"]);
+        subs.inner[1].id = Some("two".to_owned());
+        let at = Timestamp::new(0, 0, 2, 500);
+        let length = Timestamp::new(0, 0, 10, 0);
+
+        let mut stretched = subs.clone();
+        stretched.insert_gap(at, length, false);
+        assert_eq!(timing(&stretched), vec![(1, 0, 1000), (2, 2000, 13000), (3, 14000, 15000)]);
+
+        let mut split = subs.clone();
+        split.insert_gap(at, length, true);
+        assert_eq!(timing(&split),
+                   vec![(1, 0, 1000), (2, 2000, 2500), (3, 12500, 13000), (4, 14000, 15000)]);
+        assert_eq!(split.inner[2].text, "Two");
+        assert_eq!(split.inner[1].id.as_deref(), Some("two"));
+        assert_eq!(split.inner[2].id, None);
+    }
+}
@@ -1,9 +1,35 @@
-use std::ops::{Add, Sub, AddAssign, SubAssign};
+use std::ops::{Add, Sub, Mul, Div, AddAssign, SubAssign};
+use std::iter::Sum;
 use std::convert::From;
+use std::io::{Error, ErrorKind};
 
+use crate::metadata::FrameRate;
 
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+
+/// How fractional miliseconds are rounded, see ```Timestamp::mul_f64```.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Rounding {
+    /// Halves are rounded away from zero.
+    #[default]
+    Nearest,
+    Down,
+    Up,
+}
+
+impl Rounding {
+    fn apply(self, value: f64) -> f64 {
+        match self {
+            Rounding::Nearest => value.round(),
+            Rounding::Down => value.floor(),
+            Rounding::Up => value.ceil(),
+        }
+    }
+}
+
+/// Time of a subtitle, ```Timestamp::default()``` is zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Timestamp {
     pub hours: u32,
     pub minutes: u32,
@@ -29,6 +55,10 @@ impl Timestamp {
     /// assert_eq!(t3, t4);
     /// ```
     pub fn new(mut hours: u32, mut minutes: u32, mut seconds: u32, mut miliseconds: u32) -> Timestamp {
+        if cfg!(feature = "strict-math") {
+            let total = hours as u64 * 3_600_000 + minutes as u64 * 60_000 + seconds as u64 * 1000 + miliseconds as u64;
+            return Timestamp::checked_from_miliseconds(total).expect("attempt to construct timestamp with overflow");
+        }
 
         if miliseconds >= 1000 {
             let to_seconds = miliseconds / 1000;
@@ -49,10 +79,10 @@ impl Timestamp {
         }
 
         Timestamp {
-            hours: hours,
-            minutes: minutes,
-            seconds: seconds,
-            miliseconds: miliseconds,
+            hours,
+            minutes,
+            seconds,
+            miliseconds,
         }
     }
     /// Constructs new Timestamp from given overall microseconds.
@@ -71,15 +101,220 @@ impl Timestamp {
         Timestamp::new(0, 0, 0, miliseconds as u32)
     }
 
+    /// Constructs new Timestamp from given overall miliseconds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use srt::Timestamp;
+    ///
+    /// let t1 = Timestamp::from_miliseconds(3661001);
+    /// let t2 = Timestamp::new(1, 1, 1, 1);
+    /// assert_eq!(t1, t2);
+    /// ```
+    pub fn from_miliseconds(miliseconds: u64) -> Timestamp {
+        if cfg!(feature = "strict-math") {
+            return Timestamp::checked_from_miliseconds(miliseconds)
+                .expect("attempt to construct timestamp with overflow");
+        }
+        let seconds = miliseconds / 1000;
+        Timestamp::new(0, 0, seconds as u32, (miliseconds % 1000) as u32)
+    }
+
+    /// Constructs new Timestamp from given overall miliseconds,
+    /// ```None``` if the hours don't fit in ```u32```.
+    pub fn checked_from_miliseconds(miliseconds: u64) -> Option<Timestamp> {
+        let hours = miliseconds / 3_600_000;
+        if hours > u32::MAX as u64 {
+            return None;
+        }
+        Some(Timestamp {
+            hours: hours as u32,
+            minutes: (miliseconds / 60_000 % 60) as u32,
+            seconds: (miliseconds / 1000 % 60) as u32,
+            miliseconds: (miliseconds % 1000) as u32,
+        })
+    }
+
+    /// Adds times, ```None``` on overflow instead of wrapping.
+    pub fn checked_add(&self, other: Timestamp) -> Option<Timestamp> {
+        Timestamp::checked_from_miliseconds(self.total_miliseconds() + other.total_miliseconds())
+    }
+
+    /// Subtracts times, ```None``` if ```other``` is bigger instead of panicking.
+    pub fn checked_sub(&self, other: Timestamp) -> Option<Timestamp> {
+        self.total_miliseconds()
+            .checked_sub(other.total_miliseconds())
+            .and_then(Timestamp::checked_from_miliseconds)
+    }
+
+    /// Returns overall miliseconds.
     pub fn total_miliseconds(&self) -> u64 {
         let mut result: u64 = 0;
 
         result += self.miliseconds as u64;
         result += (self.seconds as u64) * 1_000;
         result += (self.minutes as u64) * 60_000;
-        result += (self.hours as u64) * 360_000;
+        result += (self.hours as u64) * 3_600_000;
         result
     }
+
+    /// Rounds to the nearest multiple of ```step``` miliseconds, halves are rounded up.
+    /// Zero ```step``` keeps the time as is.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use srt::Timestamp;
+    ///
+    /// assert_eq!(Timestamp::from_miliseconds(1049).round_to_millis(10), Timestamp::from_miliseconds(1050));
+    /// ```
+    pub fn round_to_millis(&self, step: u64) -> Timestamp {
+        if step == 0 {
+            return *self;
+        }
+        let miliseconds = self.total_miliseconds();
+        Timestamp::from_miliseconds((miliseconds + step / 2) / step * step)
+    }
+
+    /// Multiplies the time by ```factor```, rounding the result to miliseconds.
+    ///
+    /// # Panics
+    ///
+    /// Panics if ```factor``` is negative or not finite.
+    pub fn mul_f64(&self, factor: f64, rounding: Rounding) -> Timestamp {
+        if !factor.is_finite() || factor < 0.0 {
+            panic!("timestamp can only be multiplied by a finite non-negative factor");
+        }
+        Timestamp::from_miliseconds(rounding.apply(self.total_miliseconds() as f64 * factor) as u64)
+    }
+
+    /// Divides the time by ```divisor```, rounding the result to miliseconds.
+    ///
+    /// # Panics
+    ///
+    /// Panics if ```divisor``` is not positive or not finite.
+    pub fn div_f64(&self, divisor: f64, rounding: Rounding) -> Timestamp {
+        if !divisor.is_finite() || divisor <= 0.0 {
+            panic!("timestamp can only be divided by a finite positive divisor");
+        }
+        Timestamp::from_miliseconds(rounding.apply(self.total_miliseconds() as f64 / divisor) as u64)
+    }
+
+    /// Time halfway between ```a``` and ```b```, rounded down to a milisecond.
+    pub fn midpoint(a: Timestamp, b: Timestamp) -> Timestamp {
+        let (a, b) = (a.total_miliseconds(), b.total_miliseconds());
+        Timestamp::from_miliseconds(a.min(b) + a.abs_diff(b) / 2)
+    }
+
+    /// Linear interpolation: ```a``` for ```t == 0```, ```b``` for ```t == 1```,
+    /// rounded to the nearest milisecond. ```t``` outside of ```0..1``` extrapolates,
+    /// clamping at zero.
+    ///
+    /// # Panics
+    ///
+    /// Panics if ```t``` is not finite.
+    pub fn lerp(a: Timestamp, b: Timestamp, t: f64) -> Timestamp {
+        if !t.is_finite() {
+            panic!("interpolation parameter must be finite");
+        }
+        let (a, b) = (a.total_miliseconds() as f64, b.total_miliseconds() as f64);
+        Timestamp::from_miliseconds((a + (b - a) * t).round().max(0.0) as u64)
+    }
+
+    /// Moves the time back to the start of its frame, rounded up to a whole milisecond
+    /// so that it stays in the same frame.
+    pub fn floor_to_frame(&self, fps: FrameRate) -> Timestamp {
+        frame_start(frame(*self, fps), fps)
+    }
+
+    /// Moves the time forward to the start of the next frame, rounded up to a whole milisecond.
+    /// Times already at the start of a frame are kept.
+    pub fn ceil_to_frame(&self, fps: FrameRate) -> Timestamp {
+        let frame = frame(*self, fps);
+        let start = frame_start(frame, fps);
+        if start == *self {
+            start
+        } else {
+            frame_start(frame + 1, fps)
+        }
+    }
+
+    /// Parses a human-friendly time, e.g. from command line arguments: units
+    /// (```"1h2m3s"```, ```"1.5s"```, ```"250ms"```), colons (```"1:02:03"```,
+    /// ```"02:03.5"```, ```"00:00:01,500"```) or plain seconds (```"90"```).
+    /// Fractions are rounded to miliseconds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use srt::Timestamp;
+    ///
+    /// assert_eq!(Timestamp::parse_flexible("1h2m3s").unwrap(), Timestamp::new(1, 2, 3, 0));
+    /// assert_eq!(Timestamp::parse_flexible("02:03.5").unwrap(), Timestamp::new(0, 2, 3, 500));
+    /// ```
+    pub fn parse_flexible(s: &str) -> Result<Timestamp, Error> {
+        let invalid = || Error::new(ErrorKind::InvalidInput, format!("Invalid time: {:?}", s));
+        let trimmed = s.trim();
+        let miliseconds = if trimmed.is_empty() {
+            return Err(invalid());
+        } else if trimmed.contains(':') {
+            let parts: Vec<&str> = trimmed.split(':').collect();
+            if parts.len() > 3 || parts[..parts.len() - 1].iter().any(|part| part.contains(['.', ','])) {
+                return Err(invalid());
+            }
+            let mut total = 0.0;
+            for part in parts {
+                total = total * 60.0 + number(part).ok_or_else(invalid)?;
+            }
+            total * 1000.0
+        } else if trimmed.ends_with(|c: char| c.is_ascii_digit()) {
+            number(trimmed).ok_or_else(invalid)? * 1000.0
+        } else {
+            let mut total = 0.0;
+            let mut rest = trimmed;
+            while !rest.is_empty() {
+                let digits = rest.find(|c: char| c.is_alphabetic()).unwrap_or(rest.len());
+                let unit_end = rest[digits..].find(|c: char| !c.is_alphabetic()).map_or(rest.len(), |end| digits + end);
+                let value = number(&rest[..digits]).ok_or_else(invalid)?;
+                total += value *
+                         match &rest[digits..unit_end] {
+                    "h" => 3_600_000.0,
+                    "m" => 60_000.0,
+                    "s" => 1000.0,
+                    "ms" => 1.0,
+                    _ => return Err(invalid()),
+                };
+                rest = &rest[unit_end..];
+            }
+            total
+        };
+        let miliseconds = miliseconds.round();
+        if !(0.0..=u32::MAX as f64 * 1000.0).contains(&miliseconds) {
+            return Err(invalid());
+        }
+        Ok(Timestamp::from_miliseconds(miliseconds as u64))
+    }
+}
+
+/// Unsigned decimal number with ```.``` or ```,``` before the fraction.
+fn number(s: &str) -> Option<f64> {
+    let s = s.trim();
+    if s.is_empty() || !s.chars().all(|c| c.is_ascii_digit() || c == '.' || c == ',') ||
+       s.matches(['.', ',']).count() > 1 {
+        return None;
+    }
+    s.replace(',', ".").parse().ok()
+}
+
+/// Number of the frame which shows at ```time```.
+fn frame(time: Timestamp, fps: FrameRate) -> u64 {
+    time.total_miliseconds() * fps.numerator as u64 / (fps.denominator as u64 * 1000)
+}
+
+fn frame_start(frame: u64, fps: FrameRate) -> Timestamp {
+    let numerator = fps.numerator.max(1) as u64;
+    Timestamp::from_miliseconds((frame * fps.denominator as u64 * 1000).div_ceil(numerator))
 }
 
 impl<'a> From<&'a [u32; 4]> for Timestamp {
@@ -102,7 +337,13 @@ impl<'a> From<&'a [u32; 4]> for Timestamp {
 impl Add for Timestamp {
     type Output = Timestamp;
 
+    /// # Panics
+    ///
+    /// With the ```strict-math``` feature panics on overflow, see ```Timestamp::checked_add```.
     fn add(self, other: Timestamp) -> Timestamp {
+        if cfg!(feature = "strict-math") {
+            return self.checked_add(other).expect("attempt to add with overflow");
+        }
         let mut miliseconds = (self.miliseconds + other.miliseconds) as u64;
         let mut seconds = (self.seconds + other.seconds) as u64;
         let mut minutes = (self.minutes + other.minutes) as u64;
@@ -135,8 +376,41 @@ impl Add for Timestamp {
     }
 }
 
+impl Mul<f64> for Timestamp {
+    type Output = Timestamp;
+
+    /// Multiplies the time rounding to the nearest milisecond, see ```Timestamp::mul_f64```.
+    ///
+    /// # Panics
+    ///
+    /// Panics if ```factor``` is negative or not finite.
+    fn mul(self, factor: f64) -> Timestamp {
+        self.mul_f64(factor, Rounding::Nearest)
+    }
+}
+
+impl Div<f64> for Timestamp {
+    type Output = Timestamp;
+
+    /// Divides the time rounding to the nearest milisecond, see ```Timestamp::div_f64```.
+    ///
+    /// # Panics
+    ///
+    /// Panics if ```divisor``` is not positive or not finite.
+    fn div(self, divisor: f64) -> Timestamp {
+        self.div_f64(divisor, Rounding::Nearest)
+    }
+}
+
 impl AddAssign for Timestamp {
+    /// # Panics
+    ///
+    /// With the ```strict-math``` feature panics on overflow, see ```Timestamp::checked_add```.
     fn add_assign(&mut self, timestamp: Timestamp) {
+        if cfg!(feature = "strict-math") {
+            *self = self.checked_add(timestamp).expect("attempt to add with overflow");
+            return;
+        }
         self.hours += timestamp.hours;
         self.minutes += timestamp.minutes;
         self.seconds += timestamp.seconds;
@@ -162,6 +436,32 @@ impl AddAssign for Timestamp {
     }
 }
 
+impl<'a> AddAssign<&'a Timestamp> for Timestamp {
+    fn add_assign(&mut self, timestamp: &'a Timestamp) {
+        *self += *timestamp;
+    }
+}
+
+impl Sum for Timestamp {
+    /// Sums times, e.g. durations of lines:
+    ///
+    /// ```
+    /// use srt::Timestamp;
+    ///
+    /// let durations = vec![Timestamp::new(0, 0, 1, 500), Timestamp::new(0, 0, 2, 600)];
+    /// assert_eq!(durations.into_iter().sum::<Timestamp>(), Timestamp::new(0, 0, 4, 100));
+    /// ```
+    fn sum<I: Iterator<Item = Timestamp>>(iter: I) -> Timestamp {
+        iter.fold(Timestamp::default(), |sum, time| sum + time)
+    }
+}
+
+impl<'a> Sum<&'a Timestamp> for Timestamp {
+    fn sum<I: Iterator<Item = &'a Timestamp>>(iter: I) -> Timestamp {
+        iter.cloned().sum()
+    }
+}
+
 impl Sub for Timestamp {
     type Output = Timestamp;
 
@@ -214,10 +514,10 @@ impl Sub for Timestamp {
         miliseconds -= other.miliseconds;
 
         Timestamp {
-            hours: hours,
-            minutes: minutes,
-            seconds: seconds,
-            miliseconds: miliseconds,
+            hours,
+            minutes,
+            seconds,
+            miliseconds,
         }
     }
 }
@@ -275,44 +575,113 @@ impl SubAssign for Timestamp {
 #[cfg(test)]
 mod timestamp_test {
     use super::*;
+    use crate::utils;
+
+    #[test]
+    fn rounding() {
+        let time = Timestamp::from_miliseconds;
+        assert_eq!(time(1049).round_to_millis(10), time(1050));
+        assert_eq!(time(1044).round_to_millis(10), time(1040));
+        assert_eq!(time(1499).round_to_millis(1000), time(1000));
+        assert_eq!(time(1500).round_to_millis(1000), time(2000));
+        assert_eq!(time(1499).round_to_millis(0), time(1499));
+
+        let pal = FrameRate::new(25, 1);
+        assert_eq!(time(1039).floor_to_frame(pal), time(1000));
+        assert_eq!(time(1039).ceil_to_frame(pal), time(1040));
+        assert_eq!(time(1040).ceil_to_frame(pal), time(1040));
+
+        // frames of 23.976 fps start at 0, 41.71, 83.42, 125.13 ms
+        let film = FrameRate::new(24000, 1001);
+        assert_eq!(time(125).floor_to_frame(film), time(84));
+        assert_eq!(time(126).floor_to_frame(film), time(126));
+        assert_eq!(time(85).ceil_to_frame(film), time(126));
+        assert_eq!(time(84).ceil_to_frame(film), time(84));
+    }
+
+    #[test]
+    fn interpolation() {
+        let time = Timestamp::from_miliseconds;
+        assert_eq!(Timestamp::midpoint(time(1000), time(2001)), time(1500));
+        assert_eq!(Timestamp::midpoint(time(2001), time(1000)), time(1500));
+        assert_eq!(Timestamp::lerp(time(1000), time(2000), 0.25), time(1250));
+        assert_eq!(Timestamp::lerp(time(2000), time(1000), 0.25), time(1750));
+        assert_eq!(Timestamp::lerp(time(1000), time(2000), 1.5), time(2500));
+        assert_eq!(Timestamp::lerp(time(1000), time(2000), -2.0), time(0));
+    }
+
+    #[test]
+    fn sum() {
+        let subs = utils::subs_from_texts(&["One", "Two", "Three"]);
+        assert_eq!(subs.inner.iter().map(|line| line.duration()).sum::<Timestamp>(),
+                   Timestamp::new(0, 0, 3, 0));
+        let times = [Timestamp::new(0, 0, 59, 600), Timestamp::new(0, 0, 0, 500)];
+        assert_eq!(times.iter().sum::<Timestamp>(), Timestamp::new(0, 1, 0, 100));
+        assert_eq!(Vec::<Timestamp>::new().into_iter().sum::<Timestamp>(), Timestamp::default());
+        assert_eq!(Timestamp::default(), Timestamp::new(0, 0, 0, 0));
+
+        let mut total = Timestamp::default();
+        for time in &times {
+            total += time;
+        }
+        assert_eq!(total, Timestamp::new(0, 1, 0, 100));
+    }
+
+    #[test]
+    fn mul_div() {
+        let time = Timestamp::from_miliseconds;
+        assert_eq!(time(1000) * 1.5, time(1500));
+        assert_eq!(time(1001) * 0.5, time(501));
+        assert_eq!(time(1001).mul_f64(0.5, Rounding::Down), time(500));
+        assert_eq!(time(1000) / 3.0, time(333));
+        assert_eq!(time(1000).div_f64(3.0, Rounding::Up), time(334));
+        // 25 fps video played at 23.976 fps
+        assert_eq!(time(3_600_000).mul_f64(25.0 / (24000.0 / 1001.0), Rounding::Nearest), time(3_753_750));
+    }
+
+    #[test]
+    #[should_panic]
+    fn mul_negative() {
+        let _ = Timestamp::from_miliseconds(1000) * -1.0;
+    }
 
     #[test]
     fn ord() {
         let mut t1 = Timestamp::new(1, 1, 1, 1);
-        let t2 = t1.clone();
+        let t2 = t1;
 
         { // t1 == t2 
             assert!(t1 == t2);
-            assert!(!(t1 != t2));
+            assert!(!t1.ne(&t2));
 
             assert!(t1 >= t2);
             assert!(t1 <= t2);
 
-            assert!(!(t1 > t2));
-            assert!(!(t1 < t2));
+            assert!(!t1.gt(&t2));
+            assert!(!t1.lt(&t2));
         }
 
         t1.miliseconds += 1;
         { // t1 > t2
-            assert!(!(t1 == t2));
+            assert!(!t1.eq(&t2));
             assert!(t1 != t2);
 
             assert!(t1 >= t2);
-            assert!(!(t1 <= t2));
+            assert!(!t1.le(&t2));
 
             assert!(t1 > t2);
-            assert!(!(t1 < t2));
+            assert!(!t1.lt(&t2));
         }
 
         t1.miliseconds -= 2;
         { // t1 < t2
-            assert!(!(t1 == t2));
+            assert!(!t1.eq(&t2));
             assert!(t1 != t2);
 
-            assert!(!(t1 >= t2));
+            assert!(!t1.ge(&t2));
             assert!(t1 <= t2);
 
-            assert!(!(t1 > t2));
+            assert!(!t1.gt(&t2));
             assert!(t1 < t2);
         }
     }
@@ -321,7 +690,7 @@ mod timestamp_test {
     fn add() {
         {
             let t1 = Timestamp::new(1, 1, 1, 1);
-            let t2 = t1.clone();
+            let t2 = t1;
 
             let t3 = Timestamp::new(2, 2, 2, 2);
             assert_eq!(t1 + t2, t3);
@@ -329,7 +698,7 @@ mod timestamp_test {
 
         {
             let t1 = Timestamp::new(1, 58, 58, 900);
-            let t2 = t1.clone();
+            let t2 = t1;
 
             let t3 = Timestamp::new(3, 57, 57, 800);
             assert_eq!(t1 + t2, t3);
@@ -395,7 +764,7 @@ mod timestamp_test {
             let t1 = Timestamp::new(2, 2, 2, 2);
             let t2 = Timestamp::new(1, 1, 1, 1);
 
-            let t3 = t2.clone();
+            let t3 = t2;
             assert_eq!(t1 - t2, t3);
         }
         {
@@ -434,6 +803,22 @@ mod timestamp_test {
         }
     }
 
+    #[test]
+    fn total_miliseconds() {
+        let t1 = Timestamp::new(1, 1, 1, 1);
+        assert_eq!(t1.total_miliseconds(), 3661001);
+        assert_eq!(Timestamp::from_miliseconds(t1.total_miliseconds()), t1);
+    }
+
+    #[test]
+    fn total_miliseconds_hours() {
+        // An hour used to count as 360 000 miliseconds
+        assert_eq!(Timestamp::new(1, 0, 0, 0).total_miliseconds(), 3_600_000);
+        assert_eq!(Timestamp::new(2, 30, 0, 0).total_miliseconds(), 9_000_000);
+        assert!(Timestamp::new(1, 0, 0, 0).total_miliseconds() > Timestamp::new(0, 59, 59, 999).total_miliseconds());
+        assert_eq!(Timestamp::new(1, 0, 0, 0) - Timestamp::new(0, 59, 0, 0), Timestamp::new(0, 1, 0, 0));
+    }
+
     #[test]
     fn from_microseconds() {
         let t1 = Timestamp::new(0, 1, 1, 1);
@@ -444,4 +829,43 @@ mod timestamp_test {
         assert_eq!(t2, t3);
         assert_eq!(t1, t3);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn parse_flexible() {
+        let parse = |s: &str| Timestamp::parse_flexible(s).map(|time| time.total_miliseconds()).ok();
+        assert_eq!(parse("1h2m3s"), Some(3_723_000));
+        assert_eq!(parse("1h 30m"), Some(5_400_000));
+        assert_eq!(parse("1.5s"), Some(1500));
+        assert_eq!(parse("250ms"), Some(250));
+        assert_eq!(parse("90s"), Some(90_000));
+        assert_eq!(parse("90"), Some(90_000));
+        assert_eq!(parse("02:03.5"), Some(123_500));
+        assert_eq!(parse("1:02:03"), Some(3_723_000));
+        assert_eq!(parse(" 00:00:01,500 "), Some(1500));
+
+        for invalid in &["", "h", "1x", "-5s", "1:2:3:4", "1.5:30", "1..5", "1e3", "\u{6551}02:03.5"] {
+            assert_eq!(parse(invalid), None, "{:?}", invalid);
+        }
+    }
+
+    #[test]
+    fn checked() {
+        let max = Timestamp::new(u32::MAX, 0, 0, 0);
+        assert_eq!(max.checked_add(Timestamp::new(0, 59, 59, 999)), Some(Timestamp::new(u32::MAX, 59, 59, 999)));
+        assert_eq!(max.checked_add(Timestamp::new(1, 0, 0, 0)), None);
+        assert_eq!(max.checked_add(max), None);
+        assert_eq!(Timestamp::new(0, 0, 1, 0).checked_sub(Timestamp::new(0, 0, 0, 1)),
+                   Some(Timestamp::new(0, 0, 0, 999)));
+        assert_eq!(Timestamp::new(0, 0, 0, 1).checked_sub(Timestamp::new(0, 0, 1, 0)), None);
+        assert_eq!(Timestamp::checked_from_miliseconds(3_723_004), Some(Timestamp::new(1, 2, 3, 4)));
+        assert_eq!(Timestamp::checked_from_miliseconds(u64::MAX), None);
+    }
+
+    #[test]
+    #[cfg(feature = "strict-math")]
+    #[should_panic(expected = "attempt to add with overflow")]
+    fn strict_add() {
+        let max = Timestamp::new(u32::MAX, 0, 0, 0);
+        let _ = max + max;
+    }
+}
@@ -2,37 +2,178 @@ use std::fs::File;
 use std::path::Path;
 use std::io::{Error, Read};
 
-use regex::Regex;
+#[cfg(test)]
+use crate::subtitles::Subtitles;
+#[cfg(test)]
+use crate::subline::SubLine;
+#[cfg(test)]
+use crate::timestamp::Timestamp;
+
+/// Formats 128 bits as a version 4 UUID, overwriting its version and variant bits.
+pub fn uuid(high: u64, low: u64) -> String {
+    let high = (high & 0xffff_ffff_ffff_0fff) | 0x4000;
+    let low = (low & 0x3fff_ffff_ffff_ffff) | 0x8000_0000_0000_0000;
+    format!("{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+            high >> 32,
+            (high >> 16) & 0xffff,
+            high & 0xffff,
+            low >> 48,
+            low & 0xffff_ffff_ffff)
+}
 
 pub fn read_file<P: AsRef<Path>>(path: P) -> Result<String, Error> {
-    let mut file = try!(File::open(&path));
+    let mut file = File::open(&path)?;
     let mut content = String::new();
-    try!(file.read_to_string(&mut content));
+    file.read_to_string(&mut content)?;
     Ok(content)
 }
 
 pub fn prepare(content: &str) -> String {
-    let result = EOF_EMPTY_LINES.replacen(&content, 1, "\r\n\r\n");
-    UNIFY_NEWLINE_STYLE.replace_all(&result, "\r\n")
+    let result = format!("{}\r\n\r\n", content.trim_end());
+    result.replace("\r\n", "\n").replace('\n', "\r\n")
 }
 
 pub fn check(content: &str) -> bool {
-    SUBS.is_match(&content)
+    blocks(content).next().is_some()
+}
+
+/// Removes html-like (```<i>```) and ass-like (```{\an8}```) tags from text.
+pub fn strip_tags(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(['<', '{']) {
+        let closing = if rest[start..].starts_with('<') { '>' } else { '}' };
+        match rest[start..].find(closing) {
+            Some(end) => {
+                result.push_str(&rest[..start]);
+                rest = &rest[start + end + 1..];
+            }
+            None => {
+                result.push_str(&rest[..start + 1]);
+                rest = &rest[start + 1..];
+            }
+        }
+    }
+    result.push_str(rest);
+    result
 }
 
-lazy_static! {
-    pub static ref SUBS: Regex = Regex::new(r"(?x)
-        (\d+)
-        \r\n
-        (\d{2}):(\d{2}):(\d{2}),(\d{3})
-        \s-->\s
-        (\d{2}):(\d{2}):(\d{2}),(\d{3})
-        \r\n
-        ([\S\s]*?)
-        (?:\r\n){2}?").unwrap();
-
-    static ref EOF_EMPTY_LINES: Regex = Regex::new(r"\s*?\z").unwrap();
-    static ref UNIFY_NEWLINE_STYLE: Regex = Regex::new(r"(?:\r\n|\n)").unwrap();
+/// Splits ```HH:MM:SS,mmm``` (with two or more digits of hours) at the start of ```s```
+/// into the numbers and the rest, ```separator``` is the one before miliseconds.
+pub fn split_timestamp(s: &str, separator: char) -> Option<([&str; 4], &str)> {
+    let hours = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    if hours < 2 {
+        return None;
+    }
+    let mut parts = [&s[..hours]; 4];
+    let mut rest = &s[hours..];
+    for (i, &(prefix, len)) in [(':', 2), (':', 2), (separator, 3)].iter().enumerate() {
+        rest = rest.strip_prefix(prefix)?;
+        if rest.len() < len || !rest.as_bytes()[..len].iter().all(u8::is_ascii_digit) {
+            return None;
+        }
+        parts[i + 1] = &rest[..len];
+        rest = &rest[len..];
+    }
+    Some((parts, rest))
+}
+
+/// A line of prepared content, see ```blocks```.
+pub struct Block<'a> {
+    pub index: &'a str,
+    pub start: [&'a str; 4],
+    pub end: [&'a str; 4],
+    pub text: &'a str,
+}
+
+/// Lines of prepared content, i.e. with ```\r\n``` newlines and each line (including the last one)
+/// followed by an empty line. Text between lines that isn't a line is skipped.
+pub fn blocks(content: &str) -> Blocks<'_> {
+    Blocks {
+        content,
+        matched: 0,
+        position: 0,
+    }
+}
+
+/// Iterator returned by ```blocks```.
+pub struct Blocks<'a> {
+    content: &'a str,
+    /// End of the last line.
+    matched: usize,
+    position: usize,
+}
+
+impl<'a> Iterator for Blocks<'a> {
+    type Item = Block<'a>;
+
+    fn next(&mut self) -> Option<Block<'a>> {
+        let content = self.content;
+        while let Some(found) = content[self.position..].find("\r\n") {
+            let newline = self.position + found;
+            self.position = newline + 2;
+
+            let digits = content[self.matched..newline].trim_end_matches(|c: char| c.is_ascii_digit()).len();
+            let index = &content[self.matched + digits..newline];
+            if index.is_empty() {
+                continue;
+            }
+            let (start, rest) = match split_timestamp(&content[newline + 2..], ',') {
+                Some(timestamp) => timestamp,
+                None => continue,
+            };
+            let rest = match arrow(rest) {
+                Some(rest) => rest,
+                None => continue,
+            };
+            let (end, rest) = match split_timestamp(rest, ',') {
+                Some(timestamp) => timestamp,
+                None => continue,
+            };
+            let text = match rest.strip_prefix("\r\n") {
+                Some(text) => text,
+                None => continue,
+            };
+            let text_start = content.len() - text.len();
+            let text_end = text_start + text.find("\r\n\r\n")?;
+
+            self.matched = text_end + 4;
+            self.position = self.matched;
+            return Some(Block {
+                index,
+                start,
+                end,
+                text: &content[text_start..text_end],
+            });
+        }
+        None
+    }
+}
+
+/// Skips the arrow between timestamps of a line, surrounded by a single whitespace character.
+fn arrow(s: &str) -> Option<&str> {
+    let mut chars = s.chars();
+    if !chars.next()?.is_whitespace() {
+        return None;
+    }
+    let mut chars = chars.as_str().strip_prefix("-->")?.chars();
+    if !chars.next()?.is_whitespace() {
+        return None;
+    }
+    Some(chars.as_str())
+}
+
+/// Constructs ```Subtitles``` with a line for every given text.
+/// Every line lasts 1 second and is followed by 1 second gap.
+#[cfg(test)]
+pub fn subs_from_texts(texts: &[&str]) -> Subtitles {
+    let mut subs = Subtitles::default();
+    for (i, text) in texts.iter().enumerate() {
+        let start = Timestamp::new(0, 0, i as u32 * 2, 0);
+        let end = Timestamp::new(0, 0, i as u32 * 2 + 1, 0);
+        subs.try_push(SubLine::new(i as u32 + 1, text.to_string(), start, end)).unwrap();
+    }
+    subs
 }
 
 #[cfg(test)]
@@ -41,7 +182,7 @@ mod utils_tests {
 
     #[test]
     fn _check() {
-        println!("");
+        println!();
         let mut test_srt = r"1
 00:01:38,958 --> 00:01:49,609
 Firs line
@@ -72,25 +213,25 @@ Go away.
 
         test_srt = prepare(&test_srt);
         println!("after prepare: {:?}", test_srt);
-        let num_subs = SUBS.captures_iter(&test_srt).count();
+        let num_subs = blocks(&test_srt).count();
         assert_eq!(num_subs, 5);
         assert!(check(&test_srt));
 
         test_srt.pop();
-        let num_subs = SUBS.captures_iter(&test_srt).count();
+        let num_subs = blocks(&test_srt).count();
         assert_eq!(num_subs, 4);
     }
 
     #[test]
     fn _prepare() {
-        println!("");
+        println!();
         let test_srt = "1\n00:01:38,958 --> 00:01:49,609\nFirs line\n\n2\n00:04:19,604 --> 00:04:20,970\n<i>Your Grace.</i>\n\n".to_owned();
         println!("before prepare: {:?}", test_srt);
         assert!(!check(&test_srt));
 
         let mut prepaired_test_srt = prepare(&test_srt);
         println!("after prepare: {:?}", prepaired_test_srt);
-        let num_subs = SUBS.captures_iter(&prepaired_test_srt).count();
+        let num_subs = blocks(&prepaired_test_srt).count();
         assert_eq!(num_subs, 2);
         println!("{:?}", prepaired_test_srt);
         assert!(check(&prepaired_test_srt));
@@ -104,7 +245,7 @@ Go away.
         additional_test_srt = prepare(&additional_test_srt);
 
         prepaired_test_srt += &additional_test_srt;
-        let num_subs = SUBS.captures_iter(&prepaired_test_srt).count();
+        let num_subs = blocks(&prepaired_test_srt).count();
         assert_eq!(num_subs, 3);
     }
 